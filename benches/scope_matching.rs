@@ -0,0 +1,47 @@
+use auth_kit::auth::compiled_scope::CompiledScopes;
+use auth_kit::auth::scope::{authorize_with_matcher, FlexibleMatcher};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn scope_set(size: usize) -> Vec<String> {
+    (0..size)
+        .map(|i| match i % 4 {
+            0 => format!("service_{}:resource_{}:read", i, i),
+            1 => format!("service_{}:*:write", i),
+            2 => format!("service_{}.resource_{}", i, i),
+            _ => format!("action_{}:*", i),
+        })
+        .collect()
+}
+
+fn bench_scope_matching(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scope_matching");
+
+    for size in [10, 100, 1_000, 10_000] {
+        let scopes = scope_set(size);
+        let joined = scopes.join(" ");
+        let compiled = CompiledScopes::compile(&scopes);
+        let hit = format!("service_{}:resource_x:write", size - 3);
+        let miss = "unknown_service:resource:delete";
+
+        group.bench_with_input(BenchmarkId::new("flexible_matcher_hit", size), &joined, |b, joined| {
+            b.iter(|| authorize_with_matcher::<FlexibleMatcher>(black_box(joined), black_box(&hit)))
+        });
+        group.bench_with_input(BenchmarkId::new("compiled_scopes_hit", size), &compiled, |b, compiled| {
+            b.iter(|| compiled.matches(black_box(&hit)))
+        });
+        group.bench_with_input(BenchmarkId::new("flexible_matcher_miss", size), &joined, |b, joined| {
+            b.iter(|| authorize_with_matcher::<FlexibleMatcher>(black_box(joined), black_box(miss)))
+        });
+        group.bench_with_input(BenchmarkId::new("compiled_scopes_miss", size), &compiled, |b, compiled| {
+            b.iter(|| compiled.matches(black_box(miss)))
+        });
+        group.bench_with_input(BenchmarkId::new("compile", size), &scopes, |b, scopes| {
+            b.iter(|| CompiledScopes::compile(black_box(scopes)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_scope_matching);
+criterion_main!(benches);
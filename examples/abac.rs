@@ -12,11 +12,13 @@ fn main() -> Result<(), AuthError> {
          },
          department: "engineering".to_string(),
          clearance_level: 5,
+         ..Default::default()
      };
 
      let resource = Resource {
          department: "engineering".to_string(),
          required_level: 3,
+         ..Default::default()
      };
 
      let context = AuthContext {
@@ -31,12 +33,12 @@ fn main() -> Result<(), AuthError> {
             let result = auth.authorize(&context, "", "", None);
             match result {
                 Ok(_) => println!("Access granted via ABAC."),
-                Err(e) => println!("ABAC check failed: {}", e.to_string()),
+                Err(e) => println!("ABAC check failed: {}", e),
             }
 
         },
         Err(e) => {
-            println!("Error initializing Authorization: {}", e.to_string());
+            println!("Error initializing Authorization: {}", e);
         }
      }
 
@@ -30,11 +30,11 @@ fn main() -> Result<(), AuthError> {
             let result = auth.authorize(&context, "service", "create", None);
             match result {
                 Ok(_) => println!("Access granted via RBAC."),
-                Err(e) => println!("Access denied: {}", e.to_string()),
+                Err(e) => println!("Access denied: {}", e),
             }
         },
         Err(e) => {
-            println!("Error initializing Authorization: {}", e.to_string());
+            println!("Error initializing Authorization: {}", e);
         }
     }
 
@@ -7,11 +7,13 @@ fn main() -> Result<(), AuthError> {
         email: "jwt@example.com".to_string(),
         service: "admin_service".to_string(),
         scopes: vec!["admin_service.read".to_string(), "admin_service.create".to_string()],
+        ..Default::default()
     };
     
     let resource = Resource {
         department: "engineering".to_string(),
         required_level: 3,
+        ..Default::default()
     };
     
     let context = AuthContext {
@@ -26,11 +28,11 @@ fn main() -> Result<(), AuthError> {
             let result = auth.authorize(&context, "admin_service", "read", None);
             match result {
                 Ok(_) => println!("Access granted via SBA."),
-                Err(e) => println!("Access denied via SBA: {}", e.to_string()),
+                Err(e) => println!("Access denied via SBA: {}", e),
             }
         },
         Err(e) => {
-            println!("Error initializing Authorization: {}", e.to_string());
+            println!("Error initializing Authorization: {}", e);
         }
     }
 
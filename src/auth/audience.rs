@@ -0,0 +1,78 @@
+use crate::auth::scope::{GlobMatcher, ScopeMatcher};
+use crate::error::AuthError;
+use crate::model::Claims;
+
+/// Configures how the SBA strategy binds tokens to the service being accessed.
+///
+/// By default a token is only accepted by a service listed among its audiences
+/// (`Claims::service` and `Claims::audiences`). An engine can additionally accept
+/// audience patterns, such as `internal-*`, which use the [`GlobMatcher`] grammar.
+///
+/// # Example
+/// ```code
+/// let binding = AudienceBinding::default().accept("internal-*");
+/// let auth = Authorization::new("SBA")?.with_audience_binding(binding);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudienceBinding {
+    enforced: bool,
+    accepted: Vec<String>,
+}
+
+impl Default for AudienceBinding {
+    fn default() -> Self {
+        Self {
+            enforced: true,
+            accepted: Vec::new(),
+        }
+    }
+}
+
+impl AudienceBinding {
+    /// Returns a binding that does not check audiences at all.
+    ///
+    /// This restores the behaviour of earlier versions and should only be used
+    /// when tokens are known to be service-specific by other means.
+    pub fn disabled() -> Self {
+        Self {
+            enforced: false,
+            accepted: Vec::new(),
+        }
+    }
+
+    /// Accepts tokens whose audience matches `pattern`, in addition to the service itself.
+    pub fn accept(mut self, pattern: &str) -> Self {
+        self.accepted.push(pattern.to_string());
+        self
+    }
+
+    /// Returns `true` if audience binding is enforced.
+    pub fn is_enforced(&self) -> bool {
+        self.enforced
+    }
+
+    /// Checks that `claims` were issued for `service`.
+    ///
+    /// # Returns
+    /// * `Ok(())` if an audience equals `service` or matches an accepted pattern.
+    /// * `Err(AuthError::WrongAudience)` otherwise.
+    pub fn check(&self, claims: &Claims, service: &str) -> Result<(), AuthError> {
+        if !self.enforced {
+            return Ok(());
+        }
+
+        let audiences = claims.audiences();
+        let bound = audiences.iter().any(|aud| {
+            *aud == service || self.accepted.iter().any(|pattern| GlobMatcher::matches(pattern, aud))
+        });
+
+        if bound {
+            Ok(())
+        } else {
+            Err(AuthError::WrongAudience {
+                service: service.to_string(),
+                audiences: audiences.join(" "),
+            })
+        }
+    }
+}
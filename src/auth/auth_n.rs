@@ -1,15 +1,42 @@
-use crate::error::AuthError;
-use crate::model::{Role, User};
+use crate::auth::lockout::LoginThrottle;
+use crate::auth::password::{email_inputs, PasswordPolicy};
+use crate::clock::{Clock, SystemClock};
+use crate::error::{AuthError, PasswordViolation};
+use crate::mail::validate_email;
+use crate::mfa::recovery::{consume_recovery_code, generate_recovery_codes, RecoveryCodePolicy};
+use crate::model::{AccountStatus, Role, User};
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 
 /// A basic in-memory authentication service.
 ///
 /// This struct manages users, supports registration, login, and password reset
 /// with optional token verification.
-#[derive(Debug)]
 pub struct Authentication {
     /// A map of user email to `User` object.
     pub users: HashMap<String, User>,
+    throttle: Option<LoginThrottle>,
+    activation_required: bool,
+    password_policy: PasswordPolicy,
+    dummy_hash: OnceLock<String>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Authentication {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Authentication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authentication")
+            .field("users", &self.users)
+            .field("throttle", &self.throttle)
+            .field("activation_required", &self.activation_required)
+            .field("password_policy", &self.password_policy)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Authentication {
@@ -22,9 +49,70 @@ impl Authentication {
     pub fn new() -> Self {
         Self {
             users: HashMap::new(),
+            throttle: None,
+            activation_required: false,
+            password_policy: PasswordPolicy::default(),
+            dummy_hash: OnceLock::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Sets the policy applied by the plaintext password APIs (`register_with_password`,
+    /// `set_password` and `PasswordResets::reset_with_password`).
+    ///
+    /// # Example
+    /// ```code
+    /// let policy = PasswordPolicy { min_length: 12, banned_words: vec!["acme".to_string()], ..PasswordPolicy::default() };
+    /// let auth = Authentication::new().with_password_policy(policy);
+    /// ```
+    pub fn with_password_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.password_policy = policy;
+        self.dummy_hash = OnceLock::new();
+        self
+    }
+
+    /// Returns the password policy in use.
+    pub fn password_policy(&self) -> &PasswordPolicy {
+        &self.password_policy
+    }
+
+    /// Makes `register` create `Pending` accounts, which cannot log in until `activate` is called.
+    ///
+    /// # Example
+    /// ```code
+    /// let mut auth = Authentication::new().with_activation_required(true);
+    /// auth.register("user@example.com", &password_hash)?;
+    /// // after the user has confirmed their email address
+    /// auth.activate("user@example.com")?;
+    /// ```
+    pub fn with_activation_required(mut self, required: bool) -> Self {
+        self.activation_required = required;
+        self
+    }
+
+    /// Replaces the clock used to timestamp account status changes.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enables account lockout and login throttling.
+    ///
+    /// # Example
+    /// ```code
+    /// let throttle = LoginThrottle::new(Box::new(InMemoryAttemptStore::new()), LockoutPolicy::default());
+    /// let auth = Authentication::new().with_throttle(throttle);
+    /// ```
+    pub fn with_throttle(mut self, throttle: LoginThrottle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Returns the login throttle, if enabled.
+    pub fn throttle(&self) -> Option<&LoginThrottle> {
+        self.throttle.as_ref()
+    }
+
     /// Registers a new user by email and hashed password.
     ///
     /// # Arguments
@@ -33,6 +121,7 @@ impl Authentication {
     ///
     /// # Returns
     /// * `Ok(())` if registration was successful.
+    /// * `Err(AuthError::InvalidEmail)` if the email address is malformed.
     /// * `Err(AuthError::EmailAlreadyRegistered)` if the email is already in use.
     ///
     /// # Example
@@ -40,6 +129,16 @@ impl Authentication {
     /// auth.register("user@example.com", "hashed_password")?;
     /// ```
     pub fn register(&mut self, email: &str, password_hash: &str) -> Result<(), AuthError> {
+        let status = if self.activation_required { AccountStatus::Pending } else { AccountStatus::Active };
+        self.register_with_status(email, password_hash, status)
+    }
+
+    /// Registers a new user in the given initial state, usually `Pending` or `Active`.
+    ///
+    /// # Returns
+    /// * The results of `register`.
+    pub fn register_with_status(&mut self, email: &str, password_hash: &str, status: AccountStatus) -> Result<(), AuthError> {
+        validate_email(email)?;
         if self.users.contains_key(email) {
             return Err(AuthError::EmailAlreadyRegistered);
         }
@@ -50,19 +149,49 @@ impl Authentication {
             role: Role { name: "".to_string(), permissions: vec![] },
             department: "".to_string(),
             clearance_level: 0,
+            status,
+            status_changed_at: Some(self.clock.now()),
+            ..Default::default()
         };
 
         self.users.insert(email.to_string(), user);
         Ok(())
     }
 
+    /// Registers a new user with a plaintext password, enforcing the password policy.
+    ///
+    /// # Returns
+    /// * `Ok(())` if registration was successful.
+    /// * `Err(AuthError::PasswordRejected)` with every broken rule if the password is too weak.
+    /// * The errors of `register` otherwise.
+    ///
+    /// # Example
+    /// ```code
+    /// match auth.register_with_password("user@example.com", "hunter2") {
+    ///     Err(AuthError::PasswordRejected(violations)) => show_errors(&violations),
+    ///     other => other?,
+    /// }
+    /// ```
+    pub fn register_with_password(&mut self, email: &str, password: &str) -> Result<(), AuthError> {
+        validate_email(email)?;
+        if self.users.contains_key(email) {
+            return Err(AuthError::EmailAlreadyRegistered);
+        }
+        self.password_policy.check(password, &email_inputs(email))?;
+        let password_hash = hash_password_with_cost(password, self.password_policy.cost)?;
+        self.register(email, &password_hash)
+    }
+
     /// Attempts to log in a user by email.
     ///
     /// # Arguments
     /// * `email` - The email address to look up.
     ///
     /// # Returns
-    /// * `Ok(Some(User))` if the user exists.
+    /// * `Ok(Some(User))` if the user exists and is active.
+    /// * `Err(AuthError::AccountLocked)` if the account is locked by the login throttle.
+    /// * `Err(AuthError::AccountPending)`, `Err(AuthError::AccountDisabled)`,
+    ///   `Err(AuthError::AccountSuspended)` or `Err(AuthError::AccountDeleted)` if the account is not active.
     /// * `Err(AuthError::UserNotFound)` if the user does not exist.
     ///
     /// # Example
@@ -70,14 +199,173 @@ impl Authentication {
     /// let user = auth.login("user@example.com")?;
     /// ```
     pub fn login(&self, email: &str) -> Result<Option<User>, AuthError> {
+        if let Some(throttle) = &self.throttle
+            && let Some(until) = throttle.account(email)?.and_then(|r| r.locked_until)
+        {
+            return Err(AuthError::AccountLocked { until });
+        }
         match self.users.get(email) {
-            Some(user) => Ok(Some(user.clone())),
+            Some(user) => {
+                user.ensure_active()?;
+                Ok(Some(user.clone()))
+            }
             None => Err(AuthError::UserNotFound),
         }
     }
 
+    /// Logs in a user by email and plaintext password, counting failures against
+    /// the account and the client IP address when a throttle is enabled.
+    ///
+    /// Unknown emails fail exactly like wrong passwords: with the same error, after
+    /// the same bcrypt work against a dummy hash, and counted by the throttle, so
+    /// neither the response nor its timing reveals which accounts exist. Accounts
+    /// without a usable password hash, such as users provisioned through single
+    /// sign-on, are treated the same way. The account status is only checked once
+    /// the password has matched, so it is not disclosed to someone guessing passwords.
+    /// Deleted accounts have their password erased, so they fail like unknown emails.
+    ///
+    /// # Arguments
+    /// * `email` - The email address of the user.
+    /// * `password` - The plaintext password to verify.
+    /// * `ip_address` - The client IP address, if known.
+    ///
+    /// # Returns
+    /// * `Ok(User)` if the password matches.
+    /// * `Err(AuthError::InvalidPassword)` if the user does not exist or the password does not match.
+    /// * `Err(AuthError::AccountLocked)` if the account is locked.
+    /// * `Err(AuthError::TooManyAttempts)` if the account is backing off or the IP address is throttled.
+    /// * The status errors of `login` if the account is not active.
+    ///
+    /// # Example
+    /// ```code
+    /// let user = auth.login_with_password("user@example.com", "secret123", Some("203.0.113.7"))?;
+    /// ```
+    pub fn login_with_password(&mut self, email: &str, password: &str, ip_address: Option<&str>) -> Result<User, AuthError> {
+        if let Some(throttle) = &self.throttle {
+            throttle.check(email, ip_address)?;
+        }
+
+        let user = self.users.get(email).filter(|u| !u.password_hash.is_empty());
+        let result = match user.map(|u| (u, verify_password(password, &u.password_hash))) {
+            Some((user, Ok(true))) => Ok(user.clone()),
+            Some((_, Ok(false))) => Err(AuthError::InvalidPassword),
+            _ => {
+                // No usable hash: spend the same bcrypt work as a real account, then fail.
+                let _ = verify_password(password, self.dummy_hash());
+                Err(AuthError::InvalidPassword)
+            }
+        };
+
+        if let Some(throttle) = &mut self.throttle {
+            match &result {
+                Ok(_) => throttle.record_success(email)?,
+                Err(_) => {
+                    throttle.record_failure(email, ip_address)?;
+                }
+            }
+        }
+        let user = result?;
+        user.ensure_active()?;
+        Ok(user)
+    }
+
+    /// Activates a pending account, e.g. once its email address has been verified.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the account was pending.
+    /// * `Err(AuthError::InvalidStatusTransition)` if it was not.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    pub fn activate(&mut self, email: &str) -> Result<(), AuthError> {
+        let from = self.status(email)?;
+        if from != AccountStatus::Pending {
+            return Err(AuthError::InvalidStatusTransition { from, to: AccountStatus::Active });
+        }
+        self.set_status(email, AccountStatus::Active, None)
+    }
+
+    /// Disables an account (an administrator action). The account can be re-enabled with `enable`.
+    ///
+    /// OAuth2 tokens issued to the user stay valid until `AuthorizationServer::revoke_subject`
+    /// is called for them.
+    ///
+    /// # Example
+    /// ```code
+    /// auth.disable("user@example.com", Some("left the company"))?;
+    /// ```
+    pub fn disable(&mut self, email: &str, reason: Option<&str>) -> Result<(), AuthError> {
+        self.set_status(email, AccountStatus::Disabled, reason)
+    }
+
+    /// Re-enables a disabled or locked account (an administrator action).
+    ///
+    /// # Returns
+    /// * `Ok(())` if the account was disabled or locked.
+    /// * `Err(AuthError::InvalidStatusTransition)` otherwise; pending accounts are activated with `activate`.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    pub fn enable(&mut self, email: &str, reason: Option<&str>) -> Result<(), AuthError> {
+        let from = self.status(email)?;
+        if from == AccountStatus::Pending {
+            return Err(AuthError::InvalidStatusTransition { from, to: AccountStatus::Active });
+        }
+        self.set_status(email, AccountStatus::Active, reason)
+    }
+
+    /// Locks an account until an administrator re-enables it, e.g. after a suspected compromise.
+    pub fn lock(&mut self, email: &str, reason: Option<&str>) -> Result<(), AuthError> {
+        self.set_status(email, AccountStatus::Locked, reason)
+    }
+
+    /// Soft-deletes an account (an administrator action).
+    ///
+    /// The record is kept so the email cannot be registered again, but its password
+    /// hash and recovery codes are erased. Deletion cannot be undone. As with
+    /// `disable`, revoke the user's OAuth2 tokens with `AuthorizationServer::revoke_subject`.
+    pub fn delete(&mut self, email: &str, reason: Option<&str>) -> Result<(), AuthError> {
+        self.set_status(email, AccountStatus::Deleted, reason)?;
+        if let Some(user) = self.users.get_mut(email) {
+            user.password_hash.clear();
+            user.recovery_codes.clear();
+        }
+        Ok(())
+    }
+
+    /// Moves an account to another lifecycle state, enforcing the allowed transitions.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the transition is allowed.
+    /// * `Err(AuthError::InvalidStatusTransition)` if it is not.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    pub fn set_status(&mut self, email: &str, status: AccountStatus, reason: Option<&str>) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        let user = self.users.get_mut(email).ok_or(AuthError::UserNotFound)?;
+        user.transition(status, now, reason)
+    }
+
+    /// A hash of a fixed password at the policy's cost, verified in place of a
+    /// missing one so unknown accounts take as long to reject as real ones.
+    fn dummy_hash(&self) -> &str {
+        self.dummy_hash
+            .get_or_init(|| hash_password_with_cost("auth_kit-dummy-password", self.password_policy.cost).unwrap_or_default())
+    }
+
+    fn status(&self, email: &str) -> Result<AccountStatus, AuthError> {
+        Ok(self.users.get(email).ok_or(AuthError::UserNotFound)?.status)
+    }
+
+    /// Lifts a lockout on an account before it expires (an administrator action).
+    ///
+    /// Does nothing if no throttle is enabled.
+    pub fn unlock(&mut self, email: &str) -> Result<(), AuthError> {
+        match &mut self.throttle {
+            Some(throttle) => throttle.unlock(email),
+            None => Ok(()),
+        }
+    }
+
     /// Resets a user's password, validating a token before allowing the change.
     ///
+    /// This is for custom token schemes; `PasswordResets` issues and checks tokens itself.
+    ///
     /// # Arguments
     /// * `email` - The email address of the user.
     /// * `token` - The reset token to validate.
@@ -97,16 +385,139 @@ impl Authentication {
     where
         F: Fn(&str) -> bool,
     {
-        match self.users.get_mut(email) {
-            Some(user) => {
-                if !verify_token(token) {
-                    return Err(AuthError::InvalidToken);
-                }
+        if !self.users.contains_key(email) {
+            return Err(AuthError::UserNotFound);
+        }
+        if !verify_token(token) {
+            return Err(AuthError::InvalidToken);
+        }
+
+        self.store_password_hash(email, new_password_hash);
+        Ok(())
+    }
 
-                user.password_hash = new_password_hash.to_string();
-                Ok(())
+    /// Checks a new password for a user against the password policy and their password history.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the password may be used.
+    /// * `Err(AuthError::PasswordRejected)` with every broken rule otherwise.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    pub fn check_new_password(&self, email: &str, password: &str) -> Result<(), AuthError> {
+        let user = self.users.get(email).ok_or(AuthError::UserNotFound)?;
+        let mut violations = self.password_policy.violations(password, &email_inputs(email));
+
+        let previous = std::iter::once(&user.password_hash).chain(user.password_history.iter().take(self.password_policy.history_size));
+        for hash in previous.filter(|hash| !hash.is_empty()) {
+            if verify_password(password, hash).unwrap_or(false) {
+                violations.push(PasswordViolation::Reused { history: self.password_policy.history_size });
+                break;
             }
-            None => Err(AuthError::UserNotFound),
         }
+
+        if violations.is_empty() { Ok(()) } else { Err(AuthError::PasswordRejected(violations)) }
+    }
+
+    /// Sets a user's password from plaintext, enforcing the password policy and history.
+    ///
+    /// The previous hash is moved into the user's password history.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the password was changed.
+    /// * The errors of `check_new_password` otherwise.
+    ///
+    /// # Example
+    /// ```code
+    /// auth.set_password("user@example.com", "correct horse battery staple")?;
+    /// ```
+    pub fn set_password(&mut self, email: &str, password: &str) -> Result<(), AuthError> {
+        self.check_new_password(email, password)?;
+        let password_hash = hash_password_with_cost(password, self.password_policy.cost)?;
+        self.store_password_hash(email, &password_hash);
+        Ok(())
+    }
+
+    /// Replaces a user's password hash, keeping the previous one in the history.
+    pub(crate) fn store_password_hash(&mut self, email: &str, password_hash: &str) {
+        let history_size = self.password_policy.history_size;
+        if let Some(user) = self.users.get_mut(email) {
+            let previous = std::mem::replace(&mut user.password_hash, password_hash.to_string());
+            if !previous.is_empty() {
+                user.password_history.insert(0, previous);
+            }
+            user.password_history.truncate(history_size);
+        }
+    }
+
+    /// Replaces a user's recovery codes with a new batch, invalidating the old one.
+    ///
+    /// # Arguments
+    /// * `email` - The email address of the user.
+    /// * `policy` - How many codes to generate and how to hash them.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` with the plaintext codes, to be shown to the user once.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    ///
+    /// # Example
+    /// ```code
+    /// let codes = auth.regenerate_recovery_codes("user@example.com", &RecoveryCodePolicy::default())?;
+    /// ```
+    pub fn regenerate_recovery_codes(&mut self, email: &str, policy: &RecoveryCodePolicy) -> Result<Vec<String>, AuthError> {
+        let user = self.users.get_mut(email).ok_or(AuthError::UserNotFound)?;
+        let (codes, hashed) = generate_recovery_codes(policy)?;
+        user.recovery_codes = hashed;
+        Ok(codes)
+    }
+
+    /// Consumes one of a user's recovery codes.
+    ///
+    /// The code is checked and marked as used in a single call, so it cannot be
+    /// accepted twice.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the code was valid and unused.
+    /// * `Err(AuthError::RecoveryCodeUsed)` if the code was already used.
+    /// * `Err(AuthError::InvalidRecoveryCode)` if the code does not belong to the user.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    ///
+    /// # Example
+    /// ```code
+    /// auth.consume_recovery_code("user@example.com", "abcde-fghjk")?;
+    /// ```
+    pub fn consume_recovery_code(&mut self, email: &str, code: &str) -> Result<(), AuthError> {
+        let user = self.users.get_mut(email).ok_or(AuthError::UserNotFound)?;
+        consume_recovery_code(&mut user.recovery_codes, code)
     }
 }
+
+/// Hashes a plaintext password (or client secret) with bcrypt at the default cost.
+///
+/// # Returns
+/// * `Ok(String)` with the bcrypt hash.
+/// * `Err(AuthError::PasswordHashingFailed)` if hashing fails.
+///
+/// # Example
+/// ```code
+/// let password_hash = hash_password("secret123")?;
+/// auth.register("user@example.com", &password_hash)?;
+/// ```
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    hash_password_with_cost(password, bcrypt::DEFAULT_COST)
+}
+
+/// Hashes a plaintext password (or client secret) with bcrypt at the given cost.
+///
+/// Lower costs are only suitable for tests.
+pub fn hash_password_with_cost(password: &str, cost: u32) -> Result<String, AuthError> {
+    bcrypt::hash(password, cost).map_err(|e| AuthError::PasswordHashingFailed(e.to_string()))
+}
+
+/// Verifies a plaintext password (or client secret) against a bcrypt hash.
+///
+/// # Returns
+/// * `Ok(true)` if the password matches.
+/// * `Ok(false)` if it does not.
+/// * `Err(AuthError::PasswordHashingFailed)` if the hash is malformed.
+pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, AuthError> {
+    bcrypt::verify(password, password_hash).map_err(|e| AuthError::PasswordHashingFailed(e.to_string()))
+}
@@ -2,7 +2,10 @@
 ///
 /// The `Authorization` struct supports different models of access control and delegates the actual
 /// decision-making to the strategy selected at initialization.
-use crate::auth::scope::{authorize_with_matcher, FlexibleMatcher};
+use crate::auth::audience::AudienceBinding;
+use crate::auth::candidate::CandidateTemplates;
+use crate::auth::compiled_scope::CompiledScopes;
+use crate::auth::scope_graph::ScopeGraph;
 use crate::error::AuthError;
 use crate::model::{AuthContext, AuthStrategy, Identifiable, Resource};
 
@@ -12,6 +15,9 @@ use crate::model::{AuthContext, AuthStrategy, Identifiable, Resource};
 /// performs access checks based on the provided context and parameters.
 pub struct Authorization {
     strategy: AuthStrategy,
+    scope_graph: Option<ScopeGraph>,
+    candidates: CandidateTemplates,
+    audience: AudienceBinding,
 }
 
 impl Authorization {
@@ -30,7 +36,47 @@ impl Authorization {
     /// ```
     pub fn new(strategy: &str) -> Result<Self, AuthError> {
         let strategy = AuthStrategy::from_str(strategy)?;
-        Ok(Self { strategy })
+        Ok(Self {
+            strategy,
+            scope_graph: None,
+            candidates: CandidateTemplates::default(),
+            audience: AudienceBinding::default(),
+        })
+    }
+
+    /// Sets the scope implication graph used to expand claims under the SBA strategy.
+    ///
+    /// # Example
+    /// ```code
+    /// let mut graph = ScopeGraph::new();
+    /// graph.add_implication("admin_service.admin", "admin_service.read")?;
+    /// let auth = Authorization::new("SBA")?.with_scope_graph(graph);
+    /// ```
+    pub fn with_scope_graph(mut self, graph: ScopeGraph) -> Self {
+        self.scope_graph = Some(graph);
+        self
+    }
+
+    /// Sets the candidate scope templates used by the SBA strategy.
+    ///
+    /// # Example
+    /// ```code
+    /// let auth = Authorization::new("SBA")?.with_candidate_templates(CandidateTemplates::strict());
+    /// ```
+    pub fn with_candidate_templates(mut self, templates: CandidateTemplates) -> Self {
+        self.candidates = templates;
+        self
+    }
+
+    /// Sets the audience binding used by the SBA strategy.
+    ///
+    /// # Example
+    /// ```code
+    /// let auth = Authorization::new("SBA")?.with_audience_binding(AudienceBinding::default().accept("internal-*"));
+    /// ```
+    pub fn with_audience_binding(mut self, binding: AudienceBinding) -> Self {
+        self.audience = binding;
+        self
     }
 
     /// Authorizes access to a given service and permission using the selected strategy.
@@ -44,11 +90,16 @@ impl Authorization {
     /// # Returns
     /// * `Ok(())` if the access is granted.
     /// * `Err(AuthError)` if access is denied or required context is missing.
+    /// * The status errors of `User::ensure_active` if the context carries a user whose
+    ///   account is not active, whatever the strategy.
     ///
     /// # Behavior
     /// - **ABAC**: Compares user's department and clearance with resource requirements.
     /// - **RBAC**: Checks if the user's role contains the requested permission.
-    /// - **SBA**: Matches candidate scope strings using the user's claims and a flexible matcher.
+    /// - **SBA**: Checks that the token audience matches `service`, then matches candidate
+    ///   scope strings, rendered from the configured `CandidateTemplates`, using the user's
+    ///   claims and a flexible matcher. Claims are first expanded through the scope graph,
+    ///   if one is configured.
     pub fn authorize(
         &mut self,
         context: &AuthContext,
@@ -56,6 +107,10 @@ impl Authorization {
         permission: &str,
         delimiter: Option<&str>,
     ) -> Result<(), AuthError> {
+        if let Some(user) = &context.user {
+            user.ensure_active()?;
+        }
+
         match self.strategy {
             AuthStrategy::ABAC => {
                 let user = context.user.clone().ok_or(AuthError::MissingUser)?;
@@ -74,22 +129,22 @@ impl Authorization {
 
             AuthStrategy::SBA => {
                 let claims = context.claims.clone().ok_or(AuthError::MissingClaims)?;
+                self.audience.check(&claims, service)?;
                 let resource = context.resource.clone().unwrap_or_else(|| Resource {
                     department: "*".to_string(),
-                    required_level: 0,
+                    ..Resource::default()
                 });
                 let delim = delimiter.unwrap_or(".");
-                let candidates = vec![
-                    format!("{}{}{}{}{}", service, delim, resource.department, delim, permission),
-                    format!("{}{}{}", service, delim, permission),
-                    format!("{}", permission),
-                ];
-                let scopes = claims.scopes.join(" ");
+                let candidates = self.candidates.render(service, &resource, permission, delim);
+                let scopes = match &self.scope_graph {
+                    Some(graph) => CompiledScopes::compile(
+                        graph.expand(claims.scopes.iter().flat_map(|s| s.split_whitespace())),
+                    ),
+                    None => CompiledScopes::from_claims(&claims),
+                };
 
                 gen_authorize(&claims, service, permission, |_, _, _| {
-                    candidates.iter().any(|candidate| {
-                        authorize_with_matcher::<FlexibleMatcher>(&scopes, candidate)
-                    })
+                    candidates.iter().any(|candidate| scopes.matches(candidate))
                 })
             }
         }
@@ -0,0 +1,177 @@
+use crate::error::AuthError;
+use crate::model::Resource;
+
+/// Placeholders that may appear in a candidate scope template.
+const PLACEHOLDERS: [&str; 7] = [
+    "service",
+    "department",
+    "resource_type",
+    "resource_id",
+    "permission",
+    "action",
+    "delim",
+];
+
+/// An ordered list of templates used by the SBA strategy to build candidate scopes.
+///
+/// The first template is the primary, fully qualified scope. The following
+/// templates are fallback levels, tried in order, and can be limited or turned off.
+///
+/// # Placeholders
+/// - `{service}` - the service argument passed to `authorize`
+/// - `{department}` - the resource department
+/// - `{resource_type}` / `{resource_id}` - the optional resource type and id
+/// - `{permission}` / `{action}` - the permission argument passed to `authorize`
+/// - `{delim}` - the delimiter passed to `authorize` (default `"."`)
+///
+/// A candidate is skipped when one of its placeholders has no value, such as
+/// `{resource_id}` for a resource without an id.
+///
+/// # Example
+/// ```code
+/// let templates = CandidateTemplates::new([
+///     "{service}:{resource_type}:{resource_id}:{action}",
+///     "{service}:{resource_type}:{action}",
+/// ])?
+/// .fallback_levels(0);
+///
+/// let auth = Authorization::new("SBA")?.with_candidate_templates(templates);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateTemplates {
+    templates: Vec<String>,
+    fallback_levels: usize,
+}
+
+impl Default for CandidateTemplates {
+    /// Returns the legacy candidates: `service.department.permission`,
+    /// `service.permission` and the bare `permission`.
+    fn default() -> Self {
+        Self {
+            templates: vec![
+                "{service}{delim}{department}{delim}{permission}".to_string(),
+                "{service}{delim}{permission}".to_string(),
+                "{permission}".to_string(),
+            ],
+            fallback_levels: usize::MAX,
+        }
+    }
+}
+
+impl CandidateTemplates {
+    /// Creates a list of candidate templates, most specific first.
+    ///
+    /// # Returns
+    /// * `Ok(Self)` if every template is well formed.
+    /// * `Err(AuthError::InvalidTemplate)` if a template is empty, has an unclosed
+    ///   brace or uses an unknown placeholder.
+    pub fn new<I, S>(templates: I) -> Result<Self, AuthError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let templates: Vec<String> = templates.into_iter().map(|t| t.as_ref().to_string()).collect();
+        if templates.is_empty() {
+            return Err(AuthError::InvalidTemplate("no templates given".to_string()));
+        }
+        for template in &templates {
+            validate(template)?;
+        }
+
+        Ok(Self {
+            templates,
+            fallback_levels: usize::MAX,
+        })
+    }
+
+    /// Returns the default templates with fallback disabled, so only the fully
+    /// qualified `service.department.permission` scope is accepted.
+    pub fn strict() -> Self {
+        Self::default().fallback_levels(0)
+    }
+
+    /// Limits how many fallback templates are tried after the primary one.
+    ///
+    /// `0` turns fallback off entirely (strict mode).
+    pub fn fallback_levels(mut self, levels: usize) -> Self {
+        self.fallback_levels = levels;
+        self
+    }
+
+    /// Returns `true` if only the primary template is used.
+    pub fn is_strict(&self) -> bool {
+        self.fallback_levels == 0 || self.templates.len() == 1
+    }
+
+    /// Returns the templates that are currently enabled, most specific first.
+    pub fn active(&self) -> &[String] {
+        let count = self.fallback_levels.saturating_add(1).min(self.templates.len());
+        &self.templates[..count]
+    }
+
+    /// Renders the enabled templates into candidate scopes.
+    ///
+    /// # Arguments
+    /// * `service` - The target service.
+    /// * `resource` - The resource being accessed.
+    /// * `permission` - The requested permission or action.
+    /// * `delimiter` - The value substituted for `{delim}`.
+    pub fn render(&self, service: &str, resource: &Resource, permission: &str, delimiter: &str) -> Vec<String> {
+        self.active()
+            .iter()
+            .filter_map(|template| {
+                let mut rendered = String::with_capacity(template.len());
+                let mut rest = template.as_str();
+                while let Some(start) = rest.find('{') {
+                    rendered.push_str(&rest[..start]);
+                    let end = start + rest[start..].find('}')?;
+                    let value = match &rest[start + 1..end] {
+                        "service" => service,
+                        "department" => resource.department.as_str(),
+                        "resource_type" => resource.resource_type.as_deref()?,
+                        "resource_id" => resource.resource_id.as_deref()?,
+                        "permission" | "action" => permission,
+                        "delim" => delimiter,
+                        _ => return None,
+                    };
+                    if value.is_empty() {
+                        return None;
+                    }
+                    rendered.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                rendered.push_str(rest);
+                Some(rendered)
+            })
+            .collect()
+    }
+}
+
+fn validate(template: &str) -> Result<(), AuthError> {
+    if template.trim().is_empty() {
+        return Err(AuthError::InvalidTemplate("template is empty".to_string()));
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err(AuthError::InvalidTemplate(format!("unmatched '}}' in '{}'", template)));
+        }
+        let Some(len) = rest[start..].find('}') else {
+            return Err(AuthError::InvalidTemplate(format!("unclosed placeholder in '{}'", template)));
+        };
+        let name = &rest[start + 1..start + len];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(AuthError::InvalidTemplate(format!(
+                "unknown placeholder '{{{}}}' in '{}'",
+                name, template
+            )));
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    if rest.contains('}') {
+        return Err(AuthError::InvalidTemplate(format!("unmatched '}}' in '{}'", template)));
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::model::Claims;
+use std::collections::{HashMap, HashSet};
+
+/// Delimiters recognised by [`FlexibleMatcher`](crate::auth::scope::FlexibleMatcher),
+/// in the order the matcher tries them.
+const DELIMITERS: [&str; 4] = [":", ".", "@", "/"];
+
+/// A single node in the segment trie of a [`CompiledScopes`] index.
+#[derive(Debug, Default, Clone)]
+struct ScopeNode {
+    /// Children keyed on a literal segment.
+    children: HashMap<String, ScopeNode>,
+    /// Child reached through a `*` segment, which matches any single segment.
+    wildcard: Option<Box<ScopeNode>>,
+    /// `true` if a granted scope ends at this node.
+    terminal: bool,
+}
+
+impl ScopeNode {
+    fn insert(&mut self, parts: &[&str]) {
+        let mut node = self;
+        for part in parts {
+            node = if *part == "*" {
+                node.wildcard.get_or_insert_with(Box::default)
+            } else {
+                node.children.entry(part.to_string()).or_default()
+            };
+        }
+        node.terminal = true;
+    }
+
+    fn matches(&self, parts: &[&str]) -> bool {
+        match parts.split_first() {
+            None => self.terminal,
+            Some((head, rest)) => {
+                self.children.get(*head).is_some_and(|child| child.matches(rest))
+                    || self.wildcard.as_ref().is_some_and(|child| child.matches(rest))
+            }
+        }
+    }
+}
+
+/// A precompiled index of granted scopes for fast SBA matching.
+///
+/// `CompiledScopes` is built once per token or claims set and then answers
+/// [`matches`](Self::matches) queries with roughly one lookup per segment of
+/// the required scope, instead of splitting every granted scope on every check.
+///
+/// Internally it keeps one segment trie per supported delimiter, where `*`
+/// segments are stored as wildcard branches, plus an exact-match set and a flag
+/// for the global `*` scope.
+///
+/// The results are identical to
+/// [`authorize_with_matcher::<FlexibleMatcher>`](crate::auth::scope::authorize_with_matcher)
+/// over the same scopes.
+///
+/// # Example
+/// ```code
+/// let compiled = CompiledScopes::from_scope_string("read:* user_service:*:read");
+/// assert!(compiled.matches("read:users"));
+/// assert!(compiled.matches("user_service:user:read"));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CompiledScopes {
+    exact: HashSet<String>,
+    any: bool,
+    tries: [ScopeNode; 4],
+}
+
+impl CompiledScopes {
+    /// Compiles a list of granted scope tokens into an index.
+    ///
+    /// # Arguments
+    /// * `scopes` - The granted scope tokens (e.g. from a token's `scope` claim).
+    ///
+    /// # Example
+    /// ```code
+    /// let compiled = CompiledScopes::compile(["read:users", "write:*"]);
+    /// ```
+    pub fn compile<I, S>(scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut compiled = Self::default();
+        for scope in scopes {
+            compiled.insert(scope.as_ref());
+        }
+        compiled
+    }
+
+    /// Compiles a space-delimited scope string, as used by OAuth2.
+    ///
+    /// # Example
+    /// ```code
+    /// let compiled = CompiledScopes::from_scope_string("read:users write:posts");
+    /// ```
+    pub fn from_scope_string(scope_str: &str) -> Self {
+        Self::compile(scope_str.split_whitespace())
+    }
+
+    /// Compiles the scopes carried by a set of `Claims`.
+    pub fn from_claims(claims: &Claims) -> Self {
+        Self::compile(claims.scopes.iter().flat_map(|s| s.split_whitespace()))
+    }
+
+    /// Adds a single granted scope token to the index.
+    pub fn insert(&mut self, scope: &str) {
+        if scope == "*" {
+            self.any = true;
+        }
+        self.exact.insert(scope.to_string());
+
+        for (delim, trie) in DELIMITERS.iter().zip(self.tries.iter_mut()) {
+            let parts: Vec<&str> = scope.split(delim).collect();
+            if parts.len() >= 2 {
+                trie.insert(&parts);
+            }
+        }
+    }
+
+    /// Returns `true` if any compiled scope authorizes the `required_token`.
+    ///
+    /// # Arguments
+    /// * `required_token` - The scope required for the action (e.g. `"read:users"`).
+    pub fn matches(&self, required_token: &str) -> bool {
+        if self.any || self.exact.contains(required_token) {
+            return true;
+        }
+
+        DELIMITERS.iter().zip(self.tries.iter()).any(|(delim, trie)| {
+            let parts: Vec<&str> = required_token.split(delim).collect();
+            parts.len() >= 2 && trie.matches(&parts)
+        })
+    }
+
+    /// Returns the number of distinct scope tokens in the index.
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    /// Returns `true` if no scopes have been compiled.
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+}
@@ -0,0 +1,243 @@
+use crate::clock::{Clock, SystemClock};
+use crate::error::AuthError;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Thresholds and delays applied to failed logins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Failed logins after which an account is locked.
+    pub max_account_failures: u32,
+    /// How long an account stays locked, in seconds.
+    pub lockout_duration: u64,
+    /// Failed logins from one IP address after which the address is throttled.
+    pub max_ip_failures: u32,
+    /// How long a throttled IP address is refused, in seconds.
+    pub ip_lockout_duration: u64,
+    /// Delay after the first failure, doubled after each further failure, in seconds.
+    pub base_delay: u64,
+    /// Upper bound of the backoff delay, in seconds.
+    pub max_delay: u64,
+    /// Quiet period after which failures are forgotten, in seconds.
+    pub reset_after: u64,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_account_failures: 5,
+            lockout_duration: 15 * 60,
+            max_ip_failures: 50,
+            ip_lockout_duration: 15 * 60,
+            base_delay: 1,
+            max_delay: 60,
+            reset_after: 3600,
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// Returns the backoff delay after `failures` consecutive failures.
+    pub fn delay(&self, failures: u32) -> u64 {
+        match failures {
+            0 => 0,
+            n => self.base_delay.saturating_mul(1u64.checked_shl(n - 1).unwrap_or(u64::MAX)).min(self.max_delay),
+        }
+    }
+}
+
+/// What failed logins are counted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttemptKey {
+    /// An account, by identity (see `Identifiable`).
+    Account(String),
+    /// A client IP address.
+    Ip(String),
+}
+
+/// The failed logins counted against one key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttemptRecord {
+    /// Consecutive failures since the last success, lockout or reset.
+    pub failures: u32,
+    /// When the last failure happened, in seconds since the Unix epoch.
+    pub last_failure_at: u64,
+    /// When the current lock is lifted, if the key is locked.
+    pub locked_until: Option<u64>,
+}
+
+/// Persistence for failed-login counters.
+///
+/// Implementations can share counters across instances (e.g. in Redis or a database),
+/// so an attacker cannot spread attempts over several servers.
+pub trait AttemptStore: Send + Sync {
+    /// Finds the record of a key.
+    fn find(&self, key: &AttemptKey) -> Result<Option<AttemptRecord>, AuthError>;
+
+    /// Stores the record of a key.
+    fn save(&mut self, key: AttemptKey, record: AttemptRecord) -> Result<(), AuthError>;
+
+    /// Removes the record of a key.
+    fn remove(&mut self, key: &AttemptKey) -> Result<(), AuthError>;
+}
+
+/// An in-memory `AttemptStore`.
+#[derive(Debug, Default)]
+pub struct InMemoryAttemptStore {
+    records: HashMap<AttemptKey, AttemptRecord>,
+}
+
+impl InMemoryAttemptStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AttemptStore for InMemoryAttemptStore {
+    fn find(&self, key: &AttemptKey) -> Result<Option<AttemptRecord>, AuthError> {
+        Ok(self.records.get(key).copied())
+    }
+
+    fn save(&mut self, key: AttemptKey, record: AttemptRecord) -> Result<(), AuthError> {
+        self.records.insert(key, record);
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &AttemptKey) -> Result<(), AuthError> {
+        self.records.remove(key);
+        Ok(())
+    }
+}
+
+/// Counts failed logins per account and per client IP and refuses attempts
+/// while a key is locked or backing off.
+///
+/// Each failure against an account doubles the delay before the next attempt
+/// is accepted; after `max_account_failures` the account is locked for
+/// `lockout_duration`. Locks are lifted automatically once they expire, or
+/// early by an administrator with `unlock`.
+///
+/// # Example
+/// ```code
+/// let throttle = LoginThrottle::new(Box::new(InMemoryAttemptStore::new()), LockoutPolicy::default());
+/// let mut auth = Authentication::new().with_throttle(throttle);
+/// let user = auth.login_with_password("user@example.com", "secret123", Some("203.0.113.7"))?;
+/// ```
+pub struct LoginThrottle {
+    store: Box<dyn AttemptStore>,
+    policy: LockoutPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl std::fmt::Debug for LoginThrottle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoginThrottle").field("policy", &self.policy).finish_non_exhaustive()
+    }
+}
+
+impl LoginThrottle {
+    /// Creates a throttle over the given store and policy.
+    pub fn new(store: Box<dyn AttemptStore>, policy: LockoutPolicy) -> Self {
+        Self {
+            store,
+            policy,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for delays and lock expiry.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the thresholds and delays in use.
+    pub fn policy(&self) -> LockoutPolicy {
+        self.policy
+    }
+
+    /// Checks whether a login attempt may proceed.
+    ///
+    /// # Arguments
+    /// * `subject` - The account being logged into.
+    /// * `ip_address` - The client IP address, if known.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the attempt may proceed.
+    /// * `Err(AuthError::AccountLocked)` if the account is locked.
+    /// * `Err(AuthError::TooManyAttempts)` if the account is backing off or the IP address is throttled.
+    pub fn check(&self, subject: &str, ip_address: Option<&str>) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        if let Some(ip) = ip_address
+            && let Some(retry_at) = self.current(&AttemptKey::Ip(ip.to_string()), now)?.and_then(|r| r.locked_until)
+        {
+            return Err(AuthError::TooManyAttempts { retry_at });
+        }
+        if let Some(record) = self.current(&AttemptKey::Account(subject.to_string()), now)? {
+            if let Some(until) = record.locked_until {
+                return Err(AuthError::AccountLocked { until });
+            }
+            let retry_at = record.last_failure_at + self.policy.delay(record.failures);
+            if retry_at > now {
+                return Err(AuthError::TooManyAttempts { retry_at });
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a failed login, locking the account or IP address once its threshold is reached.
+    ///
+    /// # Returns
+    /// * `Ok(AttemptRecord)` with the account's updated record.
+    pub fn record_failure(&mut self, subject: &str, ip_address: Option<&str>) -> Result<AttemptRecord, AuthError> {
+        if let Some(ip) = ip_address {
+            self.fail(AttemptKey::Ip(ip.to_string()), self.policy.max_ip_failures, self.policy.ip_lockout_duration)?;
+        }
+        self.fail(AttemptKey::Account(subject.to_string()), self.policy.max_account_failures, self.policy.lockout_duration)
+    }
+
+    /// Records a successful login, clearing the account's failures.
+    ///
+    /// IP counters are left untouched, so an attacker holding one valid account
+    /// cannot use it to reset the throttle on their address.
+    pub fn record_success(&mut self, subject: &str) -> Result<(), AuthError> {
+        self.store.remove(&AttemptKey::Account(subject.to_string()))
+    }
+
+    /// Returns the current record of an account, if it has recent failures.
+    pub fn account(&self, subject: &str) -> Result<Option<AttemptRecord>, AuthError> {
+        self.current(&AttemptKey::Account(subject.to_string()), self.clock.now())
+    }
+
+    /// Lifts an account lock and clears its failures (an administrator action).
+    pub fn unlock(&mut self, subject: &str) -> Result<(), AuthError> {
+        self.store.remove(&AttemptKey::Account(subject.to_string()))
+    }
+
+    /// Lifts the throttle on an IP address and clears its failures.
+    pub fn unlock_ip(&mut self, ip_address: &str) -> Result<(), AuthError> {
+        self.store.remove(&AttemptKey::Ip(ip_address.to_string()))
+    }
+
+    /// Returns the record of a key as it stands at `now`: expired locks and
+    /// failures older than `reset_after` are treated as cleared.
+    fn current(&self, key: &AttemptKey, now: u64) -> Result<Option<AttemptRecord>, AuthError> {
+        Ok(self.store.find(key)?.filter(|r| match r.locked_until {
+            Some(until) => until > now,
+            None => now < r.last_failure_at + self.policy.reset_after,
+        }))
+    }
+
+    fn fail(&mut self, key: AttemptKey, max_failures: u32, lockout_duration: u64) -> Result<AttemptRecord, AuthError> {
+        let now = self.clock.now();
+        let mut record = self.current(&key, now)?.unwrap_or_default();
+        record.failures += 1;
+        record.last_failure_at = now;
+        if record.failures >= max_failures && record.locked_until.is_none() {
+            record.locked_until = Some(now + lockout_duration);
+        }
+        self.store.save(key, record)?;
+        Ok(record)
+    }
+}
@@ -1,8 +1,44 @@
 /// Handles authentication logic such as register, login, and reset_password.
 pub mod auth_n;
 
+/// Provides server-side sessions for logged-in users.
+pub mod session;
+
+/// Provides account lockout and login throttling.
+pub mod lockout;
+
+/// Provides email address verification for new accounts.
+pub mod verification;
+
+/// Provides issuance and redemption of password reset tokens.
+pub mod reset;
+
+/// Provides passwordless login through magic links and email codes.
+pub mod passwordless;
+
+/// Provides password strength policies and entropy estimation.
+pub mod password;
+
 /// Handles authorization strategies such as RBAC, ABAC, and SBA.
 pub mod auth_z;
 
 /// Provides utilities for flexible scope matching and parsing.
 pub mod scope;
+
+/// Provides a precompiled scope index for fast scope matching.
+pub mod compiled_scope;
+
+/// Provides a strict, single-delimiter scope grammar with validation.
+pub mod strict_scope;
+
+/// Provides a scope implication graph used to expand granted scopes.
+pub mod scope_graph;
+
+/// Provides scope set algebra such as subset checks, intersection and union.
+pub mod scope_set;
+
+/// Provides configurable candidate scope templates for the SBA strategy.
+pub mod candidate;
+
+/// Provides audience (service) binding checks for SBA tokens.
+pub mod audience;
@@ -0,0 +1,233 @@
+use crate::error::{AuthError, PasswordViolation};
+
+/// Frequently used passwords and password fragments, most common first.
+///
+/// Matches against this list are cheap for an attacker, so they add little to
+/// the entropy estimate.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "123456", "qwerty", "admin", "welcome", "letmein", "login", "abc123", "iloveyou", "monkey",
+    "dragon", "master", "sunshine", "princess", "football", "baseball", "shadow", "superman", "trustno1", "secret",
+    "hello", "freedom", "whatever", "starwars", "changeme", "default", "access", "computer", "internet", "summer",
+    "winter", "spring", "autumn", "michael", "charlie", "jordan", "hunter", "killer", "pepper", "cheese",
+    "flower", "soccer", "hockey", "batman", "thomas", "ginger", "love", "test", "user", "root", "guest", "pass",
+];
+
+/// Keyboard rows walked by patterns such as `qwerty` or `asdf`.
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Rules a new password must satisfy.
+///
+/// The defaults follow current guidance: a minimum length and an entropy floor
+/// rather than mandatory character classes, which can be switched on if needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPolicy {
+    /// Minimum number of characters.
+    pub min_length: usize,
+    /// Maximum length in UTF-8 bytes rather than characters, since bcrypt ignores
+    /// bytes beyond 72; should not exceed that.
+    pub max_length: usize,
+    /// Whether a lowercase letter is required.
+    pub require_lowercase: bool,
+    /// Whether an uppercase letter is required.
+    pub require_uppercase: bool,
+    /// Whether a digit is required.
+    pub require_digit: bool,
+    /// Whether a symbol (anything but letters and digits) is required.
+    pub require_symbol: bool,
+    /// Words the password must not contain, compared case-insensitively and with
+    /// common character substitutions (`p4ssw0rd`) undone.
+    pub banned_words: Vec<String>,
+    /// Number of previous passwords that may not be reused, besides the current one.
+    pub history_size: usize,
+    /// Minimum estimated entropy, in bits (see `estimate_entropy`).
+    pub min_entropy_bits: u32,
+    /// bcrypt cost used to hash accepted passwords.
+    pub cost: u32,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 10,
+            max_length: 72,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            banned_words: vec![],
+            history_size: 5,
+            min_entropy_bits: 40,
+            cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Returns every rule `password` breaks, ignoring password history.
+    ///
+    /// # Arguments
+    /// * `password` - The plaintext password.
+    /// * `user_inputs` - Words tied to the user, such as parts of their email address,
+    ///   which are banned and count as easy to guess.
+    pub fn violations(&self, password: &str, user_inputs: &[&str]) -> Vec<PasswordViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+        if length < self.min_length {
+            violations.push(PasswordViolation::TooShort { min: self.min_length, found: length });
+        }
+        if password.len() > self.max_length {
+            violations.push(PasswordViolation::TooLong { max: self.max_length, found: password.len() });
+        }
+
+        let classes = [
+            (self.require_lowercase, char::is_lowercase as fn(char) -> bool, PasswordViolation::MissingLowercase),
+            (self.require_uppercase, char::is_uppercase, PasswordViolation::MissingUppercase),
+            (self.require_digit, |c: char| c.is_ascii_digit(), PasswordViolation::MissingDigit),
+            (self.require_symbol, |c: char| !c.is_alphanumeric(), PasswordViolation::MissingSymbol),
+        ];
+        for (required, matches, violation) in classes {
+            if required && !password.chars().any(matches) {
+                violations.push(violation);
+            }
+        }
+
+        let normalized = normalize(password);
+        let banned = self.banned_words.iter().map(String::as_str).chain(user_inputs.iter().copied());
+        for word in banned {
+            let word_normalized = normalize(word);
+            if word_normalized.chars().count() >= 3 && normalized.contains(&word_normalized) {
+                violations.push(PasswordViolation::BannedWord(word.to_string()));
+            }
+        }
+
+        let mut dictionary: Vec<&str> = self.banned_words.iter().map(String::as_str).collect();
+        dictionary.extend(user_inputs);
+        let bits = estimate_entropy(password, &dictionary).floor() as u32;
+        if bits < self.min_entropy_bits {
+            violations.push(PasswordViolation::TooWeak { bits, min_bits: self.min_entropy_bits });
+        }
+        violations
+    }
+
+    /// Checks `password` against the policy, ignoring password history.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the password satisfies every rule.
+    /// * `Err(AuthError::PasswordRejected)` with every broken rule otherwise.
+    ///
+    /// # Example
+    /// ```code
+    /// PasswordPolicy::default().check("correct horse battery staple", &["alice"])?;
+    /// ```
+    pub fn check(&self, password: &str, user_inputs: &[&str]) -> Result<(), AuthError> {
+        let violations = self.violations(password, user_inputs);
+        if violations.is_empty() { Ok(()) } else { Err(AuthError::PasswordRejected(violations)) }
+    }
+}
+
+/// Returns the words of an email address that a password should not contain:
+/// the parts of the local part, split on punctuation.
+pub fn email_inputs(email: &str) -> Vec<&str> {
+    let local = email.split('@').next().unwrap_or_default();
+    local.split(|c: char| !c.is_alphanumeric()).filter(|part| part.len() >= 3).collect()
+}
+
+/// Estimates how many bits of entropy a password has against a guessing attack,
+/// in the spirit of zxcvbn.
+///
+/// The password is split into the cheapest sequence of patterns an attacker would
+/// try: common passwords and the given `dictionary` words (also with capitals or
+/// `l33t` substitutions), repeated characters, alphabetic or numeric sequences, and
+/// keyboard walks. Characters outside any pattern are charged by character class.
+///
+/// # Example
+/// ```code
+/// assert!(estimate_entropy("password123", &[]) < 10.0);
+/// assert!(estimate_entropy("correct horse battery staple", &[]) > 60.0);
+/// ```
+pub fn estimate_entropy(password: &str, dictionary: &[&str]) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let normalized: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+    let dictionary: Vec<(Vec<char>, String)> = COMMON_PASSWORDS
+        .iter()
+        .copied()
+        .chain(dictionary.iter().copied())
+        .map(|word| (normalize(word).chars().collect::<Vec<char>>(), word.to_lowercase()))
+        .filter(|(word, _)| word.len() >= 3)
+        .collect();
+
+    // best[i] is the cheapest encoding of the first i characters.
+    let mut best = vec![f64::INFINITY; chars.len() + 1];
+    best[0] = 0.0;
+    for start in 0..chars.len() {
+        let base = best[start];
+        if !base.is_finite() {
+            continue;
+        }
+        let mut relax = |end: usize, bits: f64| {
+            if base + bits < best[end] {
+                best[end] = base + bits;
+            }
+        };
+
+        relax(start + 1, class_size(chars[start]).log2());
+
+        for (rank, (word, original)) in dictionary.iter().enumerate() {
+            let end = start + word.len();
+            if end <= chars.len() && normalized[start..end] == word[..] {
+                let rank = if rank < COMMON_PASSWORDS.len() { rank + 1 } else { 2 };
+                let capitalized = chars[start..end].iter().any(|c| c.is_uppercase());
+                let substituted = !lower[start..end].iter().copied().eq(original.chars());
+                relax(end, (rank as f64).log2() + capitalized as u8 as f64 + substituted as u8 as f64);
+            }
+        }
+
+        let run = |step: fn(char, char) -> bool| {
+            (start + 1..chars.len()).take_while(|&i| step(lower[i - 1], lower[i])).count() + 1
+        };
+        let repeated = run(|a, b| a == b);
+        let ascending = run(|a, b| b as u32 == a as u32 + 1);
+        let descending = run(|a, b| a as u32 == b as u32 + 1);
+        let keyboard = (start + 1..chars.len())
+            .take_while(|&i| KEYBOARD_ROWS.iter().any(|row| row.contains(&format!("{}{}", lower[i - 1], lower[i]))))
+            .count()
+            + 1;
+        let class = class_size(chars[start]).log2();
+        for (length, extra) in [(repeated, 0.0), (ascending, 1.0), (descending, 1.0), (keyboard, (KEYBOARD_ROWS.len() as f64).log2())] {
+            for end in start + 3..=start + length {
+                relax(end, class + ((end - start) as f64).log2() + extra);
+            }
+        }
+    }
+    best[chars.len()]
+}
+
+/// Lowercases `word` and undoes common character substitutions.
+fn normalize(word: &str) -> String {
+    word.chars().map(|c| unleet(c.to_ascii_lowercase())).collect()
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+fn class_size(c: char) -> f64 {
+    if c.is_ascii_digit() {
+        10.0
+    } else if c.is_ascii_lowercase() || c.is_ascii_uppercase() {
+        26.0
+    } else if c.is_ascii() {
+        33.0
+    } else {
+        100.0
+    }
+}
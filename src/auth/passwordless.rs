@@ -0,0 +1,246 @@
+use crate::auth::auth_n::Authentication;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{constant_time_eq, hash_token, random_string, random_token};
+use crate::error::AuthError;
+use crate::mail::{Email, Mailer, SendLimiter};
+use crate::model::User;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Settings of passwordless login.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordlessPolicy {
+    /// How long a magic link stays valid, in seconds.
+    pub link_ttl: u64,
+    /// How long an email code stays valid, in seconds.
+    pub code_ttl: u64,
+    /// Number of digits in an email code.
+    pub code_length: usize,
+    /// Failed attempts after which a pending login is discarded.
+    pub max_attempts: u32,
+    /// Minimum time between two login emails to the same address, in seconds.
+    pub resend_interval: u64,
+    /// Maximum number of login emails to the same address per `send_window`.
+    pub max_sends: usize,
+    /// The period `max_sends` applies to, in seconds.
+    pub send_window: u64,
+    /// The magic link sent to the user; `{token}` is replaced with the login token.
+    pub login_url: String,
+    /// The subject line of magic link emails.
+    pub link_subject: String,
+    /// The subject line of email code emails.
+    pub code_subject: String,
+}
+
+impl Default for PasswordlessPolicy {
+    fn default() -> Self {
+        Self {
+            link_ttl: 15 * 60,
+            code_ttl: 10 * 60,
+            code_length: 6,
+            max_attempts: 5,
+            resend_interval: 60,
+            max_sends: 5,
+            send_window: 3600,
+            login_url: "https://localhost/login/magic?token={token}".to_string(),
+            link_subject: "Your sign-in link".to_string(),
+            code_subject: "Your sign-in code".to_string(),
+        }
+    }
+}
+
+/// How a passwordless login is completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordlessMethod {
+    /// The user follows a link containing a single-use token.
+    MagicLink,
+    /// The user types a numeric code into the page that requested it.
+    EmailCode,
+}
+
+struct PendingLogin {
+    method: PasswordlessMethod,
+    secret_hash: String,
+    binding_hash: String,
+    expires_at: u64,
+    attempts: u32,
+}
+
+/// Passwordless login through magic links or one-time codes sent by email.
+///
+/// Each login is bound to the browser or session that requested it: the caller
+/// passes a binding value (e.g. a random cookie or the pre-login session id) when
+/// starting and completing the login, so a link or code intercepted elsewhere is
+/// useless. Only hashes of tokens, codes and bindings are kept; each user has at
+/// most one pending login, and requesting a new one discards the previous one.
+/// Login emails to an address are rate-limited, which also bounds how many codes
+/// can be guessed, since every new login only gets `max_attempts` guesses.
+///
+/// # Example
+/// ```code
+/// let mut passwordless = Passwordless::new(Box::new(mailer), PasswordlessPolicy::default());
+/// passwordless.start(&auth, "user@example.com", PasswordlessMethod::EmailCode, &browser_cookie)?;
+/// // when the user submits the code from the same browser
+/// let user = passwordless.complete_code(&auth, "user@example.com", "123456", &browser_cookie)?;
+/// ```
+pub struct Passwordless {
+    policy: PasswordlessPolicy,
+    mailer: Box<dyn Mailer>,
+    pending: HashMap<String, PendingLogin>,
+    sends: SendLimiter,
+    clock: Arc<dyn Clock>,
+}
+
+impl Passwordless {
+    /// Creates a passwordless login service delivering emails through `mailer`.
+    pub fn new(mailer: Box<dyn Mailer>, policy: PasswordlessPolicy) -> Self {
+        Self {
+            sends: SendLimiter::new(policy.resend_interval, policy.max_sends, policy.send_window),
+            policy,
+            mailer,
+            pending: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for expiry.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the settings in use.
+    pub fn policy(&self) -> &PasswordlessPolicy {
+        &self.policy
+    }
+
+    /// Starts a passwordless login and emails the link or code to the user.
+    ///
+    /// Unknown addresses and accounts that cannot log in get the same `Ok(())` as
+    /// real ones, but no email, so callers must answer every request the same way
+    /// (e.g. "if an account exists, we have sent you an email"). The rate limit is
+    /// applied before the account is looked up, so it cannot tell them apart either.
+    ///
+    /// # Arguments
+    /// * `auth` - The authentication service holding the user.
+    /// * `email` - The email address of the user.
+    /// * `method` - Whether to send a magic link or a code.
+    /// * `binding` - A value tied to the requesting browser or session, presented again to complete the login.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the email was sent, or if there is no active account to send it to.
+    /// * `Err(AuthError::TooManyAttempts)` if an email was sent to the address less than
+    ///   `resend_interval` ago or it has reached `max_sends` in the current window.
+    /// * `Err(AuthError::MailDeliveryFailed)` if the email could not be sent.
+    pub fn start(&mut self, auth: &Authentication, email: &str, method: PasswordlessMethod, binding: &str) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        self.sends.record(email, now)?;
+        if !matches!(auth.login(email), Ok(Some(_))) {
+            return Ok(());
+        }
+
+        let (secret, ttl, message) = match method {
+            PasswordlessMethod::MagicLink => {
+                let token = random_token(32);
+                let link = self.policy.login_url.replace("{token}", &token);
+                let body = format!(
+                    "Sign in by opening this link in the browser you requested it from:\n\n{}\n\nThe link expires in {} minutes and can be used once. If you did not request it, ignore this email.",
+                    link,
+                    self.policy.link_ttl / 60
+                );
+                (token, self.policy.link_ttl, (self.policy.link_subject.clone(), body))
+            }
+            PasswordlessMethod::EmailCode => {
+                let code = random_string(b"0123456789", self.policy.code_length);
+                let body = format!(
+                    "Your sign-in code is:\n\n{}\n\nThe code expires in {} minutes. If you did not request it, ignore this email.",
+                    code,
+                    self.policy.code_ttl / 60
+                );
+                (code, self.policy.code_ttl, (self.policy.code_subject.clone(), body))
+            }
+        };
+
+        self.pending.insert(
+            email.to_string(),
+            PendingLogin {
+                method,
+                secret_hash: hash_token(&secret),
+                binding_hash: hash_token(binding),
+                expires_at: now + ttl,
+                attempts: 0,
+            },
+        );
+        let (subject, body) = message;
+        self.mailer.send(&Email { to: email.to_string(), subject, body })
+    }
+
+    /// Completes a magic-link login.
+    ///
+    /// # Returns
+    /// * `Ok(User)` as `Authentication::login` would return it.
+    /// * `Err(AuthError::LoginBindingMismatch)` if the link was opened from another browser or session.
+    /// * `Err(AuthError::InvalidToken)` if the token is unknown, expired or already used.
+    /// * The errors of `Authentication::login` if the user can no longer log in.
+    pub fn complete_link(&mut self, auth: &Authentication, token: &str, binding: &str) -> Result<User, AuthError> {
+        let token_hash = hash_token(token);
+        let email = self
+            .pending
+            .iter()
+            .find(|(_, p)| p.method == PasswordlessMethod::MagicLink && constant_time_eq(p.secret_hash.as_bytes(), token_hash.as_bytes()))
+            .map(|(email, _)| email.clone())
+            .ok_or(AuthError::InvalidToken)?;
+        self.complete(auth, &email, PasswordlessMethod::MagicLink, token, binding)
+    }
+
+    /// Completes an email-code login.
+    ///
+    /// # Returns
+    /// * `Ok(User)` as `Authentication::login` would return it.
+    /// * `Err(AuthError::InvalidOtp)` if the code is wrong; after `max_attempts` failures the
+    ///   pending login is discarded.
+    /// * `Err(AuthError::LoginBindingMismatch)` if the code was submitted from another browser or
+    ///   session; this does not count as a failed attempt.
+    /// * `Err(AuthError::InvalidToken)` if there is no pending login or it has expired.
+    /// * The errors of `Authentication::login` if the user can no longer log in.
+    pub fn complete_code(&mut self, auth: &Authentication, email: &str, code: &str, binding: &str) -> Result<User, AuthError> {
+        self.complete(auth, email, PasswordlessMethod::EmailCode, code.trim(), binding)
+    }
+
+    /// Removes expired pending logins.
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.pending.retain(|_, p| p.expires_at > now);
+        self.sends.purge_expired(now);
+    }
+
+    fn complete(&mut self, auth: &Authentication, email: &str, method: PasswordlessMethod, secret: &str, binding: &str) -> Result<User, AuthError> {
+        let now = self.clock.now();
+        let pending = self
+            .pending
+            .get_mut(email)
+            .filter(|p| p.method == method)
+            .ok_or(AuthError::InvalidToken)?;
+        if pending.expires_at <= now {
+            self.pending.remove(email);
+            return Err(AuthError::InvalidToken);
+        }
+
+        // A mismatch says nothing about the secret, so it does not use up a guess;
+        // otherwise anyone knowing the address could cancel the user's login.
+        if !constant_time_eq(pending.binding_hash.as_bytes(), hash_token(binding).as_bytes()) {
+            return Err(AuthError::LoginBindingMismatch);
+        }
+        if !constant_time_eq(pending.secret_hash.as_bytes(), hash_token(secret).as_bytes()) {
+            pending.attempts += 1;
+            if pending.attempts >= self.policy.max_attempts {
+                self.pending.remove(email);
+            }
+            return Err(AuthError::InvalidOtp);
+        }
+
+        self.pending.remove(email);
+        self.sends.reset(email);
+        auth.login(email)?.ok_or(AuthError::UserNotFound)
+    }
+}
@@ -0,0 +1,246 @@
+use crate::auth::auth_n::{hash_password_with_cost, Authentication};
+use crate::auth::session::SessionManager;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{constant_time_eq, hash_token, random_token};
+use crate::error::AuthError;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Settings of password reset tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetPolicy {
+    /// How long a reset token stays valid, in seconds.
+    pub token_ttl: u64,
+    /// Whether a successful reset ends every session of the user.
+    pub revoke_sessions: bool,
+}
+
+impl Default for ResetPolicy {
+    fn default() -> Self {
+        Self {
+            token_ttl: 15 * 60,
+            revoke_sessions: true,
+        }
+    }
+}
+
+/// A stored reset token. Only the hash of the token is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResetToken {
+    /// SHA-256 hash of the token.
+    pub token_hash: String,
+    /// The user identity (see `Identifiable`) the token was issued to.
+    pub subject: String,
+    /// SHA-256 hash of the password hash at issuance, so the token stops working
+    /// once the password changes by any other means.
+    pub password_fingerprint: String,
+    /// When the token expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+/// Persistence for reset tokens.
+///
+/// Implementations can share tokens across instances (e.g. in Redis or a database).
+pub trait ResetTokenStore: Send + Sync {
+    /// Stores a token.
+    fn save(&mut self, token: ResetToken) -> Result<(), AuthError>;
+
+    /// Finds a token by hash.
+    fn find(&self, token_hash: &str) -> Result<Option<ResetToken>, AuthError>;
+
+    /// Removes a token, returning it if it existed.
+    fn remove(&mut self, token_hash: &str) -> Result<Option<ResetToken>, AuthError>;
+
+    /// Removes every token of a subject.
+    fn remove_subject(&mut self, subject: &str) -> Result<(), AuthError>;
+
+    /// Removes tokens that have expired at `now`.
+    fn purge_expired(&mut self, now: u64) -> Result<(), AuthError>;
+}
+
+/// An in-memory `ResetTokenStore`.
+#[derive(Debug, Default)]
+pub struct InMemoryResetTokenStore {
+    tokens: HashMap<String, ResetToken>,
+}
+
+impl InMemoryResetTokenStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ResetTokenStore for InMemoryResetTokenStore {
+    fn save(&mut self, token: ResetToken) -> Result<(), AuthError> {
+        self.tokens.insert(token.token_hash.clone(), token);
+        Ok(())
+    }
+
+    fn find(&self, token_hash: &str) -> Result<Option<ResetToken>, AuthError> {
+        Ok(self.tokens.get(token_hash).cloned())
+    }
+
+    fn remove(&mut self, token_hash: &str) -> Result<Option<ResetToken>, AuthError> {
+        Ok(self.tokens.remove(token_hash))
+    }
+
+    fn remove_subject(&mut self, subject: &str) -> Result<(), AuthError> {
+        self.tokens.retain(|_, t| t.subject != subject);
+        Ok(())
+    }
+
+    fn purge_expired(&mut self, now: u64) -> Result<(), AuthError> {
+        self.tokens.retain(|_, t| t.expires_at > now);
+        Ok(())
+    }
+}
+
+/// Issues and redeems password reset tokens.
+///
+/// Tokens are random 256-bit values, stored only as hashes, valid once and for
+/// `token_ttl`. Each user has at most one valid token: issuing a new one
+/// invalidates the previous one.
+///
+/// # Example
+/// ```code
+/// let mut resets = PasswordResets::new(Box::new(InMemoryResetTokenStore::new()), ResetPolicy::default());
+/// if let Some(token) = resets.issue(&auth, "user@example.com")? {
+///     // email a link containing the token
+/// }
+/// // show the same "check your inbox" page either way, then when the user submits the form:
+/// resets.reset(&mut auth, &token, &hash_password("new secret")?, Some(&mut sessions))?;
+/// ```
+pub struct PasswordResets {
+    store: Box<dyn ResetTokenStore>,
+    policy: ResetPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl PasswordResets {
+    /// Creates a reset service over the given store and policy.
+    pub fn new(store: Box<dyn ResetTokenStore>, policy: ResetPolicy) -> Self {
+        Self {
+            store,
+            policy,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for token expiry.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the settings in use.
+    pub fn policy(&self) -> ResetPolicy {
+        self.policy
+    }
+
+    /// Issues a reset token for a user, invalidating any earlier one.
+    ///
+    /// No token is issued for unknown addresses or accounts that are not active.
+    /// Callers must respond to the "forgot password" form identically in every
+    /// case, so that it does not reveal which addresses have accounts.
+    ///
+    /// # Returns
+    /// * `Ok(Some(String))` with the token to deliver to the user.
+    /// * `Ok(None)` if there is no active account for `email`.
+    pub fn issue(&mut self, auth: &Authentication, email: &str) -> Result<Option<String>, AuthError> {
+        let Some(user) = auth.users.get(email).filter(|u| u.ensure_active().is_ok()) else {
+            return Ok(None);
+        };
+
+        self.store.remove_subject(email)?;
+        let token = random_token(32);
+        self.store.save(ResetToken {
+            token_hash: hash_token(&token),
+            subject: email.to_string(),
+            password_fingerprint: hash_token(&user.password_hash),
+            expires_at: self.clock.now() + self.policy.token_ttl,
+        })?;
+        Ok(Some(token))
+    }
+
+    /// Checks a reset token without consuming it, e.g. before showing the reset form.
+    ///
+    /// # Returns
+    /// * `Ok(String)` with the email of the user the token belongs to.
+    /// * `Err(AuthError::InvalidToken)` if the token is unknown, expired, already used,
+    ///   or the password has changed since it was issued.
+    pub fn verify(&self, auth: &Authentication, token: &str) -> Result<String, AuthError> {
+        let stored = self.store.find(&hash_token(token))?.ok_or(AuthError::InvalidToken)?;
+        self.check(auth, &stored)?;
+        Ok(stored.subject)
+    }
+
+    /// Redeems a reset token and sets the new password.
+    ///
+    /// # Arguments
+    /// * `auth` - The authentication service holding the user.
+    /// * `token` - The reset token.
+    /// * `new_password_hash` - The new hashed password.
+    /// * `sessions` - The session manager whose sessions of the user are ended when
+    ///   `revoke_sessions` is set.
+    ///
+    /// # Returns
+    /// * `Ok(String)` with the email of the user whose password was reset.
+    /// * `Err(AuthError::InvalidToken)` if the token is unknown, expired, already used,
+    ///   or the password has changed since it was issued.
+    pub fn reset(
+        &mut self,
+        auth: &mut Authentication,
+        token: &str,
+        new_password_hash: &str,
+        sessions: Option<&mut SessionManager>,
+    ) -> Result<String, AuthError> {
+        let stored = self.store.remove(&hash_token(token))?.ok_or(AuthError::InvalidToken)?;
+        self.check(auth, &stored)?;
+
+        auth.store_password_hash(&stored.subject, new_password_hash);
+        if self.policy.revoke_sessions
+            && let Some(sessions) = sessions
+        {
+            sessions.revoke_all(&stored.subject)?;
+        }
+        Ok(stored.subject)
+    }
+
+    /// Redeems a reset token with a plaintext password, enforcing the password policy
+    /// and history of `auth`.
+    ///
+    /// The token is only consumed if the password is accepted, so the user can try again.
+    ///
+    /// # Returns
+    /// * The results of `reset`.
+    /// * `Err(AuthError::PasswordRejected)` with every broken rule if the password is not accepted.
+    pub fn reset_with_password(
+        &mut self,
+        auth: &mut Authentication,
+        token: &str,
+        new_password: &str,
+        sessions: Option<&mut SessionManager>,
+    ) -> Result<String, AuthError> {
+        let email = self.verify(auth, token)?;
+        auth.check_new_password(&email, new_password)?;
+        let password_hash = hash_password_with_cost(new_password, auth.password_policy().cost)?;
+        self.reset(auth, token, &password_hash, sessions)
+    }
+
+    /// Removes expired tokens from the store.
+    pub fn purge_expired(&mut self) -> Result<(), AuthError> {
+        self.store.purge_expired(self.clock.now())
+    }
+
+    fn check(&self, auth: &Authentication, stored: &ResetToken) -> Result<(), AuthError> {
+        if stored.expires_at <= self.clock.now() {
+            return Err(AuthError::InvalidToken);
+        }
+        let user = auth.users.get(&stored.subject).ok_or(AuthError::InvalidToken)?;
+        if !constant_time_eq(hash_token(&user.password_hash).as_bytes(), stored.password_fingerprint.as_bytes()) {
+            return Err(AuthError::InvalidToken);
+        }
+        user.ensure_active()
+    }
+}
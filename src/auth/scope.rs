@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
 /// The `ScopeMatcher` trait defines how to match a user's scope token
 /// against a required scope token. This abstraction allows for flexible
 /// matching logic, including support for different delimiters and wildcards.
@@ -60,6 +63,269 @@ impl ScopeMatcher for FlexibleMatcher {
 }
 
 
+/// A glob-style implementation of the `ScopeMatcher` trait that extends
+/// [`FlexibleMatcher`] with recursive wildcards, in-segment globs and alternation.
+///
+/// # Grammar
+/// ```text
+/// pattern     = segment *( delim segment )
+/// delim       = ":" / "." / "@" / "/"
+/// segment     = "**" / 1*piece
+/// piece       = literal / "*" / alternation
+/// alternation = "{" literal *( "," literal ) "}"
+/// ```
+///
+/// A pattern uses a single delimiter: the first of `:`, `.`, `@` and `/` (in that
+/// order) that appears in it outside of braces. The required token is split on
+/// the same delimiter and is always treated literally.
+///
+/// # Semantics
+/// - `*` as a whole segment matches exactly one segment.
+/// - `**` as a whole segment matches one or more segments.
+/// - `*` inside a segment matches any run of characters within that segment.
+/// - `{a,b}` matches any one of the listed literals.
+/// - A pattern of just `*` or `**` matches every token.
+/// - A pattern without a delimiter only matches tokens without one.
+/// - A malformed pattern (e.g. an unclosed `{`) only matches itself exactly.
+///
+/// Patterns are compiled once and cached, and matching takes polynomial time in the
+/// length of the pattern and token, however many wildcards the pattern contains.
+///
+/// # Matching Examples
+/// - `"billing:**"` matches `"billing:invoice:read"` and `"billing:invoice:line:read"`
+/// - `"billing:**:read"` matches `"billing:invoice:line:read"`
+/// - `"report.read_*"` matches `"report.read_summary"`
+/// - `"user:{read,list}"` matches `"user:list"` but not `"user:delete"`
+/// - `"*:*:read"` matches `"user_service:user:read"`, as with `FlexibleMatcher`
+pub struct GlobMatcher;
+
+/// A single compiled segment of a `GlobMatcher` pattern.
+#[derive(Debug)]
+enum GlobSegment {
+    /// `**`, one or more whole segments.
+    Recursive,
+    /// A sequence of literal, wildcard and alternation pieces.
+    Pieces(Vec<GlobPiece>),
+}
+
+/// A piece of a single `GlobMatcher` segment.
+#[derive(Debug)]
+enum GlobPiece {
+    Literal(String),
+    Star,
+    Alternation(Vec<String>),
+}
+
+/// A compiled `GlobMatcher` pattern.
+#[derive(Debug)]
+struct CompiledGlob {
+    delim: Option<char>,
+    segments: Vec<GlobSegment>,
+}
+
+impl GlobMatcher {
+    const DELIMITERS: [char; 4] = [':', '.', '@', '/'];
+    const CACHE_CAPACITY: usize = 1024;
+
+    /// Finds the delimiter used by `pattern`, ignoring characters inside braces.
+    fn delimiter_of(pattern: &str) -> Option<char> {
+        let mut depth = 0usize;
+        let mut found: Option<char> = None;
+        for c in pattern.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                c if depth == 0 && Self::DELIMITERS.contains(&c) => {
+                    let rank = |d: char| Self::DELIMITERS.iter().position(|x| *x == d);
+                    if found.is_none_or(|f| rank(c) < rank(f)) {
+                        found = Some(c);
+                    }
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Splits `pattern` on `delim`, ignoring delimiters inside braces.
+    fn split_pattern(pattern: &str, delim: Option<char>) -> Vec<&str> {
+        let Some(delim) = delim else {
+            return vec![pattern];
+        };
+        let mut parts = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, c) in pattern.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                c if c == delim && depth == 0 => {
+                    parts.push(&pattern[start..i]);
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        parts.push(&pattern[start..]);
+        parts
+    }
+
+    fn compile_segment(segment: &str) -> Option<GlobSegment> {
+        if segment == "**" {
+            return Some(GlobSegment::Recursive);
+        }
+
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = segment.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => {
+                    if !literal.is_empty() {
+                        pieces.push(GlobPiece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(GlobPiece::Star);
+                }
+                '{' => {
+                    if !literal.is_empty() {
+                        pieces.push(GlobPiece::Literal(std::mem::take(&mut literal)));
+                    }
+                    let mut body = String::new();
+                    loop {
+                        match chars.next()? {
+                            '}' => break,
+                            '{' | '*' => return None,
+                            c => body.push(c),
+                        }
+                    }
+                    pieces.push(GlobPiece::Alternation(body.split(',').map(str::to_string).collect()));
+                }
+                '}' => return None,
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(GlobPiece::Literal(literal));
+        }
+        Some(GlobSegment::Pieces(pieces))
+    }
+
+    /// Compiles `pattern`, returning `None` if it is malformed.
+    fn compile(pattern: &str) -> Option<CompiledGlob> {
+        let delim = Self::delimiter_of(pattern);
+        let segments = Self::split_pattern(pattern, delim)
+            .into_iter()
+            .map(Self::compile_segment)
+            .collect::<Option<Vec<GlobSegment>>>()?;
+        Some(CompiledGlob { delim, segments })
+    }
+
+    /// Returns the compiled form of `pattern`, compiling it on first use.
+    ///
+    /// The cache is bounded: once it holds `CACHE_CAPACITY` patterns it is cleared,
+    /// so attacker-supplied patterns cannot grow it without limit.
+    fn compiled(pattern: &str) -> Arc<Option<CompiledGlob>> {
+        static CACHE: OnceLock<Mutex<HashMap<String, Arc<Option<CompiledGlob>>>>> = OnceLock::new();
+        let cache = CACHE.get_or_init(Default::default);
+
+        if let Ok(cache) = cache.lock()
+            && let Some(compiled) = cache.get(pattern)
+        {
+            return Arc::clone(compiled);
+        }
+        let compiled = Arc::new(Self::compile(pattern));
+        if let Ok(mut cache) = cache.lock() {
+            if cache.len() >= Self::CACHE_CAPACITY {
+                cache.clear();
+            }
+            cache.insert(pattern.to_string(), Arc::clone(&compiled));
+        }
+        compiled
+    }
+
+    /// Matches `input` against the pieces of one segment.
+    ///
+    /// `reachable[i]` records whether the pieces seen so far can consume exactly the
+    /// first `i` bytes of `input`, so the cost is linear in the number of pieces
+    /// rather than exponential in the number of wildcards.
+    fn match_pieces(pieces: &[GlobPiece], input: &str) -> bool {
+        let mut reachable = vec![false; input.len() + 1];
+        reachable[0] = true;
+        for piece in pieces {
+            let mut next = vec![false; input.len() + 1];
+            match piece {
+                GlobPiece::Star => {
+                    if let Some(first) = reachable.iter().position(|r| *r) {
+                        for (i, slot) in next.iter_mut().enumerate().skip(first) {
+                            *slot = input.is_char_boundary(i);
+                        }
+                    }
+                }
+                GlobPiece::Literal(lit) => Self::advance(&reachable, &mut next, input, std::slice::from_ref(lit)),
+                GlobPiece::Alternation(alts) => Self::advance(&reachable, &mut next, input, alts),
+            }
+            reachable = next;
+        }
+        reachable[input.len()]
+    }
+
+    /// Marks in `next` every position reached by one of `literals` from a reachable position.
+    fn advance(reachable: &[bool], next: &mut [bool], input: &str, literals: &[String]) {
+        for (i, _) in reachable.iter().enumerate().filter(|(_, r)| **r) {
+            for lit in literals {
+                if input[i..].starts_with(lit.as_str()) {
+                    next[i + lit.len()] = true;
+                }
+            }
+        }
+    }
+
+    /// Matches the segments of a required token against compiled pattern segments,
+    /// using the same reachability table as `match_pieces`.
+    fn match_segments(pattern: &[GlobSegment], required: &[&str]) -> bool {
+        let mut reachable = vec![false; required.len() + 1];
+        reachable[0] = true;
+        for segment in pattern {
+            let mut next = vec![false; required.len() + 1];
+            match segment {
+                GlobSegment::Recursive => {
+                    if let Some(first) = reachable.iter().position(|r| *r) {
+                        next.iter_mut().skip(first + 1).for_each(|slot| *slot = true);
+                    }
+                }
+                GlobSegment::Pieces(pieces) => {
+                    for (i, head) in required.iter().enumerate() {
+                        next[i + 1] = reachable[i] && Self::match_pieces(pieces, head);
+                    }
+                }
+            }
+            reachable = next;
+        }
+        reachable[required.len()]
+    }
+}
+
+impl ScopeMatcher for GlobMatcher {
+    fn matches(user_token: &str, required_token: &str) -> bool {
+        if user_token == required_token || user_token == "*" || user_token == "**" {
+            return true;
+        }
+
+        let compiled = Self::compiled(user_token);
+        let Some(compiled) = compiled.as_ref() else {
+            return false;
+        };
+
+        let required: Vec<&str> = match compiled.delim {
+            Some(d) => required_token.split(d).collect(),
+            None if required_token.contains(Self::DELIMITERS) => return false,
+            None => vec![required_token],
+        };
+
+        Self::match_segments(&compiled.segments, &required)
+    }
+}
+
 /// Parses a scope string into a list of individual scope tokens,
 /// separated by whitespace (as per OAuth2/RFC conventions).
 ///
@@ -0,0 +1,151 @@
+use crate::error::AuthError;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A directed graph of scope implications used to expand granted scopes.
+///
+/// An edge `admin -> write` means that a token granted `admin` is also treated
+/// as granted `write`. Implications are transitive, so with `write -> read`
+/// added as well, `admin` also grants `read`. Cycles are rejected when edges are added.
+///
+/// # Example
+/// ```code
+/// let mut graph = ScopeGraph::new();
+/// graph.add_implication("admin", "write")?;
+/// graph.add_implication("write", "read")?;
+/// graph.add_implication("repo", "repo:status")?;
+///
+/// assert_eq!(graph.expand(["admin"]), vec!["admin", "write", "read"]);
+/// assert_eq!(graph.minimal_cover(["read", "write", "repo:status"]), vec!["write", "repo:status"]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ScopeGraph {
+    edges: HashMap<String, Vec<String>>,
+}
+
+impl ScopeGraph {
+    /// Creates an empty `ScopeGraph`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares that holding `scope` implies holding `implied`.
+    ///
+    /// # Arguments
+    /// * `scope` - The broader scope.
+    /// * `implied` - The scope granted as a consequence.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the implication was added (or already existed).
+    /// * `Err(AuthError::ScopeImplicationCycle)` if the edge would create a cycle.
+    pub fn add_implication(&mut self, scope: &str, implied: &str) -> Result<(), AuthError> {
+        if let Some(mut path) = self.path(implied, scope) {
+            path.push(implied.to_string());
+            return Err(AuthError::ScopeImplicationCycle(path.join(" -> ")));
+        }
+
+        let targets = self.edges.entry(scope.to_string()).or_default();
+        if !targets.iter().any(|t| t == implied) {
+            targets.push(implied.to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the scopes directly implied by `scope`.
+    pub fn direct_implications(&self, scope: &str) -> &[String] {
+        self.edges.get(scope).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns `true` if holding `scope` implies holding `other`, directly or transitively.
+    ///
+    /// A scope always implies itself.
+    pub fn implies(&self, scope: &str, other: &str) -> bool {
+        self.path(scope, other).is_some()
+    }
+
+    /// Expands a set of granted scopes with everything they imply.
+    ///
+    /// The result keeps the original scopes first, in order, followed by implied
+    /// scopes in breadth-first order, without duplicates.
+    pub fn expand<I, S>(&self, scopes: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        for scope in scopes {
+            let scope = scope.as_ref().to_string();
+            if seen.insert(scope.clone()) {
+                queue.push_back(scope.clone());
+                result.push(scope);
+            }
+        }
+
+        while let Some(scope) = queue.pop_front() {
+            for implied in self.direct_implications(&scope) {
+                if seen.insert(implied.clone()) {
+                    queue.push_back(implied.clone());
+                    result.push(implied.clone());
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns the smallest subset of `required` whose expansion still covers all of `required`.
+    ///
+    /// Scopes implied by another required scope are dropped, which is useful for
+    /// showing the fewest scopes on a consent screen.
+    pub fn minimal_cover<I, S>(&self, required: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut unique: Vec<String> = Vec::new();
+        for scope in required {
+            let scope = scope.as_ref();
+            if !unique.iter().any(|s| s == scope) {
+                unique.push(scope.to_string());
+            }
+        }
+
+        unique
+            .iter()
+            .filter(|scope| {
+                !unique.iter().any(|other| other != *scope && self.implies(other, scope))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Finds an implication path from `from` to `to`, if one exists.
+    fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut parents: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        let mut seen = HashSet::from([from]);
+
+        while let Some(scope) = queue.pop_front() {
+            if scope == to {
+                let mut path = vec![scope.to_string()];
+                let mut current = scope;
+                while let Some(parent) = parents.get(current) {
+                    path.push(parent.to_string());
+                    current = parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for implied in self.direct_implications(scope) {
+                if seen.insert(implied.as_str()) {
+                    parents.insert(implied.as_str(), scope);
+                    queue.push_back(implied.as_str());
+                }
+            }
+        }
+
+        None
+    }
+}
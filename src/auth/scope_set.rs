@@ -0,0 +1,196 @@
+use crate::auth::scope::{parse_scope_string, FlexibleMatcher, ScopeMatcher};
+use crate::error::AuthError;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A set of scope tokens with set operations driven by a `ScopeMatcher`.
+///
+/// Membership follows the same semantics as
+/// [`authorize_with_matcher`](crate::auth::scope::authorize_with_matcher): a set
+/// contains a token if at least one of its scopes matches it. Wildcard scopes in
+/// the set therefore cover every token they match.
+///
+/// # Example
+/// ```code
+/// let granted: ScopeSet = ScopeSet::from_scope_string("read:* write:posts");
+/// let requested: ScopeSet = ScopeSet::from_scope_string("read:users write:posts");
+///
+/// assert!(requested.is_subset_of(&granted));
+/// assert_eq!(granted.intersect(&requested).to_string(), "write:posts read:users");
+/// ```
+pub struct ScopeSet<M: ScopeMatcher = FlexibleMatcher> {
+    scopes: Vec<String>,
+    _matcher: PhantomData<M>,
+}
+
+impl<M: ScopeMatcher> ScopeSet<M> {
+    /// Creates an empty `ScopeSet`.
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            _matcher: PhantomData,
+        }
+    }
+
+    /// Creates a `ScopeSet` from a space-delimited scope string.
+    pub fn from_scope_string(scope_str: &str) -> Self {
+        parse_scope_string(scope_str).into_iter().collect()
+    }
+
+    /// Adds a scope token to the set, ignoring exact duplicates.
+    pub fn insert(&mut self, scope: &str) {
+        if !self.scopes.iter().any(|s| s == scope) {
+            self.scopes.push(scope.to_string());
+        }
+    }
+
+    /// Returns `true` if any scope in the set matches `required_token`.
+    pub fn contains(&self, required_token: &str) -> bool {
+        self.scopes.iter().any(|s| M::matches(s, required_token))
+    }
+
+    /// Returns `true` if every scope in this set is covered by `other`.
+    ///
+    /// Wildcard scopes in this set must themselves be matched by `other`, so
+    /// `read:*` is a subset of `*:*` but not of `read:users`.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.scopes.iter().all(|s| other.contains(s))
+    }
+
+    /// Returns the scopes covered by both sets, normalized.
+    ///
+    /// Each scope of either set that the other set covers is kept, so
+    /// intersecting `read:*` with `read:users` yields `read:users`. The result never
+    /// covers a token that one of the inputs does not cover.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.scopes
+            .iter()
+            .filter(|s| other.contains(s))
+            .chain(other.scopes.iter().filter(|s| self.contains(s)))
+            .map(String::as_str)
+            .collect::<Self>()
+            .normalize()
+    }
+
+    /// Returns the scopes covered by either set, normalized.
+    pub fn union(&self, other: &Self) -> Self {
+        self.scopes
+            .iter()
+            .chain(other.scopes.iter())
+            .map(String::as_str)
+            .collect::<Self>()
+            .normalize()
+    }
+
+    /// Returns an equivalent set without scopes already covered by another entry.
+    ///
+    /// For example, `read:users read:* write:posts` normalizes to `read:* write:posts`.
+    pub fn normalize(&self) -> Self {
+        let covered_by_other = |i: usize, scope: &str| {
+            self.scopes.iter().enumerate().any(|(j, other)| {
+                j != i && M::matches(other, scope) && (j < i || !M::matches(scope, other))
+            })
+        };
+
+        self.scopes
+            .iter()
+            .enumerate()
+            .filter(|(i, s)| !covered_by_other(*i, s))
+            .map(|(_, s)| s.as_str())
+            .collect()
+    }
+
+    /// Narrows this granted set down to the `requested` scopes.
+    ///
+    /// # Returns
+    /// * `Ok(ScopeSet)` with the normalized requested scopes if all of them are granted.
+    /// * `Err(AuthError::ScopeNotGranted)` listing the requested scopes that are not covered.
+    ///
+    /// # Example
+    /// ```code
+    /// let granted: ScopeSet = ScopeSet::from_scope_string("read:* write:posts");
+    /// let narrowed = granted.down_scope(&ScopeSet::from_scope_string("read:users"))?;
+    /// ```
+    pub fn down_scope(&self, requested: &Self) -> Result<Self, AuthError> {
+        let missing: Vec<&str> = requested
+            .scopes
+            .iter()
+            .filter(|s| !self.contains(s))
+            .map(String::as_str)
+            .collect();
+
+        if missing.is_empty() {
+            Ok(requested.normalize())
+        } else {
+            Err(AuthError::ScopeNotGranted(missing.join(" ")))
+        }
+    }
+
+    /// Returns an iterator over the scope tokens in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().map(String::as_str)
+    }
+
+    /// Returns the number of scope tokens in the set.
+    pub fn len(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Returns `true` if the set has no scope tokens.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+}
+
+impl<M: ScopeMatcher> Default for ScopeSet<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: ScopeMatcher> Clone for ScopeSet<M> {
+    fn clone(&self) -> Self {
+        Self {
+            scopes: self.scopes.clone(),
+            _matcher: PhantomData,
+        }
+    }
+}
+
+impl<M: ScopeMatcher> fmt::Debug for ScopeSet<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ScopeSet").field(&self.scopes).finish()
+    }
+}
+
+impl<M: ScopeMatcher> PartialEq for ScopeSet<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.scopes == other.scopes
+    }
+}
+
+impl<M: ScopeMatcher> fmt::Display for ScopeSet<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.scopes.join(" "))
+    }
+}
+
+impl<'a, M: ScopeMatcher> FromIterator<&'a str> for ScopeSet<M> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for scope in iter {
+            set.insert(scope);
+        }
+        set
+    }
+}
+
+impl<M: ScopeMatcher> FromIterator<String> for ScopeSet<M> {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for scope in iter {
+            set.insert(&scope);
+        }
+        set
+    }
+}
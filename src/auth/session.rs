@@ -0,0 +1,295 @@
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{hash_token, random_token};
+use crate::error::AuthError;
+use crate::model::{Identifiable, User};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Timeouts applied to sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionPolicy {
+    /// How long a session stays valid without activity, in seconds.
+    pub idle_timeout: u64,
+    /// How long a session stays valid in total, regardless of activity, in seconds.
+    pub absolute_timeout: u64,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            idle_timeout: 30 * 60,
+            absolute_timeout: 12 * 3600,
+        }
+    }
+}
+
+/// How strongly the user behind a session has been authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum AuthLevel {
+    /// A single factor, such as a password.
+    #[default]
+    SingleFactor,
+    /// A second factor has been verified as well.
+    MultiFactor,
+}
+
+/// Details of the client a session was created from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientInfo {
+    /// The client IP address.
+    pub ip_address: Option<String>,
+    /// The `User-Agent` header.
+    pub user_agent: Option<String>,
+}
+
+/// A stored session. Only the hash of the session id is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    /// SHA-256 hash of the session id.
+    pub id_hash: String,
+    /// The user identity (see `Identifiable`).
+    pub subject: String,
+    /// When the session was created, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// When the session was last used.
+    pub last_seen_at: u64,
+    /// The client the session was created from.
+    pub client: ClientInfo,
+    /// How strongly the user has been authenticated.
+    pub auth_level: AuthLevel,
+}
+
+impl Session {
+    /// Returns when the session expires under `policy`: the earlier of the idle and absolute deadlines.
+    pub fn expires_at(&self, policy: &SessionPolicy) -> u64 {
+        (self.last_seen_at + policy.idle_timeout).min(self.created_at + policy.absolute_timeout)
+    }
+
+    /// Returns `true` if the session has not expired at `now` under `policy`.
+    pub fn is_active(&self, policy: &SessionPolicy, now: u64) -> bool {
+        now < self.expires_at(policy)
+    }
+}
+
+/// Persistence for sessions.
+///
+/// Implementations can share sessions across instances (e.g. in Redis or a database).
+pub trait SessionStore: Send + Sync {
+    /// Stores a session, replacing any session with the same id hash.
+    fn save(&mut self, session: Session) -> Result<(), AuthError>;
+
+    /// Finds a session by id hash.
+    fn find(&self, id_hash: &str) -> Result<Option<Session>, AuthError>;
+
+    /// Removes a session, returning it if it existed.
+    fn remove(&mut self, id_hash: &str) -> Result<Option<Session>, AuthError>;
+
+    /// Returns every session of a subject.
+    fn find_by_subject(&self, subject: &str) -> Result<Vec<Session>, AuthError>;
+
+    /// Removes every session of a subject, returning how many were removed.
+    fn remove_subject(&mut self, subject: &str) -> Result<usize, AuthError>;
+
+    /// Removes sessions that have expired at `now` under `policy`.
+    fn purge_expired(&mut self, policy: &SessionPolicy, now: u64) -> Result<(), AuthError>;
+}
+
+/// An in-memory `SessionStore`.
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    sessions: HashMap<String, Session>,
+}
+
+impl InMemorySessionStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn save(&mut self, session: Session) -> Result<(), AuthError> {
+        self.sessions.insert(session.id_hash.clone(), session);
+        Ok(())
+    }
+
+    fn find(&self, id_hash: &str) -> Result<Option<Session>, AuthError> {
+        Ok(self.sessions.get(id_hash).cloned())
+    }
+
+    fn remove(&mut self, id_hash: &str) -> Result<Option<Session>, AuthError> {
+        Ok(self.sessions.remove(id_hash))
+    }
+
+    fn find_by_subject(&self, subject: &str) -> Result<Vec<Session>, AuthError> {
+        Ok(self.sessions.values().filter(|s| s.subject == subject).cloned().collect())
+    }
+
+    fn remove_subject(&mut self, subject: &str) -> Result<usize, AuthError> {
+        let before = self.sessions.len();
+        self.sessions.retain(|_, s| s.subject != subject);
+        Ok(before - self.sessions.len())
+    }
+
+    fn purge_expired(&mut self, policy: &SessionPolicy, now: u64) -> Result<(), AuthError> {
+        self.sessions.retain(|_, s| s.is_active(policy, now));
+        Ok(())
+    }
+}
+
+/// Issues and tracks server-side sessions for logged-in users.
+///
+/// Session ids are random 256-bit tokens handed to the client (usually in a
+/// cookie); the store only ever sees their hashes.
+///
+/// # Example
+/// ```code
+/// let mut sessions = SessionManager::new(Box::new(InMemorySessionStore::new()), SessionPolicy::default());
+/// let user = auth.login("user@example.com")?.unwrap();
+/// let (session_id, _) = sessions.create(&user, ClientInfo::default(), AuthLevel::SingleFactor)?;
+/// let session = sessions.validate(&session_id)?;
+/// ```
+pub struct SessionManager {
+    store: Box<dyn SessionStore>,
+    policy: SessionPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl SessionManager {
+    /// Creates a manager over the given store and policy.
+    pub fn new(store: Box<dyn SessionStore>, policy: SessionPolicy) -> Self {
+        Self {
+            store,
+            policy,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for timeouts.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the timeouts applied to sessions.
+    pub fn policy(&self) -> SessionPolicy {
+        self.policy
+    }
+
+    /// Creates a session for a user who has just logged in.
+    ///
+    /// # Arguments
+    /// * `user` - The authenticated user.
+    /// * `client` - The IP address and user agent of the request.
+    /// * `auth_level` - How strongly the user has been authenticated.
+    ///
+    /// # Returns
+    /// * `Ok((String, Session))` with the session id to hand to the client and the stored session.
+    pub fn create(&mut self, user: &User, client: ClientInfo, auth_level: AuthLevel) -> Result<(String, Session), AuthError> {
+        let now = self.clock.now();
+        let session_id = random_token(32);
+        let session = Session {
+            id_hash: hash_token(&session_id),
+            subject: user.identity(),
+            created_at: now,
+            last_seen_at: now,
+            client,
+            auth_level,
+        };
+        self.store.save(session.clone())?;
+        Ok((session_id, session))
+    }
+
+    /// Validates a session id and records the activity.
+    ///
+    /// # Returns
+    /// * `Ok(Session)` if the session exists and has not timed out.
+    /// * `Err(AuthError::SessionExpired)` if the idle or absolute timeout has passed; the session is removed.
+    /// * `Err(AuthError::InvalidToken)` if the session id is unknown.
+    pub fn validate(&mut self, session_id: &str) -> Result<Session, AuthError> {
+        let mut session = self.active(session_id)?;
+        session.last_seen_at = self.clock.now();
+        self.store.save(session.clone())?;
+        Ok(session)
+    }
+
+    /// Issues a new id for a session and invalidates the old one.
+    ///
+    /// Call this whenever the privileges behind a session change, so that an id
+    /// obtained before the change (e.g. through session fixation) becomes useless.
+    ///
+    /// # Returns
+    /// * `Ok((String, Session))` with the new session id and the stored session.
+    /// * The errors of `validate` if the session is unknown or expired.
+    pub fn rotate(&mut self, session_id: &str) -> Result<(String, Session), AuthError> {
+        let session = self.active(session_id)?;
+        self.replace(session)
+    }
+
+    /// Raises the auth level of a session, rotating its id.
+    ///
+    /// # Example
+    /// ```code
+    /// // after the second factor has been verified
+    /// let (session_id, _) = sessions.elevate(&session_id, AuthLevel::MultiFactor)?;
+    /// ```
+    pub fn elevate(&mut self, session_id: &str, auth_level: AuthLevel) -> Result<(String, Session), AuthError> {
+        let mut session = self.active(session_id)?;
+        session.auth_level = session.auth_level.max(auth_level);
+        self.replace(session)
+    }
+
+    /// Returns the active sessions of a user, most recently used first.
+    pub fn list(&self, subject: &str) -> Result<Vec<Session>, AuthError> {
+        let now = self.clock.now();
+        let mut sessions: Vec<Session> = self
+            .store
+            .find_by_subject(subject)?
+            .into_iter()
+            .filter(|s| s.is_active(&self.policy, now))
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_seen_at));
+        Ok(sessions)
+    }
+
+    /// Ends a session (logout). Unknown ids are ignored.
+    pub fn revoke(&mut self, session_id: &str) -> Result<(), AuthError> {
+        self.store.remove(&hash_token(session_id))?;
+        Ok(())
+    }
+
+    /// Ends a session by its id hash, as listed by `list`.
+    pub fn revoke_by_hash(&mut self, id_hash: &str) -> Result<(), AuthError> {
+        self.store.remove(id_hash)?;
+        Ok(())
+    }
+
+    /// Ends every session of a user, returning how many were ended.
+    pub fn revoke_all(&mut self, subject: &str) -> Result<usize, AuthError> {
+        self.store.remove_subject(subject)
+    }
+
+    /// Removes timed-out sessions from the store.
+    pub fn purge_expired(&mut self) -> Result<(), AuthError> {
+        self.store.purge_expired(&self.policy, self.clock.now())
+    }
+
+    fn active(&mut self, session_id: &str) -> Result<Session, AuthError> {
+        let id_hash = hash_token(session_id);
+        let session = self.store.find(&id_hash)?.ok_or(AuthError::InvalidToken)?;
+        if !session.is_active(&self.policy, self.clock.now()) {
+            self.store.remove(&id_hash)?;
+            return Err(AuthError::SessionExpired);
+        }
+        Ok(session)
+    }
+
+    fn replace(&mut self, mut session: Session) -> Result<(String, Session), AuthError> {
+        self.store.remove(&session.id_hash)?;
+        let session_id = random_token(32);
+        session.id_hash = hash_token(&session_id);
+        session.last_seen_at = self.clock.now();
+        self.store.save(session.clone())?;
+        Ok((session_id, session))
+    }
+}
@@ -0,0 +1,211 @@
+use crate::error::ScopeError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Describes the formal grammar that a [`Scope`] must follow.
+///
+/// Unlike [`FlexibleMatcher`](crate::auth::scope::FlexibleMatcher), a grammar uses
+/// exactly one delimiter, so scopes like `a.b:c` are never ambiguous.
+///
+/// ```text
+/// scope   = segment *( delimiter segment )
+/// segment = "*" / 1*allowed-char
+/// ```
+///
+/// The default grammar uses `:` as delimiter, allows ASCII letters, digits,
+/// `_` and `-` in segments, accepts 1 to 8 segments of at most 64 characters each,
+/// and allows `*` as a whole-segment wildcard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeGrammar {
+    /// The single character separating segments.
+    pub delimiter: char,
+    /// Additional characters allowed in segments besides ASCII letters and digits.
+    pub extra_chars: Vec<char>,
+    /// Minimum number of segments.
+    pub min_segments: usize,
+    /// Maximum number of segments.
+    pub max_segments: usize,
+    /// Maximum length of a single segment, in characters.
+    pub max_segment_len: usize,
+    /// Whether `*` may be used as a whole-segment wildcard.
+    pub allow_wildcard: bool,
+}
+
+impl Default for ScopeGrammar {
+    fn default() -> Self {
+        Self {
+            delimiter: ':',
+            extra_chars: vec!['_', '-'],
+            min_segments: 1,
+            max_segments: 8,
+            max_segment_len: 64,
+            allow_wildcard: true,
+        }
+    }
+}
+
+impl ScopeGrammar {
+    /// Creates the default grammar with a different delimiter.
+    ///
+    /// # Example
+    /// ```code
+    /// let grammar = ScopeGrammar::with_delimiter('.');
+    /// let scope = Scope::parse_with("admin_service.read", &grammar)?;
+    /// ```
+    pub fn with_delimiter(delimiter: char) -> Self {
+        Self {
+            delimiter,
+            ..Self::default()
+        }
+    }
+
+    fn is_allowed(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || self.extra_chars.contains(&c)
+    }
+}
+
+/// A validated scope token, parsed according to a [`ScopeGrammar`].
+///
+/// # Example
+/// ```code
+/// let scope = Scope::parse("user_service:user:read")?;
+/// assert_eq!(scope.segments(), ["user_service", "user", "read"]);
+///
+/// let err = Scope::parse("user_service::read").unwrap_err();
+/// assert_eq!(err, ScopeError::EmptySegment { position: 1 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scope {
+    segments: Vec<String>,
+    delimiter: char,
+}
+
+impl Scope {
+    /// Parses a scope using the default [`ScopeGrammar`].
+    ///
+    /// # Returns
+    /// * `Ok(Scope)` if the input follows the grammar.
+    /// * `Err(ScopeError)` describing the first violation found.
+    pub fn parse(input: &str) -> Result<Self, ScopeError> {
+        Self::parse_with(input, &ScopeGrammar::default())
+    }
+
+    /// Parses a scope using the given grammar.
+    ///
+    /// # Arguments
+    /// * `input` - The scope token to parse.
+    /// * `grammar` - The grammar the token must follow.
+    ///
+    /// # Returns
+    /// * `Ok(Scope)` if the input follows the grammar.
+    /// * `Err(ScopeError)` describing the first violation found.
+    pub fn parse_with(input: &str, grammar: &ScopeGrammar) -> Result<Self, ScopeError> {
+        if input.is_empty() {
+            return Err(ScopeError::Empty);
+        }
+
+        let parts: Vec<&str> = input.split(grammar.delimiter).collect();
+        if parts.len() < grammar.min_segments {
+            return Err(ScopeError::TooFewSegments { min: grammar.min_segments, found: parts.len() });
+        }
+        if parts.len() > grammar.max_segments {
+            return Err(ScopeError::TooManySegments { max: grammar.max_segments, found: parts.len() });
+        }
+
+        for (position, part) in parts.iter().enumerate() {
+            if part.is_empty() {
+                return Err(ScopeError::EmptySegment { position });
+            }
+            if *part == "*" {
+                if !grammar.allow_wildcard {
+                    return Err(ScopeError::WildcardNotAllowed { position });
+                }
+                continue;
+            }
+            if part.chars().count() > grammar.max_segment_len {
+                return Err(ScopeError::SegmentTooLong { position, max: grammar.max_segment_len });
+            }
+            if let Some(character) = part.chars().find(|c| !grammar.is_allowed(*c)) {
+                return Err(ScopeError::InvalidCharacter { character, position });
+            }
+        }
+
+        Ok(Self {
+            segments: parts.into_iter().map(str::to_string).collect(),
+            delimiter: grammar.delimiter,
+        })
+    }
+
+    /// Parses a space-delimited list of scopes, failing on the first invalid one.
+    ///
+    /// This is intended for validating scopes at token-issue time.
+    ///
+    /// # Example
+    /// ```code
+    /// let scopes = Scope::parse_all("user:read user:write", &ScopeGrammar::default())?;
+    /// ```
+    pub fn parse_all(scope_str: &str, grammar: &ScopeGrammar) -> Result<Vec<Self>, ScopeError> {
+        scope_str.split_whitespace().map(|s| Self::parse_with(s, grammar)).collect()
+    }
+
+    /// Returns the segments of this scope.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Returns the delimiter this scope was parsed with.
+    pub fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    /// Returns `true` if any segment is a wildcard.
+    pub fn has_wildcard(&self) -> bool {
+        self.segments.iter().any(|s| s == "*")
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ScopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments.join(&self.delimiter.to_string()))
+    }
+}
+
+/// A matcher that only operates on parsed, validated [`Scope`] values.
+///
+/// A granted scope matches a required scope when both use the same delimiter,
+/// have the same number of segments, and every granted segment is either `*`
+/// or equal to the corresponding required segment.
+///
+/// # Example
+/// ```code
+/// let granted = Scope::parse("user_service:*:read")?;
+/// let required = Scope::parse("user_service:user:read")?;
+/// assert!(StrictMatcher::matches(&granted, &required));
+/// ```
+pub struct StrictMatcher;
+
+impl StrictMatcher {
+    /// Returns `true` if `granted` authorizes `required`.
+    pub fn matches(granted: &Scope, required: &Scope) -> bool {
+        granted.delimiter == required.delimiter
+            && granted.segments.len() == required.segments.len()
+            && granted
+                .segments
+                .iter()
+                .zip(required.segments.iter())
+                .all(|(g, r)| g == "*" || g == r)
+    }
+
+    /// Returns `true` if any of the `granted` scopes authorizes `required`.
+    pub fn authorize(granted: &[Scope], required: &Scope) -> bool {
+        granted.iter().any(|g| Self::matches(g, required))
+    }
+}
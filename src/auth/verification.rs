@@ -0,0 +1,190 @@
+use crate::auth::auth_n::Authentication;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{hash_token, random_token};
+use crate::error::AuthError;
+use crate::mail::{Email, Mailer, SendLimiter};
+use crate::model::AccountStatus;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// What an account may do before its email address is verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnverifiedAccess {
+    /// The account is created `Pending` and cannot log in until verified.
+    #[default]
+    Block,
+    /// The account is created `Active`; callers restrict features by checking
+    /// `User::is_email_verified`.
+    Restrict,
+}
+
+/// Settings of the email verification flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationPolicy {
+    /// How long a verification token stays valid, in seconds.
+    pub token_ttl: u64,
+    /// Minimum time between two verification emails to the same address, in seconds.
+    pub resend_interval: u64,
+    /// Maximum number of verification emails to the same address per `send_window`.
+    pub max_sends: usize,
+    /// The period `max_sends` applies to, in seconds.
+    pub send_window: u64,
+    /// What an account may do before it is verified.
+    pub unverified_access: UnverifiedAccess,
+    /// The link sent to the user; `{token}` is replaced with the verification token.
+    pub verify_url: String,
+    /// The subject line of the verification email.
+    pub subject: String,
+}
+
+impl Default for VerificationPolicy {
+    fn default() -> Self {
+        Self {
+            token_ttl: 24 * 3600,
+            resend_interval: 60,
+            max_sends: 5,
+            send_window: 24 * 3600,
+            unverified_access: UnverifiedAccess::Block,
+            verify_url: "https://localhost/verify-email?token={token}".to_string(),
+            subject: "Verify your email address".to_string(),
+        }
+    }
+}
+
+struct PendingVerification {
+    email: String,
+    expires_at: u64,
+}
+
+/// Verifies that new users own the email address they registered with.
+///
+/// Tokens are random, single-use and expire after `token_ttl`; only their hashes
+/// are kept. Sending a new token invalidates the previous one for the same address.
+///
+/// # Example
+/// ```code
+/// let mut verification = EmailVerification::new(Box::new(mailer), VerificationPolicy::default());
+/// verification.register(&mut auth, "user@example.com", &password_hash)?;
+/// // when the user follows the link
+/// let email = verification.confirm(&mut auth, &token_from_link)?;
+/// ```
+pub struct EmailVerification {
+    policy: VerificationPolicy,
+    mailer: Box<dyn Mailer>,
+    tokens: HashMap<String, PendingVerification>,
+    sends: SendLimiter,
+    clock: Arc<dyn Clock>,
+}
+
+impl EmailVerification {
+    /// Creates a verification flow delivering emails through `mailer`.
+    pub fn new(mailer: Box<dyn Mailer>, policy: VerificationPolicy) -> Self {
+        Self {
+            sends: SendLimiter::new(policy.resend_interval, policy.max_sends, policy.send_window),
+            policy,
+            mailer,
+            tokens: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for token expiry and rate limiting.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the settings in use.
+    pub fn policy(&self) -> &VerificationPolicy {
+        &self.policy
+    }
+
+    /// Registers a user and sends them a verification email.
+    ///
+    /// Depending on `unverified_access`, the account is created `Pending` or `Active`.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the user was registered and the email was sent.
+    /// * `Err(AuthError::InvalidEmail)` if the address is malformed.
+    /// * `Err(AuthError::EmailAlreadyRegistered)` if the email is already in use.
+    /// * `Err(AuthError::MailDeliveryFailed)` if the email could not be sent; the account is kept
+    ///   and the email can be resent with `send`.
+    pub fn register(&mut self, auth: &mut Authentication, email: &str, password_hash: &str) -> Result<(), AuthError> {
+        let status = match self.policy.unverified_access {
+            UnverifiedAccess::Block => AccountStatus::Pending,
+            UnverifiedAccess::Restrict => AccountStatus::Active,
+        };
+        auth.register_with_status(email, password_hash, status)?;
+        self.send(auth, email)
+    }
+
+    /// Sends (or resends) a verification email, invalidating any earlier token.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the email was sent.
+    /// * `Err(AuthError::TooManyAttempts)` if the address was emailed less than
+    ///   `resend_interval` ago or has reached `max_sends` in the current window.
+    /// * `Err(AuthError::EmailAlreadyVerified)` if the address is already verified.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    pub fn send(&mut self, auth: &Authentication, email: &str) -> Result<(), AuthError> {
+        let user = auth.users.get(email).ok_or(AuthError::UserNotFound)?;
+        if user.is_email_verified() {
+            return Err(AuthError::EmailAlreadyVerified);
+        }
+
+        let now = self.clock.now();
+        self.sends.record(email, now)?;
+
+        self.tokens.retain(|_, pending| pending.email != email);
+        let token = random_token(32);
+        self.tokens.insert(
+            hash_token(&token),
+            PendingVerification {
+                email: email.to_string(),
+                expires_at: now + self.policy.token_ttl,
+            },
+        );
+
+        let link = self.policy.verify_url.replace("{token}", &token);
+        self.mailer.send(&Email {
+            to: email.to_string(),
+            subject: self.policy.subject.clone(),
+            body: format!(
+                "Confirm your email address by opening this link:\n\n{}\n\nThe link expires in {} hours. If you did not create an account, ignore this email.",
+                link,
+                self.policy.token_ttl / 3600
+            ),
+        })
+    }
+
+    /// Confirms a verification token, marking the address verified and
+    /// activating the account if it is pending.
+    ///
+    /// # Returns
+    /// * `Ok(String)` with the verified email address.
+    /// * `Err(AuthError::InvalidToken)` if the token is unknown, expired or already used.
+    /// * `Err(AuthError::UserNotFound)` if the account no longer exists.
+    pub fn confirm(&mut self, auth: &mut Authentication, token: &str) -> Result<String, AuthError> {
+        let now = self.clock.now();
+        let pending = self
+            .tokens
+            .remove(&hash_token(token))
+            .filter(|pending| pending.expires_at > now)
+            .ok_or(AuthError::InvalidToken)?;
+
+        let user = auth.users.get_mut(&pending.email).ok_or(AuthError::UserNotFound)?;
+        user.email_verified_at = Some(now);
+        if user.status == AccountStatus::Pending {
+            auth.activate(&pending.email)?;
+        }
+        self.sends.reset(&pending.email);
+        Ok(pending.email)
+    }
+
+    /// Removes expired tokens and send counts.
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.tokens.retain(|_, pending| pending.expires_at > now);
+        self.sends.purge_expired(now);
+    }
+}
@@ -0,0 +1,40 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::rngs::OsRng;
+use rand::{Rng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Generates a random token of `bytes` bytes of entropy, encoded as base64url.
+pub fn random_token(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// Generates a random string of `len` characters drawn uniformly from `alphabet`.
+pub fn random_string(alphabet: &[u8], len: usize) -> String {
+    (0..len)
+        .map(|_| alphabet[OsRng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+/// Returns the base64url-encoded SHA-256 digest of `input`.
+pub fn sha256_base64url(input: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(input))
+}
+
+/// Hashes a high-entropy token for storage.
+///
+/// Tokens are stored by their SHA-256 digest so that a leaked store does not
+/// reveal usable tokens.
+pub fn hash_token(token: &str) -> String {
+    sha256_base64url(token.as_bytes())
+}
+
+/// Compares two byte strings in constant time with respect to their contents.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -1,3 +1,4 @@
+use crate::model::AccountStatus;
 use thiserror::Error;
 
 /// Represents all possible errors that can occur during authentication and authorization.
@@ -49,4 +50,367 @@ pub enum AuthError {
     /// Occurs when an unsupported or unrecognized authentication strategy is provided.
     #[error("Invalid strategy in context: {0}")]
     InvalidStrategy(String),
+
+    /// Occurs when a scope token does not follow the configured scope grammar.
+    #[error("Invalid scope: {0}")]
+    InvalidScope(#[from] ScopeError),
+
+    /// Occurs when adding a scope implication would create a cycle.
+    #[error("Scope implication cycle detected: {0}")]
+    ScopeImplicationCycle(String),
+
+    /// Occurs when requested scopes are not covered by the granted scopes.
+    #[error("Requested scopes not granted: {0}")]
+    ScopeNotGranted(String),
+
+    /// Occurs when an SBA candidate scope template is malformed.
+    #[error("Invalid candidate template: {0}")]
+    InvalidTemplate(String),
+
+    /// Occurs when a token was issued for a different service (audience).
+    #[error("Token audience '{audiences}' is not valid for service '{service}'")]
+    WrongAudience {
+        /// The service the token was presented to.
+        service: String,
+        /// The audiences the token was issued for.
+        audiences: String,
+    },
+
+    /// Occurs when an already rotated refresh token is presented again.
+    ///
+    /// The token family identified by the contained id has been revoked.
+    #[error("Refresh token reuse detected; token family revoked")]
+    RefreshTokenReused(String),
+
+    /// Occurs when key material or a key set is malformed or uses an unsupported algorithm.
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    /// Occurs when token claims cannot be serialized.
+    #[error("Token encoding failed: {0}")]
+    TokenEncodingFailed(String),
+
+    /// Occurs when an ID token from an external identity provider fails validation.
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+
+    /// Occurs when an external identity matches a local account that has its own password
+    /// or is linked to another identity provider account, which is never linked implicitly.
+    #[error("An account with this email already exists and must be linked explicitly")]
+    AccountLinkRequired,
+
+    /// Occurs when a session has passed its idle or absolute timeout.
+    #[error("Session expired")]
+    SessionExpired,
+
+    /// Occurs when a one-time password is malformed or does not match.
+    #[error("Invalid one-time password")]
+    InvalidOtp,
+
+    /// Occurs when a one-time password that was already accepted is presented again.
+    #[error("One-time password has already been used")]
+    OtpReplayed,
+
+    /// Occurs when a hardware token has seen too many invalid codes in a row
+    /// (RFC 4226 section 7.3) and must be resynchronized or unlocked.
+    #[error("Too many invalid one-time passwords; the token is locked")]
+    OtpLocked,
+
+    /// Occurs when a recovery code does not match any of the user's codes.
+    #[error("Invalid recovery code")]
+    InvalidRecoveryCode,
+
+    /// Occurs when a recovery code that was already consumed is presented again.
+    #[error("Recovery code has already been used")]
+    RecoveryCodeUsed,
+
+    /// Occurs when a second factor is required but the user has not enrolled one.
+    #[error("No second factor enrolled")]
+    MfaNotEnrolled,
+
+    /// Occurs when a WebAuthn registration or assertion fails verification.
+    #[error("Invalid WebAuthn response: {0}")]
+    InvalidWebAuthnResponse(String),
+
+    /// Occurs when a WebAuthn signature counter did not increase, which suggests a cloned authenticator.
+    ///
+    /// Contains the id of the affected credential.
+    #[error("Signature counter regression detected for credential {0}")]
+    SignCountRegression(String),
+
+    /// Occurs when an account is temporarily locked after repeated failed logins.
+    #[error("Account is locked until {until}")]
+    AccountLocked {
+        /// When the lock is lifted, in seconds since the Unix epoch.
+        until: u64,
+    },
+
+    /// Occurs when a login is attempted before the backoff delay has passed,
+    /// or from a client IP that has been throttled.
+    #[error("Too many login attempts; retry after {retry_at}")]
+    TooManyAttempts {
+        /// When the next attempt is allowed, in seconds since the Unix epoch.
+        retry_at: u64,
+    },
+
+    /// Occurs when a pending account tries to log in before it has been activated.
+    #[error("Account is pending activation")]
+    AccountPending,
+
+    /// Occurs when a disabled account tries to log in or is authorized.
+    #[error("Account is disabled")]
+    AccountDisabled,
+
+    /// Occurs when an account in the `Locked` state tries to log in or is authorized.
+    ///
+    /// Unlike `AccountLocked`, this lock does not expire; it lasts until an administrator lifts it.
+    #[error("Account is suspended")]
+    AccountSuspended,
+
+    /// Occurs when a soft-deleted account tries to log in or is authorized.
+    #[error("Account has been deleted")]
+    AccountDeleted,
+
+    /// Occurs when an account state change is not allowed, e.g. re-enabling a deleted account.
+    #[error("Cannot change account status from {from} to {to}")]
+    InvalidStatusTransition {
+        /// The current state.
+        from: AccountStatus,
+        /// The requested state.
+        to: AccountStatus,
+    },
+
+    /// Occurs when a string given as an email address is malformed.
+    #[error("Invalid email address: {0}")]
+    InvalidEmail(String),
+
+    /// Occurs when a verification email is requested for an address that is already verified.
+    #[error("Email address is already verified")]
+    EmailAlreadyVerified,
+
+    /// Occurs when a `Mailer` fails to send an email.
+    #[error("Mail delivery failed: {0}")]
+    MailDeliveryFailed(String),
+
+    /// Occurs when a passwordless login is completed from another browser or session
+    /// than the one that requested it.
+    #[error("Login was requested from another browser or session")]
+    LoginBindingMismatch,
+
+    /// Occurs when a password does not satisfy the `PasswordPolicy`.
+    ///
+    /// Contains every rule the password broke, so they can all be shown to the user at once.
+    #[error("Password does not meet the policy: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    PasswordRejected(Vec<PasswordViolation>),
+
+    /// Occurs when an OAuth2 request is rejected by the authorization server.
+    #[error("OAuth2 error: {0}")]
+    OAuth(#[from] OAuthError),
+}
+
+/// Describes why a scope token failed to parse against a `ScopeGrammar`.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ScopeError {
+    /// Occurs when the scope token is an empty string.
+    #[error("scope is empty")]
+    Empty,
+
+    /// Occurs when two delimiters are adjacent, or the scope starts or ends with one.
+    #[error("segment {position} is empty")]
+    EmptySegment {
+        /// Zero-based index of the offending segment.
+        position: usize,
+    },
+
+    /// Occurs when a segment contains a character outside the allowed set.
+    #[error("invalid character '{character}' in segment {position}")]
+    InvalidCharacter {
+        /// The disallowed character.
+        character: char,
+        /// Zero-based index of the offending segment.
+        position: usize,
+    },
+
+    /// Occurs when a segment is longer than the grammar allows.
+    #[error("segment {position} is longer than {max} characters")]
+    SegmentTooLong {
+        /// Zero-based index of the offending segment.
+        position: usize,
+        /// The maximum allowed segment length.
+        max: usize,
+    },
+
+    /// Occurs when the scope has fewer segments than the grammar requires.
+    #[error("expected at least {min} segments, found {found}")]
+    TooFewSegments {
+        /// The minimum number of segments.
+        min: usize,
+        /// The number of segments found.
+        found: usize,
+    },
+
+    /// Occurs when the scope has more segments than the grammar allows.
+    #[error("expected at most {max} segments, found {found}")]
+    TooManySegments {
+        /// The maximum number of segments.
+        max: usize,
+        /// The number of segments found.
+        found: usize,
+    },
+
+    /// Occurs when a wildcard segment is used but the grammar forbids it.
+    #[error("wildcard is not allowed in segment {position}")]
+    WildcardNotAllowed {
+        /// Zero-based index of the offending segment.
+        position: usize,
+    },
+}
+
+/// Describes a rule of a `PasswordPolicy` that a password breaks.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum PasswordViolation {
+    /// Occurs when the password has fewer characters than required.
+    #[error("must be at least {min} characters long")]
+    TooShort {
+        /// The minimum number of characters.
+        min: usize,
+        /// The number of characters found.
+        found: usize,
+    },
+
+    /// Occurs when the password is longer than allowed, measured in UTF-8 bytes
+    /// since that is what bcrypt limits.
+    #[error("must be at most {max} bytes long (non-ASCII characters take several bytes)")]
+    TooLong {
+        /// The maximum number of bytes.
+        max: usize,
+        /// The number of bytes found.
+        found: usize,
+    },
+
+    /// Occurs when the password has no lowercase letter but the policy requires one.
+    #[error("must contain a lowercase letter")]
+    MissingLowercase,
+
+    /// Occurs when the password has no uppercase letter but the policy requires one.
+    #[error("must contain an uppercase letter")]
+    MissingUppercase,
+
+    /// Occurs when the password has no digit but the policy requires one.
+    #[error("must contain a digit")]
+    MissingDigit,
+
+    /// Occurs when the password has no symbol but the policy requires one.
+    #[error("must contain a symbol")]
+    MissingSymbol,
+
+    /// Occurs when the password contains a banned word, or a part of the user's email address.
+    #[error("must not contain '{0}'")]
+    BannedWord(String),
+
+    /// Occurs when the password matches the current one or one of the last passwords.
+    #[error("must not reuse any of the last {history} passwords")]
+    Reused {
+        /// How many previous passwords are checked.
+        history: usize,
+    },
+
+    /// Occurs when the estimated entropy is below the policy minimum.
+    #[error("is too easy to guess ({bits} of {min_bits} bits)")]
+    TooWeak {
+        /// The estimated entropy, in bits.
+        bits: u32,
+        /// The minimum entropy, in bits.
+        min_bits: u32,
+    },
+}
+
+/// Describes an OAuth2 protocol error, as defined by RFC 6749 and its extensions.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum OAuthError {
+    /// The request is missing a parameter or is otherwise malformed.
+    #[error("invalid_request: {0}")]
+    InvalidRequest(String),
+
+    /// Client authentication failed, or the client is unknown.
+    #[error("invalid_client: {0}")]
+    InvalidClient(String),
+
+    /// The authorization grant is invalid, expired, revoked or was issued to another client.
+    #[error("invalid_grant: {0}")]
+    InvalidGrant(String),
+
+    /// The client is not allowed to use this grant type.
+    #[error("unauthorized_client: {0}")]
+    UnauthorizedClient(String),
+
+    /// The grant type is not supported by the server.
+    #[error("unsupported_grant_type: {0}")]
+    UnsupportedGrantType(String),
+
+    /// The response type is not supported by the server.
+    #[error("unsupported_response_type: {0}")]
+    UnsupportedResponseType(String),
+
+    /// The requested scope is invalid or exceeds what the client may request.
+    #[error("invalid_scope: {0}")]
+    InvalidScope(String),
+
+    /// The resource owner or server denied the request.
+    #[error("access_denied: {0}")]
+    AccessDenied(String),
+
+    /// The user has not yet approved the device authorization request (RFC 8628).
+    #[error("authorization_pending: {0}")]
+    AuthorizationPending(String),
+
+    /// The device is polling too often and must increase its interval (RFC 8628).
+    #[error("slow_down: {0}")]
+    SlowDown(String),
+
+    /// The device code has expired and the device must start over (RFC 8628).
+    #[error("expired_token: {0}")]
+    ExpiredToken(String),
+
+    /// The requested audience or resource is unknown or not allowed (RFC 8693).
+    #[error("invalid_target: {0}")]
+    InvalidTarget(String),
+}
+
+impl OAuthError {
+    /// Returns the RFC 6749 `error` code for this error.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient(_) => "invalid_client",
+            OAuthError::InvalidGrant(_) => "invalid_grant",
+            OAuthError::UnauthorizedClient(_) => "unauthorized_client",
+            OAuthError::UnsupportedGrantType(_) => "unsupported_grant_type",
+            OAuthError::UnsupportedResponseType(_) => "unsupported_response_type",
+            OAuthError::InvalidScope(_) => "invalid_scope",
+            OAuthError::AccessDenied(_) => "access_denied",
+            OAuthError::AuthorizationPending(_) => "authorization_pending",
+            OAuthError::SlowDown(_) => "slow_down",
+            OAuthError::ExpiredToken(_) => "expired_token",
+            OAuthError::InvalidTarget(_) => "invalid_target",
+        }
+    }
+
+    /// Returns the human-readable `error_description` for this error.
+    pub fn description(&self) -> &str {
+        match self {
+            OAuthError::InvalidRequest(d)
+            | OAuthError::InvalidClient(d)
+            | OAuthError::InvalidGrant(d)
+            | OAuthError::UnauthorizedClient(d)
+            | OAuthError::UnsupportedGrantType(d)
+            | OAuthError::UnsupportedResponseType(d)
+            | OAuthError::InvalidScope(d)
+            | OAuthError::AccessDenied(d)
+            | OAuthError::AuthorizationPending(d)
+            | OAuthError::SlowDown(d)
+            | OAuthError::ExpiredToken(d)
+            | OAuthError::InvalidTarget(d) => d,
+        }
+    }
 }
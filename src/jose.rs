@@ -0,0 +1,355 @@
+use crate::error::AuthError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::{Signer as _, Verifier as _};
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A JWS signature algorithm supported by this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwsAlgorithm {
+    /// ECDSA using P-256 and SHA-256.
+    ES256,
+    /// Ed25519 signatures.
+    EdDSA,
+}
+
+impl JwsAlgorithm {
+    /// Returns the `alg` header value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwsAlgorithm::ES256 => "ES256",
+            JwsAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    /// Parses an `alg` header value.
+    ///
+    /// # Returns
+    /// * `Ok(JwsAlgorithm)` for `ES256` and `EdDSA`.
+    /// * `Err(AuthError::InvalidKey)` for any other algorithm, including `none`.
+    pub fn parse(alg: &str) -> Result<Self, AuthError> {
+        match alg {
+            "ES256" => Ok(JwsAlgorithm::ES256),
+            "EdDSA" => Ok(JwsAlgorithm::EdDSA),
+            other => Err(AuthError::InvalidKey(format!("unsupported algorithm '{}'", other))),
+        }
+    }
+}
+
+/// A public JSON Web Key (RFC 7517).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jwk {
+    /// Key type: `EC` or `OKP`.
+    pub kty: String,
+    /// Curve: `P-256` or `Ed25519`.
+    pub crv: String,
+    /// Base64url x coordinate (or the Ed25519 public key).
+    pub x: String,
+    /// Base64url y coordinate, for EC keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    /// Key identifier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// Algorithm the key is used with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// Intended use, `sig` for signing keys.
+    #[serde(rename = "use", default, skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<String>,
+}
+
+impl Jwk {
+    /// Returns the algorithm implied by the key type and curve.
+    pub fn algorithm(&self) -> Result<JwsAlgorithm, AuthError> {
+        match (self.kty.as_str(), self.crv.as_str()) {
+            ("EC", "P-256") => Ok(JwsAlgorithm::ES256),
+            ("OKP", "Ed25519") => Ok(JwsAlgorithm::EdDSA),
+            (kty, crv) => Err(AuthError::InvalidKey(format!("unsupported key type '{}' / '{}'", kty, crv))),
+        }
+    }
+
+    /// Verifies a raw signature over `message` with this key.
+    ///
+    /// ES256 signatures are the 64-byte `r || s` form used by JWS.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if the signature is valid.
+    /// * `Ok(false)` if it is not.
+    /// * `Err(AuthError::InvalidKey)` if the key is malformed or unsupported.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool, AuthError> {
+        let invalid = || AuthError::InvalidKey("malformed public key".to_string());
+        let x = URL_SAFE_NO_PAD.decode(&self.x).map_err(|_| invalid())?;
+
+        match self.algorithm()? {
+            JwsAlgorithm::ES256 => {
+                let y = URL_SAFE_NO_PAD
+                    .decode(self.y.as_deref().ok_or_else(invalid)?)
+                    .map_err(|_| invalid())?;
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(invalid());
+                }
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    p256::FieldBytes::from_slice(&x),
+                    p256::FieldBytes::from_slice(&y),
+                    false,
+                );
+                let key = p256::ecdsa::VerifyingKey::from_encoded_point(&point).map_err(|_| invalid())?;
+                let Ok(signature) = p256::ecdsa::Signature::from_slice(signature) else {
+                    return Ok(false);
+                };
+                Ok(key.verify(message, &signature).is_ok())
+            }
+            JwsAlgorithm::EdDSA => {
+                let bytes: [u8; 32] = x.try_into().map_err(|_| invalid())?;
+                let key = ed25519_dalek::VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())?;
+                let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+                    return Ok(false);
+                };
+                Ok(key.verify(message, &signature).is_ok())
+            }
+        }
+    }
+}
+
+/// A JSON Web Key Set, as served from a `jwks_uri`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Jwks {
+    /// The keys in the set.
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    /// Returns the key with the given `kid`.
+    pub fn find(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|k| k.kid.as_deref() == Some(kid))
+    }
+
+    /// Serializes the set as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| r#"{"keys":[]}"#.to_string())
+    }
+
+    /// Parses a JWKS document.
+    pub fn from_json(json: &str) -> Result<Self, AuthError> {
+        serde_json::from_str(json).map_err(|e| AuthError::InvalidKey(format!("malformed JWKS: {}", e)))
+    }
+}
+
+#[derive(Clone)]
+enum KeyMaterial {
+    Es256(p256::ecdsa::SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+/// A private key used to sign JWS tokens, identified by a `kid`.
+///
+/// # Example
+/// ```code
+/// let key = SigningKey::generate(JwsAlgorithm::ES256, "2025-01");
+/// let token = jose::sign(&claims, &key)?;
+/// let jwks = Jwks { keys: vec![key.public_jwk()] };
+/// let claims: MyClaims = jose::verify(&token, &jwks)?;
+/// ```
+#[derive(Clone)]
+pub struct SigningKey {
+    kid: String,
+    material: KeyMaterial,
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("kid", &self.kid)
+            .field("alg", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKey {
+    /// Generates a new random key for `algorithm`.
+    pub fn generate(algorithm: JwsAlgorithm, kid: &str) -> Self {
+        let material = match algorithm {
+            JwsAlgorithm::ES256 => KeyMaterial::Es256(p256::ecdsa::SigningKey::random(&mut OsRng)),
+            JwsAlgorithm::EdDSA => KeyMaterial::Ed25519(ed25519_dalek::SigningKey::generate(&mut OsRng)),
+        };
+        Self { kid: kid.to_string(), material }
+    }
+
+    /// Loads a key from its 32-byte secret scalar (ES256) or seed (EdDSA).
+    ///
+    /// # Returns
+    /// * `Ok(SigningKey)` if the bytes form a valid key.
+    /// * `Err(AuthError::InvalidKey)` otherwise.
+    pub fn from_bytes(algorithm: JwsAlgorithm, kid: &str, secret: &[u8]) -> Result<Self, AuthError> {
+        let invalid = || AuthError::InvalidKey("malformed private key".to_string());
+        let material = match algorithm {
+            JwsAlgorithm::ES256 => {
+                KeyMaterial::Es256(p256::ecdsa::SigningKey::from_slice(secret).map_err(|_| invalid())?)
+            }
+            JwsAlgorithm::EdDSA => {
+                let seed: [u8; 32] = secret.try_into().map_err(|_| invalid())?;
+                KeyMaterial::Ed25519(ed25519_dalek::SigningKey::from_bytes(&seed))
+            }
+        };
+        Ok(Self { kid: kid.to_string(), material })
+    }
+
+    /// Returns the key identifier.
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// Returns the signature algorithm of this key.
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        match self.material {
+            KeyMaterial::Es256(_) => JwsAlgorithm::ES256,
+            KeyMaterial::Ed25519(_) => JwsAlgorithm::EdDSA,
+        }
+    }
+
+    /// Returns the public half of the key as a JWK.
+    pub fn public_jwk(&self) -> Jwk {
+        let (kty, crv, x, y) = match &self.material {
+            KeyMaterial::Es256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                (
+                    "EC",
+                    "P-256",
+                    URL_SAFE_NO_PAD.encode(point.x().expect("uncompressed point")),
+                    Some(URL_SAFE_NO_PAD.encode(point.y().expect("uncompressed point"))),
+                )
+            }
+            KeyMaterial::Ed25519(key) => ("OKP", "Ed25519", URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()), None),
+        };
+        Jwk {
+            kty: kty.to_string(),
+            crv: crv.to_string(),
+            x,
+            y,
+            kid: Some(self.kid.clone()),
+            alg: Some(self.algorithm().as_str().to_string()),
+            key_use: Some("sig".to_string()),
+        }
+    }
+
+    /// Signs `message`, returning the raw JWS signature.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match &self.material {
+            KeyMaterial::Es256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(message);
+                signature.to_bytes().to_vec()
+            }
+            KeyMaterial::Ed25519(key) => key.sign(message).to_bytes().to_vec(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+}
+
+/// Signs `claims` as a compact JWS (a JWT).
+///
+/// # Returns
+/// * `Ok(String)` with the compact token.
+/// * `Err(AuthError::TokenEncodingFailed)` if the claims cannot be serialized.
+pub fn sign<T: Serialize>(claims: &T, key: &SigningKey) -> Result<String, AuthError> {
+    let header = Header {
+        alg: key.algorithm().as_str().to_string(),
+        kid: Some(key.kid.clone()),
+        typ: Some("JWT".to_string()),
+    };
+    let signing_input = format!("{}.{}", encode_part(&header)?, encode_part(claims)?);
+    let signature = URL_SAFE_NO_PAD.encode(key.sign(signing_input.as_bytes()));
+    Ok(format!("{}.{}", signing_input, signature))
+}
+
+/// Verifies a compact JWS against a key set and deserializes its claims.
+///
+/// The key is chosen by the `kid` header, or is the only key of the set if the
+/// token has no `kid`. The `alg` header must match the key. Claim checks such
+/// as expiry or audience are left to the caller.
+///
+/// # Returns
+/// * `Ok(T)` with the verified claims.
+/// * `Err(AuthError::InvalidToken)` if the token is malformed, the key is unknown or
+///   the signature is invalid.
+pub fn verify<T: DeserializeOwned>(token: &str, jwks: &Jwks) -> Result<T, AuthError> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(AuthError::InvalidToken);
+    };
+
+    let header: Header = decode_part(header)?;
+    let key = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or(AuthError::InvalidToken)?;
+
+    let alg = JwsAlgorithm::parse(&header.alg).map_err(|_| AuthError::InvalidToken)?;
+    if key.algorithm().map_err(|_| AuthError::InvalidToken)? != alg
+        || key.alg.as_deref().is_some_and(|a| a != alg.as_str())
+    {
+        return Err(AuthError::InvalidToken);
+    }
+
+    let signature_bytes = URL_SAFE_NO_PAD.decode(signature).map_err(|_| AuthError::InvalidToken)?;
+    let signing_input = &token[..token.len() - signature_len(token)];
+    if !key.verify(signing_input.as_bytes(), &signature_bytes).map_err(|_| AuthError::InvalidToken)? {
+        return Err(AuthError::InvalidToken);
+    }
+    decode_part(payload)
+}
+
+fn signature_len(token: &str) -> usize {
+    token.rfind('.').map_or(token.len(), |i| token.len() - i)
+}
+
+fn encode_part<T: Serialize + ?Sized>(value: &T) -> Result<String, AuthError> {
+    serde_json::to_vec(value)
+        .map(|json| URL_SAFE_NO_PAD.encode(json))
+        .map_err(|e| AuthError::TokenEncodingFailed(e.to_string()))
+}
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T, AuthError> {
+    let bytes = URL_SAFE_NO_PAD.decode(part).map_err(|_| AuthError::InvalidToken)?;
+    serde_json::from_slice(&bytes).map_err(|_| AuthError::InvalidToken)
+}
+
+/// Serde support for claims such as `aud` that may be a single string or an array
+/// of strings. A single value is written back as a string.
+pub(crate) mod one_or_many {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn serialize<S: Serializer>(values: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        match values {
+            [one] => one.serialize(serializer),
+            many => many.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        })
+    }
+}
@@ -10,10 +10,19 @@
 //! 
 //! ## ✨ Features
 //! 
-//! - **Authentication (auth_n)**: Handles register, login, and reset_password.
+//! - **Authentication (auth_n)**: Handles register, login, and reset_password, with account lifecycle states (pending, active, disabled, locked, deleted) and admin APIs to disable, re-enable and soft-delete accounts.
+//! - **Sessions (session)**: Server-side sessions with idle and absolute timeouts, id rotation on privilege change, and per-user listing and revocation.
+//! - **Lockout (lockout)**: Per-account and per-IP failed-login counters with exponential backoff, temporary lockout with automatic or administrator unlock, and a pluggable counter store.
+//! - **Email verification (verification)**: Opt-in verification of registration emails with hashed single-use tokens, a pluggable `Mailer` (in-memory and file mailers included), rate-limited resends, and blocked or restricted access for unverified accounts.
+//! - **Password reset (reset)**: Built-in reset tokens that are high-entropy, stored hashed, single-use, short-lived and bound to the user's current password, with optional session revocation on reset.
+//! - **Passwordless login (passwordless)**: Magic links and 6-digit email codes with expiry, attempt limits and binding to the requesting browser or session, delivered through the pluggable `Mailer`.
+//! - **Password policy (password)**: Plaintext password APIs for registration, password changes and resets, enforcing length, character classes, banned words, no reuse of recent passwords and a zxcvbn-style entropy estimate, with structured violations for display.
+//! - **Multi-factor authentication (mfa)**: TOTP (RFC 6238) enrollment with `otpauth://` provisioning URIs, optional SVG QR codes (`qr` feature), replay protection, HOTP (RFC 4226) with look-ahead resynchronisation, hashed single-use recovery codes, and partial "MFA required" logins.
+//! - **WebAuthn (webauthn)**: Passkey and security-key registration and sign-in with ES256 and EdDSA credentials, `none` and `packed` self-attestation, and signature counter regression detection.
 //! - **Authorization (auth_z)**: Supports **ABAC** (Attribute-Based Access Control), 
 //!   **RBAC** (Role-Based Access Control), and **SBA** (Scope-Based Authorization)
 //! - **Scope matching**: Flexible support for OAuth2-style scopes with customizable formats.
+//! - **OAuth2 (oauth2)**: Framework-agnostic authorization server with the authorization code + PKCE, client credentials and device authorization flows, refresh token rotation, token introspection and revocation, token exchange with delegation and impersonation, a minimal OpenID Connect provider, and a relying-party helper for external identity providers.
 //!
 //!---
 //! 
@@ -37,11 +46,13 @@
 //!         },
 //!         department: "engineering".to_string(),
 //!         clearance_level: 5,
+//!         ..Default::default()
 //!     };
 //!
 //!     let resource = Resource {
 //!         department: "engineering".to_string(),
 //!         required_level: 3,
+//!         ..Default::default()
 //!     };
 //!
 //!     let context = AuthContext {
@@ -129,6 +140,7 @@
 //!         email: "jwt@example.com".to_string(),
 //!         service: "admin_service".to_string(),
 //!         scopes: vec!["admin_service:create".to_string()],
+//!         ..Default::default()
 //!     };
 //!
 //!     let context = AuthContext {
@@ -191,4 +203,25 @@ pub mod error;
 pub mod auth;
 
 /// Common data types used in authentication and policy evaluation.
-pub mod model;
\ No newline at end of file
+pub mod model;
+
+/// Time sources used for expiry checks.
+pub mod clock;
+
+/// Cryptographic helpers for random tokens and hashing.
+pub mod crypto;
+
+/// JSON Web Signatures and keys (ES256 and EdDSA).
+pub mod jose;
+
+/// Outgoing email delivery.
+pub mod mail;
+
+/// Multi-factor authentication.
+pub mod mfa;
+
+/// WebAuthn (passkey) registration and authentication.
+pub mod webauthn;
+
+/// OAuth2 authorization server flows.
+pub mod oauth2;
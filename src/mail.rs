@@ -0,0 +1,177 @@
+use crate::error::AuthError;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// An outgoing email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    /// The recipient address.
+    pub to: String,
+    /// The subject line.
+    pub subject: String,
+    /// The plain-text body.
+    pub body: String,
+}
+
+/// Delivers emails, e.g. through SMTP or a transactional email API.
+pub trait Mailer: Send + Sync {
+    /// Sends an email.
+    ///
+    /// # Returns
+    /// * `Ok(())` once the email has been accepted for delivery.
+    /// * `Err(AuthError::MailDeliveryFailed)` otherwise.
+    fn send(&self, email: &Email) -> Result<(), AuthError>;
+}
+
+/// A `Mailer` that keeps sent emails in memory, intended for tests.
+///
+/// Clones share the same outbox, so a test can keep a handle and read what a
+/// service has sent.
+///
+/// # Example
+/// ```code
+/// let mailer = InMemoryMailer::new();
+/// let mut verification = EmailVerification::new(Box::new(mailer.clone()), VerificationPolicy::default());
+/// verification.register(&mut auth, "user@example.com", &password_hash)?;
+/// let email = mailer.last_to("user@example.com").unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryMailer {
+    outbox: Arc<Mutex<Vec<Email>>>,
+}
+
+impl InMemoryMailer {
+    /// Creates a mailer with an empty outbox.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns every email sent so far, oldest first.
+    pub fn sent(&self) -> Vec<Email> {
+        self.outbox.lock().map(|outbox| outbox.clone()).unwrap_or_default()
+    }
+
+    /// Returns the most recent email sent to `to`.
+    pub fn last_to(&self, to: &str) -> Option<Email> {
+        self.sent().into_iter().rev().find(|email| email.to == to)
+    }
+}
+
+impl Mailer for InMemoryMailer {
+    fn send(&self, email: &Email) -> Result<(), AuthError> {
+        self.outbox
+            .lock()
+            .map_err(|_| AuthError::MailDeliveryFailed("outbox lock poisoned".to_string()))?
+            .push(email.clone());
+        Ok(())
+    }
+}
+
+/// A `Mailer` that appends emails to a file instead of delivering them, for
+/// local development.
+#[derive(Debug, Clone)]
+pub struct FileMailer {
+    path: PathBuf,
+}
+
+impl FileMailer {
+    /// Creates a mailer writing to `path`. The file is created on first use.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, email: &Email) -> Result<(), AuthError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| AuthError::MailDeliveryFailed(e.to_string()))?;
+        writeln!(file, "To: {}\nSubject: {}\n\n{}\n", email.to, email.subject, email.body)
+            .map_err(|e| AuthError::MailDeliveryFailed(e.to_string()))
+    }
+}
+
+/// Limits how often emails are sent to the same address: at most one per
+/// `resend_interval`, and at most `max_sends` within any `window`.
+///
+/// # Example
+/// ```code
+/// let mut limiter = SendLimiter::new(60, 5, 3600);
+/// limiter.record("user@example.com", now)?;
+/// mailer.send(&email)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SendLimiter {
+    resend_interval: u64,
+    max_sends: usize,
+    window: u64,
+    sends: HashMap<String, Vec<u64>>,
+}
+
+impl SendLimiter {
+    /// Creates a limiter with the given interval, count and window (in seconds).
+    pub fn new(resend_interval: u64, max_sends: usize, window: u64) -> Self {
+        Self { resend_interval, max_sends, window, sends: HashMap::new() }
+    }
+
+    /// Records a send to `to` if the limits allow it.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the email may be sent; the send is counted.
+    /// * `Err(AuthError::TooManyAttempts)` if `to` was emailed less than `resend_interval`
+    ///   ago or has reached `max_sends` in the current window.
+    pub fn record(&mut self, to: &str, now: u64) -> Result<(), AuthError> {
+        let window_start = now.saturating_sub(self.window);
+        let sends = self.sends.entry(to.to_string()).or_default();
+        sends.retain(|at| *at > window_start);
+        if let Some(last) = sends.last()
+            && now < last + self.resend_interval
+        {
+            return Err(AuthError::TooManyAttempts { retry_at: last + self.resend_interval });
+        }
+        if sends.len() >= self.max_sends {
+            return Err(AuthError::TooManyAttempts { retry_at: sends[0] + self.window });
+        }
+        sends.push(now);
+        Ok(())
+    }
+
+    /// Forgets the sends to `to`, e.g. once the flow they belong to has completed.
+    pub fn reset(&mut self, to: &str) {
+        self.sends.remove(to);
+    }
+
+    /// Forgets addresses with no send in the current window.
+    pub fn purge_expired(&mut self, now: u64) {
+        let window_start = now.saturating_sub(self.window);
+        self.sends.retain(|_, sends| sends.last().is_some_and(|at| *at > window_start));
+    }
+}
+
+/// Checks that `email` looks like a deliverable address: a non-empty local part,
+/// a single `@`, and a dotted domain, with no whitespace.
+///
+/// This is deliberately lenient; only a verification email proves an address works.
+///
+/// # Returns
+/// * `Ok(())` if the address is plausible.
+/// * `Err(AuthError::InvalidEmail)` otherwise.
+pub fn validate_email(email: &str) -> Result<(), AuthError> {
+    let invalid = || AuthError::InvalidEmail(email.to_string());
+    if email.len() > 254 || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid());
+    }
+    let (local, domain) = email.split_once('@').ok_or_else(invalid)?;
+    let domain_ok = domain.contains('.')
+        && !domain.contains('@')
+        && domain.split('.').all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'));
+    if local.is_empty() || local.len() > 64 || !domain_ok {
+        return Err(invalid());
+    }
+    Ok(())
+}
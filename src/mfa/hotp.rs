@@ -0,0 +1,224 @@
+use crate::crypto::constant_time_eq;
+use crate::error::AuthError;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::collections::HashMap;
+
+/// HMAC algorithm used to derive one-time passwords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OtpAlgorithm {
+    /// HMAC-SHA1, the only algorithm every authenticator app supports.
+    #[default]
+    Sha1,
+    /// HMAC-SHA256.
+    Sha256,
+    /// HMAC-SHA512.
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    /// Returns the `algorithm` parameter value used in `otpauth://` URIs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// Computes an HOTP value (RFC 4226 section 5.3) for `counter`.
+///
+/// # Arguments
+/// * `secret` - The shared secret.
+/// * `counter` - The moving factor; for TOTP, the current time step.
+/// * `digits` - Number of decimal digits in the code (6 to 8).
+/// * `algorithm` - The HMAC algorithm.
+///
+/// # Example
+/// ```code
+/// assert_eq!(hotp(b"12345678901234567890", 0, 6, OtpAlgorithm::Sha1), "755224");
+/// ```
+pub fn hotp(secret: &[u8], counter: u64, digits: u32, algorithm: OtpAlgorithm) -> String {
+    let message = counter.to_be_bytes();
+    let digest = match algorithm {
+        OtpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(secret, &message),
+        OtpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(secret, &message),
+        OtpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(secret, &message),
+    };
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]]) & 0x7fff_ffff;
+    format!("{:0width$}", binary as u64 % 10u64.pow(digits), width = digits as usize)
+}
+
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Settings shared by every HOTP credential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotpConfig {
+    /// Number of digits per code.
+    pub digits: u32,
+    /// HMAC algorithm.
+    pub algorithm: OtpAlgorithm,
+    /// How many counter values past the expected one `verify` accepts (RFC 4226 section 7.4).
+    pub look_ahead: u64,
+    /// How many counter values past the expected one `resync` searches.
+    pub resync_window: u64,
+    /// Consecutive invalid codes after which the token is locked (RFC 4226 section 7.3).
+    pub max_failures: u32,
+}
+
+impl Default for HotpConfig {
+    fn default() -> Self {
+        Self {
+            digits: 6,
+            algorithm: OtpAlgorithm::Sha1,
+            look_ahead: 10,
+            resync_window: 100,
+            max_failures: 5,
+        }
+    }
+}
+
+/// A hardware token's secret and the next counter value the server expects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotpCredential {
+    /// The shared secret.
+    pub secret: Vec<u8>,
+    /// The next counter value; every accepted code moves it past the matched value.
+    pub counter: u64,
+    /// Invalid codes presented since the last accepted one.
+    pub failures: u32,
+}
+
+/// Counter-based one-time passwords (RFC 4226) for hardware tokens.
+///
+/// Tokens advance their counter on every button press, so the server accepts a
+/// code up to `look_ahead` values ahead of the counter it expects. A token that
+/// has drifted further can be brought back with `resync` and two consecutive codes.
+/// After `max_failures` invalid codes in a row the token is locked until it is
+/// resynchronized or unlocked, so codes cannot be guessed without limit.
+///
+/// # Example
+/// ```code
+/// let mut hotp = HotpAuthenticator::new(HotpConfig::default());
+/// hotp.register("user@example.com", secret_from_token_vendor, 0);
+/// hotp.verify("user@example.com", "755224")?;
+/// ```
+#[derive(Debug, Default)]
+pub struct HotpAuthenticator {
+    config: HotpConfig,
+    credentials: HashMap<String, HotpCredential>,
+}
+
+impl HotpAuthenticator {
+    /// Creates an authenticator with no credentials.
+    pub fn new(config: HotpConfig) -> Self {
+        Self {
+            config,
+            credentials: HashMap::new(),
+        }
+    }
+
+    /// Returns the HOTP settings.
+    pub fn config(&self) -> &HotpConfig {
+        &self.config
+    }
+
+    /// Registers a hardware token for `subject`, replacing any previous one.
+    ///
+    /// # Arguments
+    /// * `subject` - The user identity (see `Identifiable`).
+    /// * `secret` - The token secret, as provided by the token vendor.
+    /// * `counter` - The token's current counter, usually `0` for a new token.
+    pub fn register(&mut self, subject: &str, secret: Vec<u8>, counter: u64) {
+        self.credentials.insert(subject.to_string(), HotpCredential { secret, counter, failures: 0 });
+    }
+
+    /// Returns `true` if `subject` has a registered token.
+    pub fn is_enrolled(&self, subject: &str) -> bool {
+        self.credentials.contains_key(subject)
+    }
+
+    /// Returns the credential of `subject`, if any.
+    pub fn credential(&self, subject: &str) -> Option<&HotpCredential> {
+        self.credentials.get(subject)
+    }
+
+    /// Removes the token of `subject`.
+    pub fn disable(&mut self, subject: &str) {
+        self.credentials.remove(subject);
+    }
+
+    /// Clears the failure count of a locked token, e.g. after an administrator checked the user.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the token was unlocked.
+    /// * `Err(AuthError::MfaNotEnrolled)` if `subject` has no token.
+    pub fn unlock(&mut self, subject: &str) -> Result<(), AuthError> {
+        let credential = self.credentials.get_mut(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        credential.failures = 0;
+        Ok(())
+    }
+
+    /// Verifies a code within the look-ahead window and advances the counter past it.
+    ///
+    /// A code can only be used once, since the counter moves past it.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the code is valid.
+    /// * `Err(AuthError::MfaNotEnrolled)` if `subject` has no token.
+    /// * `Err(AuthError::OtpLocked)` if the token is locked after `max_failures` invalid codes.
+    /// * `Err(AuthError::InvalidOtp)` if the code does not match any counter in the window.
+    pub fn verify(&mut self, subject: &str, code: &str) -> Result<(), AuthError> {
+        let config = self.config;
+        let credential = self.credentials.get_mut(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        if credential.failures >= config.max_failures {
+            return Err(AuthError::OtpLocked);
+        }
+        let last = credential.counter.saturating_add(config.look_ahead);
+        let Some(counter) = (credential.counter..=last).find(|&counter| matches_code(&config, &credential.secret, counter, code)) else {
+            credential.failures += 1;
+            return Err(AuthError::InvalidOtp);
+        };
+        credential.counter = counter + 1;
+        credential.failures = 0;
+        Ok(())
+    }
+
+    /// Resynchronizes a token that has drifted beyond the look-ahead window.
+    ///
+    /// The user presses the token button twice; both codes must match consecutive
+    /// counter values within `resync_window` of the expected counter. Matching two
+    /// consecutive codes also unlocks a locked token.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the codes matched; the counter moves past the second one.
+    /// * `Err(AuthError::MfaNotEnrolled)` if `subject` has no token.
+    /// * `Err(AuthError::InvalidOtp)` if no consecutive pair matched.
+    pub fn resync(&mut self, subject: &str, first: &str, second: &str) -> Result<(), AuthError> {
+        let config = self.config;
+        let credential = self.credentials.get_mut(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        let last = credential.counter.saturating_add(config.resync_window);
+        let counter = (credential.counter..=last)
+            .find(|&counter| {
+                matches_code(&config, &credential.secret, counter, first)
+                    && matches_code(&config, &credential.secret, counter + 1, second)
+            })
+            .ok_or(AuthError::InvalidOtp)?;
+        credential.counter = counter + 2;
+        credential.failures = 0;
+        Ok(())
+    }
+}
+
+fn matches_code(config: &HotpConfig, secret: &[u8], counter: u64, code: &str) -> bool {
+    let code = code.trim();
+    let expected = hotp(secret, counter, config.digits, config.algorithm);
+    constant_time_eq(expected.as_bytes(), code.as_bytes())
+}
@@ -0,0 +1,215 @@
+use crate::auth::auth_n::Authentication;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{hash_token, random_token};
+use crate::error::AuthError;
+use crate::mfa::hotp::HotpAuthenticator;
+use crate::mfa::recovery::remaining_recovery_codes;
+use crate::mfa::totp::TotpAuthenticator;
+use crate::model::{Identifiable, User};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The result of the first login step.
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    /// The user has no second factor; login is complete.
+    Authenticated(Box<User>),
+    /// The user must present a second factor before login completes.
+    MfaRequired(MfaChallenge),
+}
+
+/// A login that is waiting for its second factor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MfaChallenge {
+    /// Opaque token identifying the pending login; hand it back to `complete`.
+    pub token: String,
+    /// The user identity (see `Identifiable`).
+    pub subject: String,
+    /// When the challenge expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+/// A second factor that can complete a pending login.
+///
+/// Implemented for `TotpAuthenticator`, `HotpAuthenticator` and `Authentication`
+/// (recovery codes); factors are looked up by the user identity (see `Identifiable`).
+pub trait SecondFactor {
+    /// Returns `true` if `user` has this factor enrolled.
+    fn is_enrolled(&self, user: &User) -> bool;
+
+    /// Checks a code presented by `user`, consuming it if it is single-use.
+    fn verify(&mut self, user: &User, code: &str) -> Result<(), AuthError>;
+}
+
+impl SecondFactor for TotpAuthenticator {
+    fn is_enrolled(&self, user: &User) -> bool {
+        TotpAuthenticator::is_enrolled(self, &user.identity())
+    }
+
+    fn verify(&mut self, user: &User, code: &str) -> Result<(), AuthError> {
+        TotpAuthenticator::verify(self, &user.identity(), code)
+    }
+}
+
+impl SecondFactor for HotpAuthenticator {
+    fn is_enrolled(&self, user: &User) -> bool {
+        HotpAuthenticator::is_enrolled(self, &user.identity())
+    }
+
+    fn verify(&mut self, user: &User, code: &str) -> Result<(), AuthError> {
+        HotpAuthenticator::verify(self, &user.identity(), code)
+    }
+}
+
+/// Recovery codes, which are stored on the user and consumed through `Authentication`.
+impl SecondFactor for Authentication {
+    fn is_enrolled(&self, user: &User) -> bool {
+        remaining_recovery_codes(&user.recovery_codes) > 0
+    }
+
+    fn verify(&mut self, user: &User, code: &str) -> Result<(), AuthError> {
+        self.consume_recovery_code(&user.email, code)
+    }
+}
+
+struct PendingLogin {
+    user: User,
+    expires_at: u64,
+}
+
+/// Failed second-factor attempts of a subject.
+struct Failures {
+    count: u32,
+    resets_at: u64,
+}
+
+/// Tracks logins that passed the first factor and still need a second one.
+///
+/// Failed attempts are counted per user across challenges: after `max_attempts`
+/// failures within `ttl` seconds the challenge is discarded, and further challenges
+/// for that user cannot be completed until the window has passed, so a fresh
+/// challenge does not buy more guesses.
+///
+/// # Example
+/// ```code
+/// let user = auth.login_with_password("user@example.com", password)?;
+/// match logins.begin(user, &[&totp, &hotp]) {
+///     LoginOutcome::Authenticated(user) => start_session(*user),
+///     LoginOutcome::MfaRequired(challenge) => ask_for_code(challenge.token),
+/// }
+/// // later, with the code the user typed, or a recovery code:
+/// let user = logins.complete(&challenge_token, &mut totp, &code)?;
+/// let user = logins.complete(&challenge_token, &mut auth, &recovery_code)?;
+/// ```
+pub struct MfaLogins {
+    pending: HashMap<String, PendingLogin>,
+    failures: HashMap<String, Failures>,
+    ttl: u64,
+    max_attempts: u32,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MfaLogins {
+    fn default() -> Self {
+        Self::new(300)
+    }
+}
+
+impl MfaLogins {
+    /// Creates a tracker whose challenges expire after `ttl` seconds.
+    pub fn new(ttl: u64) -> Self {
+        Self {
+            pending: HashMap::new(),
+            failures: HashMap::new(),
+            ttl,
+            max_attempts: 5,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets how many failed attempts a user gets within `ttl` seconds; the default is 5.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Replaces the clock used for expiry checks.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Starts a login for a user who has passed the first factor.
+    ///
+    /// # Arguments
+    /// * `user` - The user returned by `Authentication::login_with_password`.
+    /// * `factors` - The second factors the application offers.
+    ///
+    /// # Returns
+    /// `LoginOutcome::Authenticated` if the user has none of `factors` enrolled,
+    /// otherwise `LoginOutcome::MfaRequired` with a challenge to complete.
+    pub fn begin(&mut self, user: User, factors: &[&dyn SecondFactor]) -> LoginOutcome {
+        if !factors.iter().any(|factor| factor.is_enrolled(&user)) {
+            return LoginOutcome::Authenticated(Box::new(user));
+        }
+        let token = random_token(32);
+        let expires_at = self.clock.now() + self.ttl;
+        let challenge = MfaChallenge {
+            token: token.clone(),
+            subject: user.identity(),
+            expires_at,
+        };
+        self.pending.insert(hash_token(&token), PendingLogin { user, expires_at });
+        LoginOutcome::MfaRequired(challenge)
+    }
+
+    /// Completes a pending login once `factor` accepts `code`.
+    ///
+    /// A failed verification leaves the challenge in place so the user can retry,
+    /// until it expires or the user reaches `max_attempts` failures.
+    ///
+    /// # Returns
+    /// * `Ok(User)` if the challenge is valid and `factor` accepts the code.
+    /// * `Err(AuthError::InvalidToken)` if the challenge is unknown or expired.
+    /// * `Err(AuthError::TooManyAttempts)` if the user has no attempts left; the challenge is discarded.
+    /// * The error returned by `factor` otherwise.
+    pub fn complete(&mut self, token: &str, factor: &mut dyn SecondFactor, code: &str) -> Result<User, AuthError> {
+        let key = hash_token(token);
+        let now = self.clock.now();
+        let pending = match self.pending.get(&key) {
+            Some(pending) if pending.expires_at > now => pending,
+            Some(_) => {
+                self.pending.remove(&key);
+                return Err(AuthError::InvalidToken);
+            }
+            None => return Err(AuthError::InvalidToken),
+        };
+        let subject = pending.user.identity();
+        if let Some(failures) = self.failures.get(&subject).filter(|f| f.resets_at > now && f.count >= self.max_attempts) {
+            let retry_at = failures.resets_at;
+            self.pending.remove(&key);
+            return Err(AuthError::TooManyAttempts { retry_at });
+        }
+
+        if let Err(error) = factor.verify(&pending.user, code) {
+            let failures = self.failures.entry(subject).or_insert(Failures { count: 0, resets_at: 0 });
+            if failures.resets_at <= now {
+                *failures = Failures { count: 0, resets_at: now + self.ttl };
+            }
+            failures.count += 1;
+            if failures.count >= self.max_attempts {
+                self.pending.remove(&key);
+            }
+            return Err(error);
+        }
+        self.failures.remove(&subject);
+        self.pending.remove(&key).map(|p| p.user).ok_or(AuthError::InvalidToken)
+    }
+
+    /// Removes expired challenges.
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.pending.retain(|_, p| p.expires_at > now);
+        self.failures.retain(|_, f| f.resets_at > now);
+    }
+}
@@ -0,0 +1,11 @@
+/// Provides counter-based one-time passwords (RFC 4226) for hardware tokens.
+pub mod hotp;
+
+/// Provides partial logins that wait for a second factor.
+pub mod login;
+
+/// Provides single-use recovery codes stored as bcrypt hashes.
+pub mod recovery;
+
+/// Provides time-based one-time passwords (RFC 6238).
+pub mod totp;
@@ -0,0 +1,92 @@
+use crate::auth::auth_n::{hash_password_with_cost, verify_password};
+use crate::crypto::random_string;
+use crate::error::AuthError;
+use crate::model::RecoveryCode;
+
+/// Characters used in recovery codes: lowercase letters and digits without
+/// look-alikes such as `0`/`o` and `1`/`l`.
+pub const RECOVERY_CODE_ALPHABET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+
+/// How recovery codes are generated and hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryCodePolicy {
+    /// Number of codes per batch.
+    pub count: usize,
+    /// Number of characters per code, excluding the separating dash.
+    pub length: usize,
+    /// bcrypt cost used to hash each code.
+    pub cost: u32,
+}
+
+impl Default for RecoveryCodePolicy {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            length: 10,
+            cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+/// Generates a batch of recovery codes.
+///
+/// # Returns
+/// * `Ok((Vec<String>, Vec<RecoveryCode>))` with the plaintext codes to show to the
+///   user once, formatted as `xxxxx-xxxxx`, and their hashes to store.
+/// * `Err(AuthError::PasswordHashingFailed)` if hashing fails.
+///
+/// # Example
+/// ```code
+/// let (codes, hashed) = generate_recovery_codes(&RecoveryCodePolicy::default())?;
+/// user.recovery_codes = hashed;
+/// ```
+pub fn generate_recovery_codes(policy: &RecoveryCodePolicy) -> Result<(Vec<String>, Vec<RecoveryCode>), AuthError> {
+    let mut codes = Vec::with_capacity(policy.count);
+    let mut hashed = Vec::with_capacity(policy.count);
+    for _ in 0..policy.count {
+        let code = random_string(RECOVERY_CODE_ALPHABET, policy.length);
+        hashed.push(RecoveryCode {
+            hash: hash_password_with_cost(&code, policy.cost)?,
+            used: false,
+        });
+        let (head, tail) = code.split_at(policy.length / 2);
+        codes.push(format!("{}-{}", head, tail));
+    }
+    Ok((codes, hashed))
+}
+
+/// Normalizes a recovery code as typed by a user: lowercase, without dashes or whitespace.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Marks the code matching `code` as used.
+///
+/// # Returns
+/// * `Ok(())` if an unused code matched.
+/// * `Err(AuthError::RecoveryCodeUsed)` if the code matched one that was already used.
+/// * `Err(AuthError::InvalidRecoveryCode)` if no code matched.
+pub fn consume_recovery_code(codes: &mut [RecoveryCode], code: &str) -> Result<(), AuthError> {
+    let code = normalize_recovery_code(code);
+    if code.is_empty() {
+        return Err(AuthError::InvalidRecoveryCode);
+    }
+    for stored in codes.iter_mut() {
+        if verify_password(&code, &stored.hash)? {
+            if stored.used {
+                return Err(AuthError::RecoveryCodeUsed);
+            }
+            stored.used = true;
+            return Ok(());
+        }
+    }
+    Err(AuthError::InvalidRecoveryCode)
+}
+
+/// Returns how many codes in the batch are still unused.
+pub fn remaining_recovery_codes(codes: &[RecoveryCode]) -> usize {
+    codes.iter().filter(|c| !c.used).count()
+}
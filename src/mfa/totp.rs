@@ -0,0 +1,232 @@
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::constant_time_eq;
+use crate::error::AuthError;
+use crate::oauth2::server::percent_encode;
+use crate::mfa::hotp::{hotp, OtpAlgorithm};
+use data_encoding::BASE32_NOPAD;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Settings shared by every TOTP enrollment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotpConfig {
+    /// Issuer shown in authenticator apps, e.g. the product name.
+    pub issuer: String,
+    /// Number of digits per code.
+    pub digits: u32,
+    /// Length of a time step, in seconds.
+    pub period: u64,
+    /// HMAC algorithm.
+    pub algorithm: OtpAlgorithm,
+    /// Number of time steps accepted on either side of the current one, to tolerate clock drift.
+    pub window: u64,
+    /// Length of generated secrets, in bytes.
+    pub secret_len: usize,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "auth_kit".to_string(),
+            digits: 6,
+            period: 30,
+            algorithm: OtpAlgorithm::Sha1,
+            window: 1,
+            secret_len: 20,
+        }
+    }
+}
+
+impl TotpConfig {
+    /// Returns the TOTP code for `secret` at `time` (RFC 6238 section 4).
+    pub fn code_at(&self, secret: &[u8], time: u64) -> String {
+        hotp(secret, time / self.period, self.digits, self.algorithm)
+    }
+}
+
+/// A user's TOTP secret and verification state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotpEnrollment {
+    /// The shared secret.
+    pub secret: Vec<u8>,
+    /// Whether the user has proven possession of the secret with a valid code.
+    pub confirmed: bool,
+    /// The time step of the last accepted code; codes from this step or earlier are rejected.
+    pub last_used_step: Option<u64>,
+}
+
+/// What a user needs to add an enrollment to an authenticator app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotpSetup {
+    /// The secret, base32-encoded without padding, for manual entry.
+    pub secret: String,
+    /// The `otpauth://totp/...` provisioning URI.
+    pub uri: String,
+}
+
+impl TotpSetup {
+    /// Renders the provisioning URI as an SVG QR code.
+    ///
+    /// # Returns
+    /// * `Ok(String)` with the SVG document.
+    /// * `Err(AuthError::TokenEncodingFailed)` if the URI does not fit in a QR code.
+    #[cfg(feature = "qr")]
+    pub fn qr_code_svg(&self) -> Result<String, AuthError> {
+        let code = qrcode::QrCode::new(self.uri.as_bytes()).map_err(|e| AuthError::TokenEncodingFailed(e.to_string()))?;
+        Ok(code.render::<qrcode::render::svg::Color>().min_dimensions(200, 200).build())
+    }
+}
+
+/// Time-based one-time passwords (RFC 6238) as a second factor.
+///
+/// Enrollment is two-step: `begin_enrollment` creates an unconfirmed secret and
+/// `confirm_enrollment` activates it once the user has entered a valid code.
+///
+/// # Example
+/// ```code
+/// let mut totp = TotpAuthenticator::new(TotpConfig { issuer: "Example".to_string(), ..TotpConfig::default() });
+/// let setup = totp.begin_enrollment("user@example.com");
+/// // show setup.uri as a QR code, then:
+/// totp.confirm_enrollment("user@example.com", "123456")?;
+/// totp.verify("user@example.com", "654321")?;
+/// ```
+pub struct TotpAuthenticator {
+    config: TotpConfig,
+    enrollments: HashMap<String, TotpEnrollment>,
+    pending: HashMap<String, TotpEnrollment>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TotpAuthenticator {
+    /// Creates an authenticator with no enrollments.
+    pub fn new(config: TotpConfig) -> Self {
+        Self {
+            config,
+            enrollments: HashMap::new(),
+            pending: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used to compute time steps.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the TOTP settings.
+    pub fn config(&self) -> &TotpConfig {
+        &self.config
+    }
+
+    /// Generates a new secret for `subject`, replacing any unconfirmed one.
+    ///
+    /// A confirmed enrollment stays active until the new secret is confirmed.
+    ///
+    /// # Arguments
+    /// * `subject` - The user identity (see `Identifiable`), also used as the account name.
+    ///
+    /// # Returns
+    /// The secret and provisioning URI to show to the user.
+    pub fn begin_enrollment(&mut self, subject: &str) -> TotpSetup {
+        let mut secret = vec![0u8; self.config.secret_len];
+        OsRng.fill_bytes(&mut secret);
+        let setup = TotpSetup {
+            secret: BASE32_NOPAD.encode(&secret),
+            uri: self.provisioning_uri(subject, &secret),
+        };
+        self.pending.insert(subject.to_string(), TotpEnrollment { secret, confirmed: false, last_used_step: None });
+        setup
+    }
+
+    /// Activates a pending enrollment after checking a code from the user's app.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the code is valid; the enrollment replaces any previous one.
+    /// * `Err(AuthError::MfaNotEnrolled)` if no enrollment was started.
+    /// * `Err(AuthError::InvalidOtp)` if the code is wrong.
+    pub fn confirm_enrollment(&mut self, subject: &str, code: &str) -> Result<(), AuthError> {
+        let pending = self.pending.get(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        let step = self.matching_step(pending, code)?;
+        let mut enrollment = self.pending.remove(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        enrollment.confirmed = true;
+        enrollment.last_used_step = Some(step);
+        self.enrollments.insert(subject.to_string(), enrollment);
+        Ok(())
+    }
+
+    /// Returns `true` if `subject` has a confirmed enrollment.
+    pub fn is_enrolled(&self, subject: &str) -> bool {
+        self.enrollments.contains_key(subject)
+    }
+
+    /// Returns the confirmed enrollment of `subject`, if any.
+    pub fn enrollment(&self, subject: &str) -> Option<&TotpEnrollment> {
+        self.enrollments.get(subject)
+    }
+
+    /// Restores a confirmed enrollment, e.g. loaded from a database.
+    pub fn insert_enrollment(&mut self, subject: &str, enrollment: TotpEnrollment) {
+        self.enrollments.insert(subject.to_string(), enrollment);
+    }
+
+    /// Removes the enrollment of `subject`, pending or confirmed.
+    pub fn disable(&mut self, subject: &str) {
+        self.enrollments.remove(subject);
+        self.pending.remove(subject);
+    }
+
+    /// Verifies a code against the confirmed enrollment of `subject`.
+    ///
+    /// Codes from the current time step and `window` steps around it are accepted,
+    /// but each step only once: a code cannot be replayed, and neither can an
+    /// older code once a newer one has been used.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the code is valid.
+    /// * `Err(AuthError::MfaNotEnrolled)` if `subject` has no confirmed enrollment.
+    /// * `Err(AuthError::InvalidOtp)` if the code is wrong.
+    /// * `Err(AuthError::OtpReplayed)` if the code's time step was already used.
+    pub fn verify(&mut self, subject: &str, code: &str) -> Result<(), AuthError> {
+        let enrollment = self.enrollments.get(subject).ok_or(AuthError::MfaNotEnrolled)?;
+        let step = self.matching_step(enrollment, code)?;
+        if enrollment.last_used_step.is_some_and(|last| step <= last) {
+            return Err(AuthError::OtpReplayed);
+        }
+        if let Some(enrollment) = self.enrollments.get_mut(subject) {
+            enrollment.last_used_step = Some(step);
+        }
+        Ok(())
+    }
+
+    fn matching_step(&self, enrollment: &TotpEnrollment, code: &str) -> Result<u64, AuthError> {
+        let code = code.trim();
+        if code.len() != self.config.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AuthError::InvalidOtp);
+        }
+        let current = self.clock.now() / self.config.period;
+        let first = current.saturating_sub(self.config.window);
+        (first..=current + self.config.window)
+            .find(|&step| {
+                let expected = hotp(&enrollment.secret, step, self.config.digits, self.config.algorithm);
+                constant_time_eq(expected.as_bytes(), code.as_bytes())
+            })
+            .ok_or(AuthError::InvalidOtp)
+    }
+
+    fn provisioning_uri(&self, subject: &str, secret: &[u8]) -> String {
+        let issuer = percent_encode(&self.config.issuer);
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&period={}",
+            issuer,
+            percent_encode(subject),
+            BASE32_NOPAD.encode(secret),
+            issuer,
+            self.config.algorithm.as_str(),
+            self.config.digits,
+            self.config.period
+        )
+    }
+}
@@ -23,6 +23,7 @@ impl AuthStrategy {
     /// # Returns
     ///
     /// A `Result` containing the matching `AuthStrategy` or an error string if invalid.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(strategy: &str) -> Result<Self, AuthError> {
         match strategy.to_uppercase().as_str() {
             "ABAC" => Ok(AuthStrategy::ABAC),
@@ -43,19 +44,154 @@ pub struct AuthContext {
     pub resource: Option<Resource>,
 }
 
+/// The account at an external identity provider that a user signs in with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederatedIdentity {
+    /// The identity provider's issuer identifier (`iss`).
+    pub issuer: String,
+    /// The user's stable identifier at the identity provider (`sub`).
+    pub subject: String,
+}
+
 /// Represents a user in the system.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct User {
     /// User's email address (also serves as identity).
     pub email: String,
     /// Password hash (not used directly in authorization logic).
     pub password_hash: String,
+    /// Hashes of previous passwords, most recent first, used to prevent reuse.
+    pub password_history: Vec<String>,
     /// Role assigned to the user.
     pub role: Role,
     /// Department to which the user belongs.
     pub department: String,
     /// Clearance level of the user.
     pub clearance_level: u8,
+    /// The user's current batch of single-use recovery codes, stored hashed.
+    pub recovery_codes: Vec<RecoveryCode>,
+    /// Lifecycle state of the account. Only `Active` accounts may log in or be authorized.
+    pub status: AccountStatus,
+    /// When `status` last changed, in seconds since the Unix epoch.
+    pub status_changed_at: Option<u64>,
+    /// Why `status` last changed, e.g. an administrator's note.
+    pub status_reason: Option<String>,
+    /// When the user proved ownership of `email`, if they have.
+    pub email_verified_at: Option<u64>,
+    /// The identity provider account linked to this user, for single sign-on.
+    pub federated_identity: Option<FederatedIdentity>,
+}
+
+impl User {
+    /// Moves the account to another lifecycle state, recording when and why.
+    ///
+    /// # Arguments
+    /// * `to` - The new state.
+    /// * `at` - The time of the change, in seconds since the Unix epoch.
+    /// * `reason` - An optional reason, kept with the account.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the transition is allowed.
+    /// * `Err(AuthError::InvalidStatusTransition)` otherwise; the account is left unchanged.
+    ///
+    /// # Example
+    /// ```code
+    /// user.transition(AccountStatus::Disabled, now, Some("left the company"))?;
+    /// ```
+    pub fn transition(&mut self, to: AccountStatus, at: u64, reason: Option<&str>) -> Result<(), AuthError> {
+        if !self.status.can_transition_to(to) {
+            return Err(AuthError::InvalidStatusTransition { from: self.status, to });
+        }
+        self.status = to;
+        self.status_changed_at = Some(at);
+        self.status_reason = reason.map(str::to_string);
+        Ok(())
+    }
+
+    /// Returns `true` if the user has verified their email address.
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    /// Checks that the account may log in or be authorized.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the account is active.
+    /// * `Err(AuthError::AccountPending)`, `Err(AuthError::AccountDisabled)`,
+    ///   `Err(AuthError::AccountSuspended)` or `Err(AuthError::AccountDeleted)` otherwise.
+    pub fn ensure_active(&self) -> Result<(), AuthError> {
+        match self.status {
+            AccountStatus::Active => Ok(()),
+            AccountStatus::Pending => Err(AuthError::AccountPending),
+            AccountStatus::Disabled => Err(AuthError::AccountDisabled),
+            AccountStatus::Locked => Err(AuthError::AccountSuspended),
+            AccountStatus::Deleted => Err(AuthError::AccountDeleted),
+        }
+    }
+}
+
+/// Lifecycle state of a user account.
+///
+/// Allowed transitions:
+/// * `Pending` → `Active`, `Deleted`
+/// * `Active` → `Disabled`, `Locked`, `Deleted`
+/// * `Disabled` → `Active`, `Deleted`
+/// * `Locked` → `Active`, `Disabled`, `Deleted`
+///
+/// `Deleted` is final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AccountStatus {
+    /// Registered but not yet activated, e.g. awaiting email verification.
+    Pending,
+    /// May log in.
+    #[default]
+    Active,
+    /// Switched off by an administrator; can be re-enabled.
+    Disabled,
+    /// Locked by an administrator or a security process, e.g. after a suspected compromise.
+    Locked,
+    /// Soft-deleted. The record is kept so the email cannot be re-registered.
+    Deleted,
+}
+
+impl AccountStatus {
+    /// Returns the lowercase name of the state.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Pending => "pending",
+            AccountStatus::Active => "active",
+            AccountStatus::Disabled => "disabled",
+            AccountStatus::Locked => "locked",
+            AccountStatus::Deleted => "deleted",
+        }
+    }
+
+    /// Returns `true` if an account in this state may move to `to`.
+    pub fn can_transition_to(&self, to: AccountStatus) -> bool {
+        use AccountStatus::*;
+        matches!(
+            (self, to),
+            (Pending, Active | Deleted)
+                | (Active, Disabled | Locked | Deleted)
+                | (Disabled, Active | Deleted)
+                | (Locked, Active | Disabled | Deleted)
+        )
+    }
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single-use recovery code. Only the bcrypt hash of the code is kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryCode {
+    /// bcrypt hash of the normalized code.
+    pub hash: String,
+    /// Whether the code has already been used.
+    pub used: bool,
 }
 
 /// A trait for any type that can be identified in audit or authorization logs.
@@ -77,7 +213,7 @@ impl Identifiable for Claims {
 }
 
 /// Represents claims typically extracted from a JWT or OAuth2 token.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 pub struct Claims {
     /// Email address associated with the token.
     pub email: String,
@@ -85,19 +221,36 @@ pub struct Claims {
     pub service: String,
     /// A list of scope strings representing granted permissions.
     pub scopes: Vec<String>,
+    /// Additional services (audiences) the token is valid for.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+}
+
+impl Claims {
+    /// Returns every audience of the token: `service` followed by `audiences`.
+    pub fn audiences(&self) -> Vec<&str> {
+        std::iter::once(self.service.as_str())
+            .chain(self.audiences.iter().map(String::as_str))
+            .filter(|aud| !aud.is_empty())
+            .collect()
+    }
 }
 
 /// Represents a resource that may require access control.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Resource {
     /// Department the resource belongs to.
     pub department: String,
     /// Required clearance level to access the resource.
     pub required_level: u8,
+    /// Optional resource type (e.g. "invoice"), used by SBA candidate templates.
+    pub resource_type: Option<String>,
+    /// Optional resource identifier, used by SBA candidate templates.
+    pub resource_id: Option<String>,
 }
 
 /// Represents a role assigned to users, containing named permissions.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Role {
     /// Name of the role (e.g. "admin", "editor").
     pub name: String,
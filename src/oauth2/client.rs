@@ -0,0 +1,324 @@
+use crate::auth::auth_n::verify_password;
+use crate::error::{AuthError, OAuthError};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::HashMap;
+
+/// An OAuth2 grant type a client may be allowed to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrantType {
+    /// `authorization_code`
+    AuthorizationCode,
+    /// `client_credentials`
+    ClientCredentials,
+    /// `refresh_token`
+    RefreshToken,
+    /// `urn:ietf:params:oauth:grant-type:device_code`
+    DeviceCode,
+    /// `urn:ietf:params:oauth:grant-type:token-exchange`
+    TokenExchange,
+}
+
+impl GrantType {
+    /// Returns the `grant_type` parameter value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrantType::AuthorizationCode => "authorization_code",
+            GrantType::ClientCredentials => "client_credentials",
+            GrantType::RefreshToken => "refresh_token",
+            GrantType::DeviceCode => "urn:ietf:params:oauth:grant-type:device_code",
+            GrantType::TokenExchange => "urn:ietf:params:oauth:grant-type:token-exchange",
+        }
+    }
+}
+
+/// A hashed client secret, optionally expiring after a rotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSecret {
+    /// The bcrypt hash of the secret.
+    pub hash: String,
+    /// When the secret stops being accepted, in seconds since the Unix epoch.
+    pub expires_at: Option<u64>,
+}
+
+/// An OAuth2 client registered with the authorization server.
+///
+/// A client with at least one secret is confidential and must authenticate at
+/// the token endpoint; a client without secrets is public.
+///
+/// # Example
+/// ```code
+/// let client = Client::new("web_app")
+///     .with_redirect_uri("https://app.example.com/callback")
+///     .with_scopes(&["profile", "user_service:*"]);
+///
+/// let service = Client::new("billing_worker")
+///     .with_secret_hash(&hash_password("s3cret")?)
+///     .with_grant_types(&[GrantType::ClientCredentials])
+///     .with_scopes(&["user_service:user:read"])
+///     .with_access_token_ttl(300);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Client {
+    /// Unique identifier of the client.
+    pub client_id: String,
+    /// Redirect URIs the client may use; matched exactly.
+    pub redirect_uris: Vec<String>,
+    /// Scopes the client may request, matched with `FlexibleMatcher` semantics.
+    pub allowed_scopes: Vec<String>,
+    /// Hashed secrets; more than one is valid during a rotation window.
+    pub secrets: Vec<ClientSecret>,
+    /// Grant types the client may use.
+    pub grant_types: Vec<GrantType>,
+    /// Access token lifetime in seconds, overriding the server default.
+    pub access_token_ttl: Option<u64>,
+}
+
+impl Client {
+    /// Creates a public client allowed to use the authorization code grant,
+    /// with no redirect URIs and no allowed scopes.
+    pub fn new(client_id: &str) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            redirect_uris: Vec::new(),
+            allowed_scopes: Vec::new(),
+            secrets: Vec::new(),
+            grant_types: vec![GrantType::AuthorizationCode],
+            access_token_ttl: None,
+        }
+    }
+
+    /// Adds a hashed secret, making the client confidential.
+    ///
+    /// The hash is produced with [`hash_password`](crate::auth::auth_n::hash_password).
+    pub fn with_secret_hash(mut self, secret_hash: &str) -> Self {
+        self.secrets.push(ClientSecret {
+            hash: secret_hash.to_string(),
+            expires_at: None,
+        });
+        self
+    }
+
+    /// Replaces the grant types the client may use.
+    pub fn with_grant_types(mut self, grant_types: &[GrantType]) -> Self {
+        self.grant_types = grant_types.to_vec();
+        self
+    }
+
+    /// Sets the access token lifetime for this client, in seconds.
+    pub fn with_access_token_ttl(mut self, ttl: u64) -> Self {
+        self.access_token_ttl = Some(ttl);
+        self
+    }
+
+    /// Returns `true` if the client has secrets and must authenticate.
+    pub fn is_confidential(&self) -> bool {
+        !self.secrets.is_empty()
+    }
+
+    /// Returns `true` if the client may use `grant_type`.
+    pub fn allows_grant(&self, grant_type: GrantType) -> bool {
+        self.grant_types.contains(&grant_type)
+    }
+
+    /// Checks `secret` against the client's unexpired secrets.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if a secret valid at `now` matches.
+    /// * `Ok(false)` otherwise.
+    /// * `Err(AuthError::PasswordHashingFailed)` if a stored hash is malformed.
+    pub fn verify_secret(&self, secret: &str, now: u64) -> Result<bool, AuthError> {
+        for stored in self.secrets.iter().filter(|s| s.expires_at.is_none_or(|exp| exp > now)) {
+            if verify_password(secret, &stored.hash)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Adds a redirect URI.
+    pub fn with_redirect_uri(mut self, redirect_uri: &str) -> Self {
+        self.redirect_uris.push(redirect_uri.to_string());
+        self
+    }
+
+    /// Adds scopes the client may request.
+    pub fn with_scopes(mut self, scopes: &[&str]) -> Self {
+        self.allowed_scopes.extend(scopes.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Resolves the redirect URI for a request.
+    ///
+    /// # Returns
+    /// * `Ok(String)` with the registered URI that exactly matches `requested`, or the
+    ///   only registered URI if none was requested.
+    /// * `Err(AuthError::OAuth(InvalidRequest))` if the URI is not registered or is ambiguous.
+    pub fn resolve_redirect_uri(&self, requested: Option<&str>) -> Result<String, AuthError> {
+        match requested {
+            Some(uri) if self.redirect_uris.iter().any(|r| r == uri) => Ok(uri.to_string()),
+            Some(uri) => Err(OAuthError::InvalidRequest(format!("redirect_uri '{}' is not registered", uri)).into()),
+            None if self.redirect_uris.len() == 1 => Ok(self.redirect_uris[0].clone()),
+            None => Err(OAuthError::InvalidRequest("redirect_uri is required".to_string()).into()),
+        }
+    }
+}
+
+/// Validates a redirect URI at registration time.
+///
+/// A redirect URI must be absolute and must not contain a fragment. Plain `http`
+/// is only allowed for loopback hosts; private-use schemes (e.g. `com.example.app:/cb`)
+/// are allowed for native apps.
+pub fn validate_redirect_uri(uri: &str) -> Result<(), AuthError> {
+    let invalid = |reason: &str| Err(OAuthError::InvalidRequest(format!("redirect_uri '{}' {}", uri, reason)).into());
+
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return invalid("is not an absolute URI");
+    };
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+        return invalid("has an invalid scheme");
+    }
+    if uri.contains('#') {
+        return invalid("must not contain a fragment");
+    }
+
+    match scheme.to_ascii_lowercase().as_str() {
+        "https" | "http" => {
+            let Some(authority) = rest.strip_prefix("//") else {
+                return invalid("is not an absolute URI");
+            };
+            let host_port = authority.split(['/', '?']).next().unwrap_or_default();
+            let host = host_port.rsplit_once(':').map_or(host_port, |(h, _)| h);
+            if host.is_empty() {
+                return invalid("has no host");
+            }
+            if scheme.eq_ignore_ascii_case("http") && !matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+                return invalid("must use https unless it targets a loopback host");
+            }
+            Ok(())
+        }
+        _ if rest.is_empty() => invalid("is not an absolute URI"),
+        _ => Ok(()),
+    }
+}
+
+/// Parses an HTTP Basic `Authorization` header into a client id and secret.
+///
+/// # Returns
+/// * `Ok((String, String))` with the client id and secret.
+/// * `Err(AuthError::OAuth(InvalidClient))` if the header is not a well-formed Basic credential.
+///
+/// # Example
+/// ```code
+/// let (client_id, secret) = parse_basic_auth("Basic YmlsbGluZ193b3JrZXI6czNjcmV0")?;
+/// ```
+pub fn parse_basic_auth(authorization: &str) -> Result<(String, String), AuthError> {
+    let invalid = || AuthError::from(OAuthError::InvalidClient("malformed Basic credentials".to_string()));
+    let encoded = authorization.strip_prefix("Basic ").ok_or_else(invalid)?;
+    let decoded = STANDARD.decode(encoded.trim()).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or_else(invalid)?;
+    Ok((client_id.to_string(), client_secret.to_string()))
+}
+
+/// A request that carries client credentials in `client_id` and `client_secret`.
+pub trait ClientCredentials {
+    /// Returns mutable references to the request's client id and secret.
+    fn credentials_mut(&mut self) -> (&mut Option<String>, &mut Option<String>);
+
+    /// Fills the client id and secret from an HTTP Basic `Authorization` header.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the header was a well-formed Basic credential.
+    /// * `Err(AuthError::OAuth(InvalidClient))` otherwise.
+    ///
+    /// # Example
+    /// ```code
+    /// request.apply_basic_auth("Basic YmlsbGluZ193b3JrZXI6czNjcmV0")?;
+    /// ```
+    fn apply_basic_auth(&mut self, authorization: &str) -> Result<(), AuthError> {
+        let (client_id, client_secret) = parse_basic_auth(authorization)?;
+        let (id, secret) = self.credentials_mut();
+        *id = Some(client_id);
+        *secret = Some(client_secret);
+        Ok(())
+    }
+}
+
+/// An in-memory registry of OAuth2 clients keyed by client id.
+#[derive(Debug, Default)]
+pub struct ClientRegistry {
+    clients: HashMap<String, Client>,
+}
+
+impl ClientRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a client after validating its redirect URIs.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the client was registered.
+    /// * `Err(AuthError::OAuth(InvalidRequest))` if the client id is taken or a redirect URI is invalid.
+    pub fn register(&mut self, client: Client) -> Result<(), AuthError> {
+        if client.client_id.is_empty() {
+            return Err(OAuthError::InvalidRequest("client_id must not be empty".to_string()).into());
+        }
+        if self.clients.contains_key(&client.client_id) {
+            return Err(OAuthError::InvalidRequest(format!("client '{}' is already registered", client.client_id)).into());
+        }
+        for uri in &client.redirect_uris {
+            validate_redirect_uri(uri)?;
+        }
+
+        self.clients.insert(client.client_id.clone(), client);
+        Ok(())
+    }
+
+    /// Looks up a client by id.
+    ///
+    /// # Returns
+    /// * `Ok(&Client)` if the client exists.
+    /// * `Err(AuthError::OAuth(InvalidClient))` otherwise.
+    pub fn get(&self, client_id: &str) -> Result<&Client, AuthError> {
+        self.clients
+            .get(client_id)
+            .ok_or_else(|| OAuthError::InvalidClient(format!("unknown client '{}'", client_id)).into())
+    }
+
+    /// Rotates a client's secret, keeping older secrets valid for an overlap window.
+    ///
+    /// # Arguments
+    /// * `client_id` - The client whose secret is rotated.
+    /// * `new_secret_hash` - The hash of the new secret.
+    /// * `overlap` - How long, in seconds, the previous secrets stay valid.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the secret was rotated.
+    /// * `Err(AuthError::OAuth(InvalidClient))` if the client does not exist.
+    pub fn rotate_secret(&mut self, client_id: &str, new_secret_hash: &str, overlap: u64, now: u64) -> Result<(), AuthError> {
+        let client = self
+            .clients
+            .get_mut(client_id)
+            .ok_or_else(|| OAuthError::InvalidClient(format!("unknown client '{}'", client_id)))?;
+
+        let deadline = now + overlap;
+        client.secrets.retain(|s| s.expires_at.is_none_or(|exp| exp > now));
+        for secret in &mut client.secrets {
+            secret.expires_at = Some(secret.expires_at.map_or(deadline, |exp| exp.min(deadline)));
+        }
+        client.secrets.push(ClientSecret {
+            hash: new_secret_hash.to_string(),
+            expires_at: None,
+        });
+        Ok(())
+    }
+
+    /// Removes a client from the registry, returning it if it existed.
+    pub fn remove(&mut self, client_id: &str) -> Option<Client> {
+        self.clients.remove(client_id)
+    }
+}
@@ -0,0 +1,288 @@
+use crate::crypto::{hash_token, random_string, random_token};
+use crate::error::{AuthError, OAuthError};
+use crate::model::{Identifiable, User};
+use crate::oauth2::client::ClientCredentials;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Characters used in user codes: upper-case consonants only, so codes contain
+/// no vowels (no accidental words) and no look-alikes such as `0`/`O` or `1`/`I`.
+pub const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// Number of characters in a user code, excluding the separator.
+pub const USER_CODE_LENGTH: usize = 8;
+
+/// How many seconds a device must add to its polling interval after `slow_down`.
+pub const SLOW_DOWN_INCREMENT: u64 = 5;
+
+/// Generates a user code such as `WDJB-MJHT`.
+pub fn generate_user_code() -> String {
+    let code = random_string(USER_CODE_ALPHABET, USER_CODE_LENGTH);
+    let (head, tail) = code.split_at(USER_CODE_LENGTH / 2);
+    format!("{}-{}", head, tail)
+}
+
+/// Normalizes a user code typed by a user.
+///
+/// Case is ignored and separators such as `-` and spaces are dropped, so
+/// `wdjb mjht` and `WDJB-MJHT` are the same code.
+pub fn normalize_user_code(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Parameters of a device authorization request (RFC 8628 section 3.1).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeviceAuthorizationRequest {
+    /// The requesting client.
+    pub client_id: Option<String>,
+    /// The client secret, for confidential clients.
+    pub client_secret: Option<String>,
+    /// Space-delimited requested scopes; defaults to the client's allowed scopes.
+    pub scope: Option<String>,
+}
+
+impl ClientCredentials for DeviceAuthorizationRequest {
+    fn credentials_mut(&mut self) -> (&mut Option<String>, &mut Option<String>) {
+        (&mut self.client_id, &mut self.client_secret)
+    }
+}
+
+/// A device authorization response (RFC 8628 section 3.2).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceAuthorizationResponse {
+    /// The code the device uses when polling the token endpoint.
+    pub device_code: String,
+    /// The code the user enters on the verification page.
+    pub user_code: String,
+    /// The page where the user enters the code.
+    pub verification_uri: String,
+    /// The verification page with the user code pre-filled, e.g. for a QR code.
+    pub verification_uri_complete: String,
+    /// Lifetime of the codes, in seconds.
+    pub expires_in: u64,
+    /// Minimum number of seconds between polls.
+    pub interval: u64,
+}
+
+/// Where a device authorization stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceAuthorizationStatus {
+    /// Waiting for the user to approve or deny.
+    Pending,
+    /// Approved by the given user identity.
+    Approved {
+        /// The identity of the approving user.
+        subject: String,
+    },
+    /// Denied by the user.
+    Denied,
+}
+
+/// A pending device authorization, as shown on the verification page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceAuthorization {
+    /// The requesting client.
+    pub client_id: String,
+    /// The scopes the device will receive once approved.
+    pub scopes: Vec<String>,
+    /// The user code, formatted for display.
+    pub user_code: String,
+    /// The current status.
+    pub status: DeviceAuthorizationStatus,
+    /// Expiry time of the codes, in seconds since the Unix epoch.
+    pub expires_at: u64,
+    /// Current minimum polling interval, in seconds.
+    pub interval: u64,
+    last_polled_at: Option<u64>,
+}
+
+/// In-memory state machine for the device authorization grant (RFC 8628).
+///
+/// A device starts an authorization and receives a device code and a user code.
+/// The user enters the user code on another device and approves or denies it,
+/// while the device polls with the device code. Device codes are stored by their
+/// SHA-256 hash.
+///
+/// # Example
+/// ```code
+/// let mut devices = DeviceAuthorizations::new();
+/// let (device_code, pending) = devices.start("tv_app", scopes, 600, 5, now);
+///
+/// devices.approve(&pending.user_code, &user, now)?;
+/// let approved = devices.poll(&device_code, "tv_app", now + 5)?;
+/// ```
+#[derive(Debug, Default)]
+pub struct DeviceAuthorizations {
+    pending: HashMap<String, DeviceAuthorization>,
+    user_codes: HashMap<String, String>,
+}
+
+impl DeviceAuthorizations {
+    /// Creates an empty state machine.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a device authorization.
+    ///
+    /// # Arguments
+    /// * `client_id` - The requesting client.
+    /// * `scopes` - The scopes granted once approved.
+    /// * `expires_in` - Lifetime of the codes, in seconds.
+    /// * `interval` - Minimum polling interval, in seconds.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `(String, DeviceAuthorization)` with the raw device code and the stored authorization.
+    pub fn start(
+        &mut self,
+        client_id: &str,
+        scopes: Vec<String>,
+        expires_in: u64,
+        interval: u64,
+        now: u64,
+    ) -> (String, DeviceAuthorization) {
+        self.purge_expired(now);
+
+        let user_code = loop {
+            let candidate = generate_user_code();
+            if !self.user_codes.contains_key(&normalize_user_code(&candidate)) {
+                break candidate;
+            }
+        };
+        let device_code = random_token(32);
+        let device_hash = hash_token(&device_code);
+        let authorization = DeviceAuthorization {
+            client_id: client_id.to_string(),
+            scopes,
+            user_code: user_code.clone(),
+            status: DeviceAuthorizationStatus::Pending,
+            expires_at: now + expires_in,
+            interval,
+            last_polled_at: None,
+        };
+
+        self.user_codes.insert(normalize_user_code(&user_code), device_hash.clone());
+        self.pending.insert(device_hash, authorization.clone());
+        (device_code, authorization)
+    }
+
+    /// Looks up an authorization by user code, for the verification page.
+    ///
+    /// # Returns
+    /// * `Ok(&DeviceAuthorization)` if the code is known and not expired.
+    /// * `Err(AuthError::OAuth(InvalidGrant))` otherwise.
+    pub fn lookup(&self, user_code: &str, now: u64) -> Result<&DeviceAuthorization, AuthError> {
+        self.user_codes
+            .get(&normalize_user_code(user_code))
+            .and_then(|hash| self.pending.get(hash))
+            .filter(|a| a.expires_at > now)
+            .ok_or_else(|| OAuthError::InvalidGrant("user code is invalid or expired".to_string()).into())
+    }
+
+    /// Approves a pending authorization on behalf of an authenticated user.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the authorization is now bound to `user`.
+    /// * `Err(AuthError::OAuth(InvalidGrant))` if the code is unknown, expired or already decided.
+    /// * The status errors of `User::ensure_active` if the account is not active.
+    pub fn approve(&mut self, user_code: &str, user: &User, now: u64) -> Result<(), AuthError> {
+        user.ensure_active()?;
+        self.decide(user_code, DeviceAuthorizationStatus::Approved { subject: user.identity() }, now)
+    }
+
+    /// Denies a pending authorization.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the authorization was denied.
+    /// * `Err(AuthError::OAuth(InvalidGrant))` if the code is unknown, expired or already decided.
+    pub fn deny(&mut self, user_code: &str, now: u64) -> Result<(), AuthError> {
+        self.decide(user_code, DeviceAuthorizationStatus::Denied, now)
+    }
+
+    /// Handles a poll from the device.
+    ///
+    /// Approved and denied authorizations are removed once reported, so a device
+    /// code can only be exchanged once.
+    ///
+    /// # Returns
+    /// * `Ok(DeviceAuthorization)` once the user has approved.
+    /// * `Err(AuthError::OAuth(AuthorizationPending))` while waiting for the user.
+    /// * `Err(AuthError::OAuth(SlowDown))` if polled faster than the interval; the interval grows.
+    /// * `Err(AuthError::OAuth(AccessDenied))` if the user denied the request.
+    /// * `Err(AuthError::OAuth(ExpiredToken))` if the codes have expired.
+    /// * `Err(AuthError::OAuth(InvalidGrant))` if the device code is unknown or belongs to another client.
+    pub fn poll(&mut self, device_code: &str, client_id: &str, now: u64) -> Result<DeviceAuthorization, AuthError> {
+        let device_hash = hash_token(device_code);
+        let authorization = self
+            .pending
+            .get_mut(&device_hash)
+            .filter(|a| a.client_id == client_id)
+            .ok_or_else(|| OAuthError::InvalidGrant("unknown device code".to_string()))?;
+
+        if authorization.expires_at <= now {
+            self.remove(&device_hash);
+            return Err(OAuthError::ExpiredToken("device code has expired".to_string()).into());
+        }
+        if authorization.last_polled_at.is_some_and(|last| now < last + authorization.interval) {
+            authorization.interval += SLOW_DOWN_INCREMENT;
+            authorization.last_polled_at = Some(now);
+            return Err(OAuthError::SlowDown(format!("poll at most every {} seconds", authorization.interval)).into());
+        }
+        authorization.last_polled_at = Some(now);
+
+        match authorization.status {
+            DeviceAuthorizationStatus::Pending => {
+                Err(OAuthError::AuthorizationPending("waiting for the user to approve".to_string()).into())
+            }
+            DeviceAuthorizationStatus::Denied => {
+                self.remove(&device_hash);
+                Err(OAuthError::AccessDenied("the user denied the request".to_string()).into())
+            }
+            DeviceAuthorizationStatus::Approved { .. } => {
+                Ok(self.remove(&device_hash).expect("authorization exists"))
+            }
+        }
+    }
+
+    /// Denies every authorization approved by `subject` that the device has not collected yet.
+    pub fn revoke_subject(&mut self, subject: &str) {
+        for authorization in self.pending.values_mut() {
+            if matches!(&authorization.status, DeviceAuthorizationStatus::Approved { subject: s } if s == subject) {
+                authorization.status = DeviceAuthorizationStatus::Denied;
+            }
+        }
+    }
+
+    /// Removes expired authorizations.
+    pub fn purge_expired(&mut self, now: u64) {
+        self.pending.retain(|_, a| a.expires_at > now);
+        let pending = &self.pending;
+        self.user_codes.retain(|_, hash| pending.contains_key(hash));
+    }
+
+    fn decide(&mut self, user_code: &str, status: DeviceAuthorizationStatus, now: u64) -> Result<(), AuthError> {
+        let authorization = self
+            .user_codes
+            .get(&normalize_user_code(user_code))
+            .and_then(|hash| self.pending.get_mut(hash))
+            .filter(|a| a.expires_at > now)
+            .ok_or_else(|| OAuthError::InvalidGrant("user code is invalid or expired".to_string()))?;
+
+        if authorization.status != DeviceAuthorizationStatus::Pending {
+            return Err(OAuthError::InvalidGrant("user code was already used".to_string()).into());
+        }
+        authorization.status = status;
+        Ok(())
+    }
+
+    fn remove(&mut self, device_hash: &str) -> Option<DeviceAuthorization> {
+        let authorization = self.pending.remove(device_hash)?;
+        self.user_codes.remove(&normalize_user_code(&authorization.user_code));
+        Some(authorization)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::auth::scope::{GlobMatcher, ScopeMatcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Token type identifier of OAuth2 access tokens (RFC 8693 section 3).
+pub const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+
+/// The `act` (actor) claim of a delegated token (RFC 8693 section 4.1).
+///
+/// The outermost actor is the party currently acting; earlier actors in a
+/// delegation chain are nested inside it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActorClaim {
+    /// The identity of the acting party.
+    pub sub: String,
+    /// The previous actor in the chain, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<ActorClaim>>,
+}
+
+impl ActorClaim {
+    /// Creates an actor claim for `sub`, wrapping the previous chain.
+    pub fn new(sub: &str, previous: Option<ActorClaim>) -> Self {
+        Self {
+            sub: sub.to_string(),
+            act: previous.map(Box::new),
+        }
+    }
+
+    /// Returns the actors in the chain, the current actor first.
+    pub fn chain(&self) -> Vec<&str> {
+        let mut chain = vec![self.sub.as_str()];
+        let mut next = self.act.as_deref();
+        while let Some(actor) = next {
+            chain.push(actor.sub.as_str());
+            next = actor.act.as_deref();
+        }
+        chain
+    }
+}
+
+/// What a client may do at the token exchange grant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExchangeRule {
+    /// Audiences the client may request tokens for; patterns use the `GlobMatcher` grammar.
+    pub audiences: Vec<String>,
+    /// Audiences for which the client may also exchange without an actor token,
+    /// receiving a token that is indistinguishable from the subject's own
+    /// (impersonation); patterns use the `GlobMatcher` grammar.
+    pub impersonation_audiences: Vec<String>,
+}
+
+/// Decides which clients may exchange tokens for which audiences.
+///
+/// Clients without a rule may not use token exchange at all. Exchanges with an
+/// actor token are delegation: the issued token records the actor in its `act`
+/// claim. Exchanges without one are impersonation and must be allowed explicitly.
+///
+/// # Example
+/// ```code
+/// let policy = TokenExchangePolicy::new()
+///     .allow("orders_service", &["billing_service", "inventory-*"])
+///     .allow_impersonation("support_console", &["user_service"]);
+/// let server = AuthorizationServer::new(config).with_exchange_policy(policy);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenExchangePolicy {
+    rules: HashMap<String, ExchangeRule>,
+}
+
+impl TokenExchangePolicy {
+    /// Creates a policy that allows no exchanges.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows `client_id` to exchange tokens for `audiences` by delegation.
+    pub fn allow(mut self, client_id: &str, audiences: &[&str]) -> Self {
+        let rule = self.rules.entry(client_id.to_string()).or_default();
+        rule.audiences.extend(audiences.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Allows `client_id` to exchange tokens for `audiences` by delegation or impersonation.
+    ///
+    /// Impersonation is only allowed for these audiences, not for those allowed with `allow`.
+    pub fn allow_impersonation(mut self, client_id: &str, audiences: &[&str]) -> Self {
+        self = self.allow(client_id, audiences);
+        let rule = self.rules.entry(client_id.to_string()).or_default();
+        rule.impersonation_audiences.extend(audiences.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Returns the rule for `client_id`, if any.
+    pub fn rule(&self, client_id: &str) -> Option<&ExchangeRule> {
+        self.rules.get(client_id)
+    }
+
+    /// Returns `true` if `client_id` may request a token for `audience`.
+    pub fn allows_audience(&self, client_id: &str, audience: &str) -> bool {
+        self.rule(client_id)
+            .is_some_and(|rule| rule.audiences.iter().any(|pattern| GlobMatcher::matches(pattern, audience)))
+    }
+
+    /// Returns `true` if `client_id` may exchange without an actor token for `audience`.
+    pub fn allows_impersonation(&self, client_id: &str, audience: &str) -> bool {
+        self.rule(client_id).is_some_and(|rule| {
+            rule.impersonation_audiences.iter().any(|pattern| GlobMatcher::matches(pattern, audience))
+        })
+    }
+}
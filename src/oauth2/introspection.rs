@@ -0,0 +1,174 @@
+use crate::error::{AuthError, OAuthError};
+use crate::model::Claims;
+use crate::oauth2::client::ClientCredentials;
+use crate::oauth2::exchange::ActorClaim;
+use serde::{Deserialize, Serialize};
+
+/// Parameters of an introspection request (RFC 7662 section 2.1).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IntrospectionRequest {
+    /// The token to introspect.
+    pub token: String,
+    /// Optional hint: `access_token` or `refresh_token`.
+    pub token_type_hint: Option<String>,
+    /// The calling client (usually a resource server).
+    pub client_id: Option<String>,
+    /// The client secret.
+    pub client_secret: Option<String>,
+}
+
+impl ClientCredentials for IntrospectionRequest {
+    fn credentials_mut(&mut self) -> (&mut Option<String>, &mut Option<String>) {
+        (&mut self.client_id, &mut self.client_secret)
+    }
+}
+
+/// Parameters of a revocation request (RFC 7009 section 2.1).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RevocationRequest {
+    /// The access or refresh token to revoke.
+    pub token: String,
+    /// Optional hint: `access_token` or `refresh_token`.
+    pub token_type_hint: Option<String>,
+    /// The client the token was issued to.
+    pub client_id: Option<String>,
+    /// The client secret, for confidential clients.
+    pub client_secret: Option<String>,
+}
+
+impl ClientCredentials for RevocationRequest {
+    fn credentials_mut(&mut self) -> (&mut Option<String>, &mut Option<String>) {
+        (&mut self.client_id, &mut self.client_secret)
+    }
+}
+
+/// The kind of token named by `token_type_hint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenTypeHint {
+    /// `access_token`
+    AccessToken,
+    /// `refresh_token`
+    RefreshToken,
+}
+
+impl TokenTypeHint {
+    /// Parses a `token_type_hint` parameter.
+    ///
+    /// Unknown hints are ignored (RFC 7662 section 2.1), so this returns `None` for them.
+    pub fn parse(hint: Option<&str>) -> Option<Self> {
+        match hint {
+            Some("access_token") => Some(TokenTypeHint::AccessToken),
+            Some("refresh_token") => Some(TokenTypeHint::RefreshToken),
+            _ => None,
+        }
+    }
+
+    /// Returns the parameter value for this hint.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenTypeHint::AccessToken => "access_token",
+            TokenTypeHint::RefreshToken => "refresh_token",
+        }
+    }
+}
+
+/// An introspection response (RFC 7662 section 2.2).
+///
+/// An inactive token carries no other members, so nothing about it leaks to the caller.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntrospectionResponse {
+    /// Whether the token is currently active.
+    pub active: bool,
+    /// Space-delimited scopes of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// The client the token was issued to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The subject of the token (user identity or client id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audiences (services) the token is bound to; a string or an array in JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::jose::one_or_many")]
+    pub aud: Vec<String>,
+    /// Issuer of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Expiry time, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// Issue time, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    /// The kind of token, `access_token` or `refresh_token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// The delegation chain of an exchanged token (RFC 8693 section 4.1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+impl IntrospectionResponse {
+    /// Returns the response for an unknown, expired or revoked token.
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+
+    /// Serializes the response as RFC 7662 JSON.
+    ///
+    /// # Example
+    /// ```code
+    /// assert_eq!(IntrospectionResponse::inactive().to_json(), r#"{"active":false}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| r#"{"active":false}"#.to_string())
+    }
+
+    /// Parses an introspection response received from an authorization server.
+    ///
+    /// # Returns
+    /// * `Ok(IntrospectionResponse)` if `json` is a valid response.
+    /// * `Err(AuthError::OAuth(InvalidRequest))` otherwise.
+    pub fn from_json(json: &str) -> Result<Self, AuthError> {
+        serde_json::from_str(json)
+            .map_err(|e| OAuthError::InvalidRequest(format!("malformed introspection response: {}", e)).into())
+    }
+
+    /// Converts an active response into `Claims` for the SBA strategy.
+    ///
+    /// `sub` becomes `email`, the first `aud` becomes `service` and any further ones
+    /// `audiences`, and `scope` is split into `scopes`.
+    ///
+    /// # Returns
+    /// * `Ok(Claims)` if the token is active.
+    /// * `Err(AuthError::InvalidToken)` if the token is inactive or has no subject.
+    ///
+    /// # Example
+    /// ```code
+    /// let response = IntrospectionResponse::from_json(&body)?;
+    /// let context = AuthContext { user: None, claims: Some(response.to_claims()?), resource: None };
+    /// authz.authorize(&context, "user_service", "read", Some(":"))?;
+    /// ```
+    pub fn to_claims(&self) -> Result<Claims, AuthError> {
+        if !self.active {
+            return Err(AuthError::InvalidToken);
+        }
+        let email = self.sub.clone().filter(|s| !s.is_empty()).ok_or(AuthError::InvalidToken)?;
+
+        let mut audiences = self.aud.clone();
+        let service = if audiences.is_empty() { String::new() } else { audiences.remove(0) };
+
+        Ok(Claims {
+            email,
+            service,
+            audiences,
+            scopes: self
+                .scope
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,26 @@
+/// Handles OAuth2 client registration and redirect URI validation.
+pub mod client;
+
+/// Implements PKCE (RFC 7636) code challenges and verification.
+pub mod pkce;
+
+/// Implements the authorization server state machine and token endpoint.
+pub mod server;
+
+/// Implements refresh token rotation with reuse detection.
+pub mod refresh;
+
+/// Implements token introspection (RFC 7662) and revocation (RFC 7009).
+pub mod introspection;
+
+/// Implements the device authorization grant (RFC 8628).
+pub mod device;
+
+/// Implements a minimal OpenID Connect provider: ID tokens, userinfo and discovery.
+pub mod oidc;
+
+/// Implements an OpenID Connect relying party for external identity providers.
+pub mod relying_party;
+
+/// Implements token exchange (RFC 8693) policies and actor claims.
+pub mod exchange;
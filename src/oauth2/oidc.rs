@@ -0,0 +1,231 @@
+use crate::jose::{self, JwsAlgorithm, Jwks, SigningKey};
+use crate::error::AuthError;
+use crate::model::{Identifiable, User};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+/// The scope that turns an authorization request into an OpenID Connect request.
+pub const OPENID_SCOPE: &str = "openid";
+
+/// How and when the user authenticated, as reported in ID tokens.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthenticationInfo {
+    /// When the user authenticated, in seconds since the Unix epoch.
+    pub auth_time: u64,
+    /// Authentication context class reference, e.g. `urn:example:loa:2`.
+    pub acr: Option<String>,
+    /// Authentication methods used, e.g. `pwd` or `otp` (RFC 8176).
+    pub amr: Vec<String>,
+}
+
+impl AuthenticationInfo {
+    /// Describes a password login at `auth_time`.
+    pub fn password(auth_time: u64) -> Self {
+        Self {
+            auth_time,
+            acr: None,
+            amr: vec!["pwd".to_string()],
+        }
+    }
+}
+
+/// Claims of an ID token (OpenID Connect Core section 2).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    /// Issuer identifier.
+    pub iss: String,
+    /// Subject (the user identity).
+    pub sub: String,
+    /// The client the token is intended for.
+    pub aud: String,
+    /// Expiry time, in seconds since the Unix epoch.
+    pub exp: u64,
+    /// Issue time, in seconds since the Unix epoch.
+    pub iat: u64,
+    /// When the user authenticated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<u64>,
+    /// The `nonce` from the authorization request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Authentication context class reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acr: Option<String>,
+    /// Authentication methods references.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+    /// Hash of the access token issued alongside the ID token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_hash: Option<String>,
+    /// The user's email, if the `email` scope was granted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Whether the user's email has been verified, released together with `email`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+}
+
+/// A userinfo response, filtered by the scopes of the access token.
+///
+/// `profile` releases `role` and `department`; `email` releases `email`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserInfo {
+    /// Subject (the user identity).
+    pub sub: String,
+    /// The user's email.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// The name of the user's role.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// The user's department.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+}
+
+impl UserInfo {
+    /// Builds the userinfo for `user`, releasing only the claims covered by `scopes`.
+    pub fn from_user(user: &User, scopes: &[String]) -> Self {
+        let granted = |scope: &str| scopes.iter().any(|s| s == scope);
+        let profile = granted("profile");
+        Self {
+            sub: user.identity(),
+            email: granted("email").then(|| user.email.clone()),
+            role: profile.then(|| user.role.name.clone()),
+            department: profile.then(|| user.department.clone()),
+        }
+    }
+
+    /// Serializes the userinfo as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// The `.well-known/openid-configuration` document (OpenID Connect Discovery section 3).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    /// Issuer identifier.
+    pub issuer: String,
+    /// URL of the authorization endpoint.
+    pub authorization_endpoint: String,
+    /// URL of the token endpoint.
+    pub token_endpoint: String,
+    /// URL of the userinfo endpoint.
+    pub userinfo_endpoint: String,
+    /// URL of the JWKS document.
+    pub jwks_uri: String,
+    /// URL of the introspection endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introspection_endpoint: Option<String>,
+    /// URL of the revocation endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_endpoint: Option<String>,
+    /// URL of the device authorization endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_authorization_endpoint: Option<String>,
+    /// Supported scopes.
+    pub scopes_supported: Vec<String>,
+    /// Supported response types.
+    pub response_types_supported: Vec<String>,
+    /// Supported grant types.
+    pub grant_types_supported: Vec<String>,
+    /// Supported subject identifier types.
+    pub subject_types_supported: Vec<String>,
+    /// Algorithms used to sign ID tokens.
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    /// Supported client authentication methods at the token endpoint.
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    /// Supported PKCE methods.
+    pub code_challenge_methods_supported: Vec<String>,
+    /// Claims that may be returned.
+    pub claims_supported: Vec<String>,
+}
+
+impl ProviderMetadata {
+    /// Serializes the document as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Signs ID tokens and publishes the matching keys.
+///
+/// The first key signs new tokens. Further keys are only published in the JWKS,
+/// so tokens signed before a key rotation stay verifiable.
+///
+/// # Example
+/// ```code
+/// let provider = OidcProvider::new(SigningKey::generate(JwsAlgorithm::ES256, "2025-01"));
+/// let mut server = AuthorizationServer::new(config).with_oidc(provider);
+///
+/// let discovery = server.openid_configuration().unwrap().to_json();
+/// let jwks = server.jwks().unwrap().to_json();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    keys: Vec<SigningKey>,
+    id_token_ttl: u64,
+}
+
+impl OidcProvider {
+    /// Creates a provider signing with `key`, issuing ID tokens valid for one hour.
+    pub fn new(key: SigningKey) -> Self {
+        Self {
+            keys: vec![key],
+            id_token_ttl: 3600,
+        }
+    }
+
+    /// Publishes an additional verification key, e.g. the previous key after a rotation.
+    pub fn with_published_key(mut self, key: SigningKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Sets the ID token lifetime, in seconds.
+    pub fn with_id_token_ttl(mut self, ttl: u64) -> Self {
+        self.id_token_ttl = ttl;
+        self
+    }
+
+    /// Returns the key that signs new ID tokens.
+    pub fn signing_key(&self) -> &SigningKey {
+        &self.keys[0]
+    }
+
+    /// Returns the ID token lifetime, in seconds.
+    pub fn id_token_ttl(&self) -> u64 {
+        self.id_token_ttl
+    }
+
+    /// Returns the public keys as a JWKS document.
+    pub fn jwks(&self) -> Jwks {
+        Jwks {
+            keys: self.keys.iter().map(SigningKey::public_jwk).collect(),
+        }
+    }
+
+    /// Signs ID token claims with the active key.
+    pub fn sign_id_token(&self, claims: &IdTokenClaims) -> Result<String, AuthError> {
+        jose::sign(claims, self.signing_key())
+    }
+
+    /// Computes the `at_hash` of an access token for the active key's algorithm.
+    pub fn at_hash(&self, access_token: &str) -> String {
+        at_hash(access_token, self.signing_key().algorithm())
+    }
+}
+
+/// Computes `at_hash`: the left half of the hash of the access token, base64url-encoded.
+///
+/// ES256 uses SHA-256 and EdDSA (Ed25519) uses SHA-512.
+pub fn at_hash(access_token: &str, algorithm: JwsAlgorithm) -> String {
+    let digest = match algorithm {
+        JwsAlgorithm::ES256 => Sha256::digest(access_token.as_bytes()).to_vec(),
+        JwsAlgorithm::EdDSA => Sha512::digest(access_token.as_bytes()).to_vec(),
+    };
+    URL_SAFE_NO_PAD.encode(&digest[..digest.len() / 2])
+}
@@ -0,0 +1,62 @@
+use crate::crypto::{constant_time_eq, sha256_base64url};
+use crate::error::{AuthError, OAuthError};
+
+/// The PKCE code challenge method (RFC 7636).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeChallengeMethod {
+    /// The challenge is the verifier itself.
+    Plain,
+    /// The challenge is `BASE64URL(SHA256(verifier))`.
+    S256,
+}
+
+impl CodeChallengeMethod {
+    /// Parses the `code_challenge_method` parameter.
+    ///
+    /// A missing method defaults to `plain`, as required by RFC 7636.
+    pub fn parse(method: Option<&str>) -> Result<Self, AuthError> {
+        match method {
+            None | Some("plain") => Ok(CodeChallengeMethod::Plain),
+            Some("S256") => Ok(CodeChallengeMethod::S256),
+            Some(other) => Err(OAuthError::InvalidRequest(format!(
+                "unsupported code_challenge_method '{}'",
+                other
+            ))
+            .into()),
+        }
+    }
+
+    /// Returns the parameter value for this method.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeChallengeMethod::Plain => "plain",
+            CodeChallengeMethod::S256 => "S256",
+        }
+    }
+}
+
+/// Returns `true` if `verifier` is 43 to 128 characters from the unreserved set.
+pub fn is_valid_verifier(verifier: &str) -> bool {
+    (43..=128).contains(&verifier.len())
+        && verifier.chars().all(|c| c.is_ascii_alphanumeric() || "-._~".contains(c))
+}
+
+/// Computes the code challenge for `verifier` with the given method.
+///
+/// # Example
+/// ```code
+/// let challenge = code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk", CodeChallengeMethod::S256);
+/// assert_eq!(challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+/// ```
+pub fn code_challenge(verifier: &str, method: CodeChallengeMethod) -> String {
+    match method {
+        CodeChallengeMethod::Plain => verifier.to_string(),
+        CodeChallengeMethod::S256 => sha256_base64url(verifier.as_bytes()),
+    }
+}
+
+/// Verifies a code verifier against the stored challenge.
+pub fn verify(verifier: &str, challenge: &str, method: CodeChallengeMethod) -> bool {
+    is_valid_verifier(verifier)
+        && constant_time_eq(code_challenge(verifier, method).as_bytes(), challenge.as_bytes())
+}
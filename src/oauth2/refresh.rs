@@ -0,0 +1,280 @@
+use crate::crypto::{hash_token, random_token};
+use crate::error::AuthError;
+use std::collections::HashMap;
+
+/// Lifetimes applied to refresh tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshTokenPolicy {
+    /// How long a refresh token stays valid without being used, in seconds.
+    pub idle_ttl: u64,
+    /// How long a token family may be refreshed in total, in seconds.
+    pub absolute_ttl: u64,
+}
+
+impl Default for RefreshTokenPolicy {
+    fn default() -> Self {
+        Self {
+            idle_ttl: 14 * 24 * 3600,
+            absolute_ttl: 90 * 24 * 3600,
+        }
+    }
+}
+
+/// A stored refresh token. Only the hash of the token is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshTokenRecord {
+    /// SHA-256 hash of the refresh token.
+    pub token_hash: String,
+    /// Identifier shared by every token produced by rotating the same original token.
+    pub family_id: String,
+    /// The user identity or client id the token was issued for.
+    pub subject: String,
+    /// The client the token was issued to.
+    pub client_id: String,
+    /// Scopes granted to the token family.
+    pub scopes: Vec<String>,
+    /// When this token was issued, in seconds since the Unix epoch.
+    pub issued_at: u64,
+    /// When this token expires if it is not used.
+    pub idle_expires_at: u64,
+    /// When the whole family expires, regardless of use.
+    pub absolute_expires_at: u64,
+    /// Whether the token has already been exchanged.
+    pub used: bool,
+    /// Whether the token (or its family) has been revoked.
+    pub revoked: bool,
+}
+
+impl RefreshTokenRecord {
+    /// Returns `true` if the token is unused, unrevoked and unexpired at `now`.
+    pub fn is_active(&self, now: u64) -> bool {
+        !self.used && !self.revoked && now < self.idle_expires_at && now < self.absolute_expires_at
+    }
+}
+
+/// Persistence for refresh tokens.
+///
+/// Implementations can share tokens across instances (e.g. in a database).
+/// `consume` must be atomic so that a token can only be rotated once.
+pub trait RefreshTokenStore: Send + Sync {
+    /// Stores a new record.
+    fn save(&mut self, record: RefreshTokenRecord) -> Result<(), AuthError>;
+
+    /// Finds a record by token hash.
+    fn find(&self, token_hash: &str) -> Result<Option<RefreshTokenRecord>, AuthError>;
+
+    /// Marks a token as used, returning `false` if it was already used.
+    fn consume(&mut self, token_hash: &str) -> Result<bool, AuthError>;
+
+    /// Revokes every token in a family.
+    fn revoke_family(&mut self, family_id: &str) -> Result<(), AuthError>;
+
+    /// Revokes every token issued for a subject.
+    fn revoke_subject(&mut self, subject: &str) -> Result<(), AuthError>;
+
+    /// Removes records whose family or idle lifetime has passed.
+    fn purge_expired(&mut self, now: u64) -> Result<(), AuthError>;
+}
+
+/// An in-memory `RefreshTokenStore`.
+#[derive(Debug, Default)]
+pub struct InMemoryRefreshTokenStore {
+    records: HashMap<String, RefreshTokenRecord>,
+}
+
+impl InMemoryRefreshTokenStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RefreshTokenStore for InMemoryRefreshTokenStore {
+    fn save(&mut self, record: RefreshTokenRecord) -> Result<(), AuthError> {
+        self.records.insert(record.token_hash.clone(), record);
+        Ok(())
+    }
+
+    fn find(&self, token_hash: &str) -> Result<Option<RefreshTokenRecord>, AuthError> {
+        Ok(self.records.get(token_hash).cloned())
+    }
+
+    fn consume(&mut self, token_hash: &str) -> Result<bool, AuthError> {
+        match self.records.get_mut(token_hash) {
+            Some(record) if !record.used => {
+                record.used = true;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn revoke_family(&mut self, family_id: &str) -> Result<(), AuthError> {
+        self.records
+            .values_mut()
+            .filter(|r| r.family_id == family_id)
+            .for_each(|r| r.revoked = true);
+        Ok(())
+    }
+
+    fn revoke_subject(&mut self, subject: &str) -> Result<(), AuthError> {
+        self.records
+            .values_mut()
+            .filter(|r| r.subject == subject)
+            .for_each(|r| r.revoked = true);
+        Ok(())
+    }
+
+    fn purge_expired(&mut self, now: u64) -> Result<(), AuthError> {
+        self.records
+            .retain(|_, r| now < r.absolute_expires_at && (now < r.idle_expires_at || r.used));
+        Ok(())
+    }
+}
+
+/// Issues, rotates and revokes refresh tokens.
+///
+/// Every use of a refresh token rotates it: the presented token is consumed and
+/// a new token of the same family is returned. Presenting a consumed token again
+/// is treated as theft, and the whole family is revoked.
+///
+/// # Example
+/// ```code
+/// let mut refresh = RefreshTokenManager::new(Box::new(InMemoryRefreshTokenStore::new()), RefreshTokenPolicy::default());
+/// let (token, _) = refresh.issue("user@example.com", "web_app", &scopes, now)?;
+/// let (next, record) = refresh.rotate(&token, Some("web_app"), now)?;
+/// ```
+pub struct RefreshTokenManager {
+    store: Box<dyn RefreshTokenStore>,
+    policy: RefreshTokenPolicy,
+}
+
+impl RefreshTokenManager {
+    /// Creates a manager over the given store and policy.
+    pub fn new(store: Box<dyn RefreshTokenStore>, policy: RefreshTokenPolicy) -> Self {
+        Self { store, policy }
+    }
+
+    /// Returns the lifetimes applied to new tokens.
+    pub fn policy(&self) -> RefreshTokenPolicy {
+        self.policy
+    }
+
+    /// Issues a refresh token starting a new family.
+    ///
+    /// # Arguments
+    /// * `subject` - The user identity (see `Identifiable`) or client id.
+    /// * `client_id` - The client the token is issued to.
+    /// * `scopes` - Scopes granted to the family.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `Ok((String, RefreshTokenRecord))` with the raw token and its stored record.
+    pub fn issue(
+        &mut self,
+        subject: &str,
+        client_id: &str,
+        scopes: &[String],
+        now: u64,
+    ) -> Result<(String, RefreshTokenRecord), AuthError> {
+        let family_id = random_token(16);
+        self.issue_in_family(&family_id, subject, client_id, scopes, now, now + self.policy.absolute_ttl)
+    }
+
+    /// Exchanges a refresh token for a new one in the same family.
+    ///
+    /// # Arguments
+    /// * `token` - The presented refresh token.
+    /// * `client_id` - If given, the token must have been issued to this client.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `Ok((String, RefreshTokenRecord))` with the new token and its stored record.
+    /// * `Err(AuthError::RefreshTokenReused)` if the token was already used; its family is revoked.
+    /// * `Err(AuthError::InvalidToken)` if the token is unknown, revoked, expired or
+    ///   belongs to another client.
+    pub fn rotate(&mut self, token: &str, client_id: Option<&str>, now: u64) -> Result<(String, RefreshTokenRecord), AuthError> {
+        let token_hash = hash_token(token);
+        let record = self.store.find(&token_hash)?.ok_or(AuthError::InvalidToken)?;
+
+        if client_id.is_some_and(|id| id != record.client_id) {
+            return Err(AuthError::InvalidToken);
+        }
+        if record.used {
+            self.store.revoke_family(&record.family_id)?;
+            return Err(AuthError::RefreshTokenReused(record.family_id));
+        }
+        if !record.is_active(now) {
+            return Err(AuthError::InvalidToken);
+        }
+        if !self.store.consume(&token_hash)? {
+            self.store.revoke_family(&record.family_id)?;
+            return Err(AuthError::RefreshTokenReused(record.family_id));
+        }
+
+        self.issue_in_family(
+            &record.family_id,
+            &record.subject,
+            &record.client_id,
+            &record.scopes,
+            now,
+            record.absolute_expires_at,
+        )
+    }
+
+    /// Looks up the record for a refresh token without consuming it.
+    pub fn find(&self, token: &str) -> Result<Option<RefreshTokenRecord>, AuthError> {
+        self.store.find(&hash_token(token))
+    }
+
+    /// Revokes a refresh token together with its whole family.
+    ///
+    /// Unknown tokens are ignored.
+    pub fn revoke(&mut self, token: &str) -> Result<(), AuthError> {
+        match self.store.find(&hash_token(token))? {
+            Some(record) => self.store.revoke_family(&record.family_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Revokes every refresh token of a family.
+    pub fn revoke_family(&mut self, family_id: &str) -> Result<(), AuthError> {
+        self.store.revoke_family(family_id)
+    }
+
+    /// Revokes every refresh token issued for `subject`.
+    pub fn revoke_subject(&mut self, subject: &str) -> Result<(), AuthError> {
+        self.store.revoke_subject(subject)
+    }
+
+    /// Removes expired records from the store.
+    pub fn purge_expired(&mut self, now: u64) -> Result<(), AuthError> {
+        self.store.purge_expired(now)
+    }
+
+    fn issue_in_family(
+        &mut self,
+        family_id: &str,
+        subject: &str,
+        client_id: &str,
+        scopes: &[String],
+        now: u64,
+        absolute_expires_at: u64,
+    ) -> Result<(String, RefreshTokenRecord), AuthError> {
+        let token = random_token(32);
+        let record = RefreshTokenRecord {
+            token_hash: hash_token(&token),
+            family_id: family_id.to_string(),
+            subject: subject.to_string(),
+            client_id: client_id.to_string(),
+            scopes: scopes.to_vec(),
+            issued_at: now,
+            idle_expires_at: (now + self.policy.idle_ttl).min(absolute_expires_at),
+            absolute_expires_at,
+            used: false,
+            revoked: false,
+        };
+        self.store.save(record.clone())?;
+        Ok((token, record))
+    }
+}
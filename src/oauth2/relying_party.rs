@@ -0,0 +1,412 @@
+use crate::auth::auth_n::Authentication;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{constant_time_eq, random_token};
+use crate::error::AuthError;
+use crate::jose::{self, Jwks};
+use crate::model::{AccountStatus, FederatedIdentity, Role, User};
+use crate::oauth2::pkce::{code_challenge, CodeChallengeMethod};
+use crate::oauth2::server::append_query;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Configuration of a relying party registered with an external identity provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelyingPartyConfig {
+    /// Expected `iss` of ID tokens, e.g. `https://idp.example.com/realms/main`.
+    pub issuer: String,
+    /// Authorization endpoint of the identity provider.
+    pub authorization_endpoint: String,
+    /// Our client id at the identity provider; expected in `aud`.
+    pub client_id: String,
+    /// Our redirect URI, registered at the identity provider.
+    pub redirect_uri: String,
+    /// Scopes to request; `openid` is always included.
+    pub scopes: Vec<String>,
+    /// Claim holding the user's groups.
+    pub groups_claim: String,
+    /// Accepted clock skew when checking `exp` and `iat`, in seconds.
+    pub leeway: u64,
+    /// How long a started login may take, in seconds.
+    pub login_ttl: u64,
+    /// Whether to reject ID tokens whose `email_verified` claim is not `true`, including
+    /// tokens without the claim.
+    pub require_verified_email: bool,
+}
+
+impl Default for RelyingPartyConfig {
+    fn default() -> Self {
+        Self {
+            issuer: String::new(),
+            authorization_endpoint: String::new(),
+            client_id: String::new(),
+            redirect_uri: String::new(),
+            scopes: vec!["openid".to_string(), "email".to_string(), "profile".to_string()],
+            groups_claim: "groups".to_string(),
+            leeway: 60,
+            login_ttl: 600,
+            require_verified_email: true,
+        }
+    }
+}
+
+/// Maps identity provider groups onto local roles.
+///
+/// Mappings are checked in insertion order and the first group the user belongs
+/// to wins; users in no mapped group get the default role, if any.
+///
+/// # Example
+/// ```code
+/// let mapping = RoleMapping::new()
+///     .with_group("idp-admins", Role { name: "admin".into(), permissions: vec![Permission::Create, Permission::Delete] })
+///     .with_group("idp-staff", Role { name: "staff".into(), permissions: vec![Permission::Read] })
+///     .with_default(Role { name: "guest".into(), permissions: vec![] });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoleMapping {
+    groups: Vec<(String, Role)>,
+    default_role: Option<Role>,
+}
+
+impl RoleMapping {
+    /// Creates an empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps members of `group` to `role`.
+    pub fn with_group(mut self, group: &str, role: Role) -> Self {
+        self.groups.push((group.to_string(), role));
+        self
+    }
+
+    /// Sets the role of users in no mapped group.
+    pub fn with_default(mut self, role: Role) -> Self {
+        self.default_role = Some(role);
+        self
+    }
+
+    /// Returns the role for a user in `groups`.
+    pub fn resolve(&self, groups: &[String]) -> Option<&Role> {
+        self.groups
+            .iter()
+            .find(|(group, _)| groups.contains(group))
+            .map(|(_, role)| role)
+            .or(self.default_role.as_ref())
+    }
+}
+
+/// A login started by [`RelyingParty::begin_login`], to be completed on the callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingLogin {
+    /// The `state` sent to the identity provider.
+    pub state: String,
+    /// The `nonce` expected in the ID token.
+    pub nonce: String,
+    /// The PKCE code verifier to send with the token request.
+    pub code_verifier: String,
+    /// When the login was started, in seconds since the Unix epoch.
+    pub started_at: u64,
+}
+
+/// The result of [`RelyingParty::begin_login`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginRedirect {
+    /// The URL to redirect the user to.
+    pub url: String,
+    /// The `state` of the login, for correlating the callback.
+    pub state: String,
+}
+
+/// Claims read from an external ID token.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExternalIdentity {
+    /// The issuer.
+    pub iss: String,
+    /// The subject at the identity provider.
+    pub sub: String,
+    /// The user's email, if released.
+    pub email: Option<String>,
+    /// Whether the identity provider verified the email.
+    pub email_verified: Option<bool>,
+    /// Groups from the configured groups claim.
+    pub groups: Vec<String>,
+    /// Expiry time, in seconds since the Unix epoch.
+    pub exp: u64,
+}
+
+#[derive(Deserialize)]
+struct RawIdToken {
+    iss: String,
+    sub: String,
+    #[serde(with = "crate::jose::one_or_many")]
+    aud: Vec<String>,
+    exp: u64,
+    #[serde(default)]
+    iat: Option<u64>,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    azp: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<bool>,
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
+}
+
+/// An OpenID Connect relying party for federating with an external identity provider.
+///
+/// The relying party is framework- and transport-agnostic: it builds the
+/// authorization redirect, remembers `state`, `nonce` and the PKCE verifier, and
+/// validates the ID token returned by the token endpoint. Fetching the JWKS and
+/// calling the token endpoint is left to the caller's HTTP client.
+///
+/// # Example
+/// ```code
+/// let mut rp = RelyingParty::new(config, Jwks::from_json(&jwks_body)?)
+///     .with_role_mapping(mapping);
+///
+/// let redirect = rp.begin_login();
+/// // ... user returns to the callback with `code` and `state` ...
+/// let login = rp.complete_login(&state)?;
+/// // ... POST code + login.code_verifier to the token endpoint ...
+/// let identity = rp.validate_id_token(&id_token, &login.nonce)?;
+/// let user = rp.provision(&mut auth, &identity)?;
+/// ```
+pub struct RelyingParty {
+    config: RelyingPartyConfig,
+    jwks: Jwks,
+    roles: RoleMapping,
+    pending: HashMap<String, PendingLogin>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RelyingParty {
+    /// Creates a relying party trusting the identity provider's key set.
+    pub fn new(config: RelyingPartyConfig, jwks: Jwks) -> Self {
+        Self {
+            config,
+            jwks,
+            roles: RoleMapping::new(),
+            pending: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets how identity provider groups map onto local roles.
+    pub fn with_role_mapping(mut self, roles: RoleMapping) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    /// Replaces the clock used for expiry checks.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Replaces the trusted key set, e.g. after the identity provider rotated keys.
+    pub fn set_jwks(&mut self, jwks: Jwks) {
+        self.jwks = jwks;
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &RelyingPartyConfig {
+        &self.config
+    }
+
+    /// Starts a login, returning the authorization URL to redirect the user to.
+    ///
+    /// A fresh `state`, `nonce` and PKCE verifier are generated and remembered
+    /// until [`complete_login`](Self::complete_login) is called with the same state.
+    pub fn begin_login(&mut self) -> LoginRedirect {
+        let now = self.clock.now();
+        let ttl = self.config.login_ttl;
+        self.pending.retain(|_, login| login.started_at + ttl > now);
+
+        let login = PendingLogin {
+            state: random_token(16),
+            nonce: random_token(16),
+            code_verifier: random_token(32),
+            started_at: now,
+        };
+
+        let mut scopes = vec!["openid".to_string()];
+        scopes.extend(self.config.scopes.iter().filter(|s| *s != "openid").cloned());
+        let scope = scopes.join(" ");
+        let challenge = code_challenge(&login.code_verifier, CodeChallengeMethod::S256);
+        let url = append_query(
+            &self.config.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &self.config.client_id),
+                ("redirect_uri", &self.config.redirect_uri),
+                ("scope", &scope),
+                ("state", &login.state),
+                ("nonce", &login.nonce),
+                ("code_challenge", &challenge),
+                ("code_challenge_method", CodeChallengeMethod::S256.as_str()),
+            ],
+        );
+
+        let redirect = LoginRedirect {
+            url,
+            state: login.state.clone(),
+        };
+        self.pending.insert(login.state.clone(), login);
+        redirect
+    }
+
+    /// Completes a login on the callback by consuming its `state`.
+    ///
+    /// # Returns
+    /// * `Ok(PendingLogin)` with the nonce and PKCE verifier for the token request.
+    /// * `Err(AuthError::InvalidToken)` if the state is unknown, already used or expired.
+    pub fn complete_login(&mut self, state: &str) -> Result<PendingLogin, AuthError> {
+        let login = self.pending.remove(state).ok_or(AuthError::InvalidToken)?;
+        if login.started_at + self.config.login_ttl <= self.clock.now() {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(login)
+    }
+
+    /// Validates an ID token returned by the identity provider.
+    ///
+    /// Checks the signature against the trusted JWKS, then `iss`, `aud` (and `azp`
+    /// when there are several audiences), `exp`, `iat` and `nonce`.
+    ///
+    /// # Returns
+    /// * `Ok(ExternalIdentity)` with the validated claims.
+    /// * `Err(AuthError::InvalidIdToken)` describing the first failed check.
+    pub fn validate_id_token(&self, id_token: &str, expected_nonce: &str) -> Result<ExternalIdentity, AuthError> {
+        let reject = |reason: &str| Err(AuthError::InvalidIdToken(reason.to_string()));
+        let claims: RawIdToken = jose::verify(id_token, &self.jwks)
+            .map_err(|_| AuthError::InvalidIdToken("signature verification failed".to_string()))?;
+        let now = self.clock.now();
+        let client_id = self.config.client_id.as_str();
+
+        if claims.iss != self.config.issuer {
+            return reject("unexpected issuer");
+        }
+        if !claims.aud.iter().any(|aud| aud == client_id) {
+            return reject("token is not intended for this client");
+        }
+        if claims.aud.len() > 1 && claims.azp.as_deref() != Some(client_id) {
+            return reject("authorized party does not match this client");
+        }
+        if claims.exp + self.config.leeway <= now {
+            return reject("token has expired");
+        }
+        if claims.iat.is_some_and(|iat| iat > now + self.config.leeway) {
+            return reject("token was issued in the future");
+        }
+        let nonce_matches = claims
+            .nonce
+            .as_deref()
+            .is_some_and(|nonce| constant_time_eq(nonce.as_bytes(), expected_nonce.as_bytes()));
+        if !nonce_matches {
+            return reject("nonce does not match");
+        }
+        if self.config.require_verified_email && claims.email_verified != Some(true) {
+            return reject("email is not verified");
+        }
+
+        let groups = match claims.other.get(&self.config.groups_claim) {
+            Some(serde_json::Value::Array(values)) => {
+                values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect()
+            }
+            Some(serde_json::Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(ExternalIdentity {
+            iss: claims.iss,
+            sub: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            groups,
+            exp: claims.exp,
+        })
+    }
+
+    /// Maps an external identity onto a local `User` without storing it.
+    ///
+    /// # Returns
+    /// * `Ok(User)` with the email as identity and the role from the group mapping.
+    /// * `Err(AuthError::InvalidIdToken)` if the identity has no email.
+    pub fn map_user(&self, identity: &ExternalIdentity) -> Result<User, AuthError> {
+        let email = identity
+            .email
+            .clone()
+            .filter(|e| !e.is_empty())
+            .ok_or_else(|| AuthError::InvalidIdToken("token has no email claim".to_string()))?;
+
+        Ok(User {
+            email,
+            password_hash: String::new(),
+            role: self.role_for(identity),
+            department: String::new(),
+            clearance_level: 0,
+            ..Default::default()
+        })
+    }
+
+    /// Provisions the user just in time and syncs their role from the group mapping.
+    ///
+    /// Unknown users are created through [`Authentication::register_with_status`]
+    /// without a local password, so they can only sign in through the identity
+    /// provider. They start out `Active` even when activation is required, since
+    /// the identity provider has already vouched for them. An email the identity
+    /// provider reports as verified is recorded as verified on the local user.
+    /// Existing users keep their role when none of their groups is mapped.
+    ///
+    /// Users are linked to the identity provider account by `iss` and `sub`, which,
+    /// unlike the email claim, are never reassigned. A local account with a password
+    /// or linked to another identity provider account is never taken over, and an
+    /// identity whose email no longer matches its linked account is refused rather
+    /// than given a second account; both need an explicit linking step.
+    ///
+    /// # Returns
+    /// * `Ok(User)` with the stored user.
+    /// * `Err(AuthError::InvalidIdToken)` if the identity has no email.
+    /// * `Err(AuthError::AccountLinkRequired)` if the email belongs to a local account with a
+    ///   password or to another identity, or the identity is linked to an account with another email.
+    /// * The status errors of `User::ensure_active` if the account is not active.
+    pub fn provision(&self, auth: &mut Authentication, identity: &ExternalIdentity) -> Result<User, AuthError> {
+        let mapped = self.map_user(identity)?;
+        let federated = FederatedIdentity { issuer: identity.iss.clone(), subject: identity.sub.clone() };
+        let linked_elsewhere = auth
+            .users
+            .values()
+            .any(|u| u.email != mapped.email && u.federated_identity.as_ref() == Some(&federated));
+        if linked_elsewhere {
+            return Err(AuthError::AccountLinkRequired);
+        }
+        if !auth.users.contains_key(&mapped.email) {
+            auth.register_with_status(&mapped.email, "", AccountStatus::Active)?;
+        }
+
+        let user = auth.users.get_mut(&mapped.email).ok_or(AuthError::UserNotFound)?;
+        if !user.password_hash.is_empty() || user.federated_identity.as_ref().is_some_and(|f| *f != federated) {
+            return Err(AuthError::AccountLinkRequired);
+        }
+        user.ensure_active()?;
+        user.federated_identity = Some(federated);
+        if identity.email_verified == Some(true) && user.email_verified_at.is_none() {
+            user.email_verified_at = Some(self.clock.now());
+        }
+        if let Some(role) = self.roles.resolve(&identity.groups) {
+            user.role = role.clone();
+        }
+        Ok(user.clone())
+    }
+
+    fn role_for(&self, identity: &ExternalIdentity) -> Role {
+        self.roles.resolve(&identity.groups).cloned().unwrap_or(Role {
+            name: "".to_string(),
+            permissions: vec![],
+        })
+    }
+}
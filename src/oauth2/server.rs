@@ -0,0 +1,1108 @@
+use crate::auth::scope_set::ScopeSet;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{hash_token, random_token};
+use crate::error::{AuthError, OAuthError};
+use crate::model::{Claims, Identifiable, User};
+use crate::oauth2::client::{Client, ClientCredentials, ClientRegistry, GrantType};
+use crate::oauth2::device::{
+    DeviceAuthorization, DeviceAuthorizationRequest, DeviceAuthorizationResponse, DeviceAuthorizationStatus,
+    DeviceAuthorizations,
+};
+use crate::jose::Jwks;
+use crate::oauth2::exchange::{ActorClaim, TokenExchangePolicy, ACCESS_TOKEN_TYPE};
+use crate::oauth2::introspection::{IntrospectionRequest, IntrospectionResponse, RevocationRequest, TokenTypeHint};
+use crate::oauth2::oidc::{AuthenticationInfo, IdTokenClaims, OidcProvider, ProviderMetadata, UserInfo, OPENID_SCOPE};
+use crate::oauth2::pkce::{self, CodeChallengeMethod};
+use crate::oauth2::refresh::RefreshTokenManager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Configuration of an `AuthorizationServer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    /// Issuer identifier of the server (e.g. `https://auth.example.com`).
+    pub issuer: String,
+    /// Audience (service) that issued access tokens are bound to.
+    pub audience: String,
+    /// Lifetime of authorization codes, in seconds.
+    pub code_ttl: u64,
+    /// Lifetime of access tokens, in seconds.
+    pub access_token_ttl: u64,
+    /// Whether the `plain` PKCE method is accepted in addition to `S256`.
+    pub allow_plain_pkce: bool,
+    /// Absolute URL of the page where users enter device user codes.
+    pub verification_uri: String,
+    /// Lifetime of device and user codes, in seconds.
+    pub device_code_ttl: u64,
+    /// Initial minimum interval between device polls, in seconds.
+    pub device_poll_interval: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "auth_kit".to_string(),
+            audience: "auth_kit".to_string(),
+            code_ttl: 60,
+            access_token_ttl: 3600,
+            allow_plain_pkce: true,
+            verification_uri: "/device".to_string(),
+            device_code_ttl: 600,
+            device_poll_interval: 5,
+        }
+    }
+}
+
+/// Parameters of an authorization request (the `/authorize` endpoint).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthorizationRequest {
+    /// Must be `code`.
+    pub response_type: String,
+    /// The requesting client.
+    pub client_id: String,
+    /// Where to send the user back; must be registered for the client.
+    pub redirect_uri: Option<String>,
+    /// Space-delimited requested scopes; defaults to the client's allowed scopes.
+    pub scope: Option<String>,
+    /// Opaque value echoed back to the client.
+    pub state: Option<String>,
+    /// PKCE code challenge.
+    pub code_challenge: Option<String>,
+    /// PKCE method, `S256` or `plain` (the default).
+    pub code_challenge_method: Option<String>,
+    /// OpenID Connect nonce, echoed in the ID token.
+    pub nonce: Option<String>,
+}
+
+/// A validated authorization request, ready to be shown on a consent screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatedAuthorization {
+    /// The requesting client.
+    pub client_id: String,
+    /// The resolved redirect URI.
+    pub redirect_uri: String,
+    /// The granted scopes.
+    pub scopes: Vec<String>,
+    /// Opaque value echoed back to the client.
+    pub state: Option<String>,
+    /// OpenID Connect nonce, echoed in the ID token.
+    pub nonce: Option<String>,
+    code_challenge: String,
+    method: CodeChallengeMethod,
+    redirect_uri_supplied: bool,
+}
+
+/// The successful result of an authorization request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorizationResponse {
+    /// The one-time authorization code.
+    pub code: String,
+    /// The `state` from the request, if any.
+    pub state: Option<String>,
+    /// The redirect URI to send the user to.
+    pub redirect_uri: String,
+}
+
+impl AuthorizationResponse {
+    /// Builds the full redirect URL carrying the code and state.
+    pub fn redirect_url(&self) -> String {
+        let mut params = vec![("code", self.code.as_str())];
+        if let Some(state) = &self.state {
+            params.push(("state", state));
+        }
+        append_query(&self.redirect_uri, &params)
+    }
+}
+
+/// Builds the redirect URL that reports an authorization error to the client.
+///
+/// Only use this once the client and redirect URI have been validated; otherwise
+/// the error must be shown to the user instead of redirecting.
+pub fn error_redirect_url(redirect_uri: &str, error: &OAuthError, state: Option<&str>) -> String {
+    let mut params = vec![("error", error.error_code()), ("error_description", error.description())];
+    if let Some(state) = state {
+        params.push(("state", state));
+    }
+    append_query(redirect_uri, &params)
+}
+
+/// Parameters of a token request (the `/token` endpoint).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenRequest {
+    /// The grant type, e.g. `authorization_code`.
+    pub grant_type: String,
+    /// The authorization code (authorization code grant).
+    pub code: Option<String>,
+    /// The redirect URI used in the authorization request.
+    pub redirect_uri: Option<String>,
+    /// The requesting client.
+    pub client_id: Option<String>,
+    /// The client secret, for confidential clients.
+    pub client_secret: Option<String>,
+    /// The PKCE code verifier.
+    pub code_verifier: Option<String>,
+    /// Space-delimited requested scopes.
+    pub scope: Option<String>,
+    /// The refresh token (refresh token grant).
+    pub refresh_token: Option<String>,
+    /// The device code (device authorization grant).
+    pub device_code: Option<String>,
+    /// The token being exchanged (token exchange grant).
+    pub subject_token: Option<String>,
+    /// The type of `subject_token`.
+    pub subject_token_type: Option<String>,
+    /// A token identifying the acting party (token exchange grant).
+    pub actor_token: Option<String>,
+    /// The type of `actor_token`.
+    pub actor_token_type: Option<String>,
+    /// The requested type of the issued token.
+    pub requested_token_type: Option<String>,
+    /// The service the issued token is intended for (token exchange grant).
+    pub audience: Option<String>,
+}
+
+impl ClientCredentials for TokenRequest {
+    fn credentials_mut(&mut self) -> (&mut Option<String>, &mut Option<String>) {
+        (&mut self.client_id, &mut self.client_secret)
+    }
+}
+
+/// A successful token response (RFC 6749 section 5.1).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenResponse {
+    /// The issued access token.
+    pub access_token: String,
+    /// Always `Bearer`.
+    pub token_type: String,
+    /// Lifetime of the access token, in seconds.
+    pub expires_in: u64,
+    /// Space-delimited granted scopes.
+    pub scope: String,
+    /// A new refresh token, if one was issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// An OpenID Connect ID token, if the `openid` scope was granted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    /// The type of the issued token, for token exchange responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_token_type: Option<String>,
+}
+
+/// An error response (RFC 6749 section 5.2).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorResponse {
+    /// The error code, e.g. `invalid_grant`.
+    pub error: String,
+    /// A human-readable description.
+    pub error_description: String,
+}
+
+impl From<&OAuthError> for ErrorResponse {
+    fn from(error: &OAuthError) -> Self {
+        Self {
+            error: error.error_code().to_string(),
+            error_description: error.description().to_string(),
+        }
+    }
+}
+
+/// An access token issued by the server, as stored in its token table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuedToken {
+    /// Claims carried by the token; `email` holds the subject.
+    pub claims: Claims,
+    /// The client the token was issued to.
+    pub client_id: String,
+    /// Issue time, in seconds since the Unix epoch.
+    pub issued_at: u64,
+    /// Expiry time, in seconds since the Unix epoch.
+    pub expires_at: u64,
+    /// The refresh token family the token was issued with, if any.
+    pub refresh_family: Option<String>,
+    /// The delegation chain, for tokens issued by token exchange.
+    pub act: Option<ActorClaim>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingCode {
+    authorization: ValidatedAuthorization,
+    subject: String,
+    authentication: AuthenticationInfo,
+    email_verified: bool,
+    expires_at: u64,
+    consumed: bool,
+    access_token_hash: Option<String>,
+    refresh_family: Option<String>,
+}
+
+/// A framework-agnostic OAuth2 authorization server.
+///
+/// The server is an in-memory state machine: HTTP handlers deserialize request
+/// parameters into `AuthorizationRequest` or `TokenRequest`, call the server, and
+/// serialize the result. Codes and tokens are stored by their SHA-256 hash.
+///
+/// # Example
+/// ```code
+/// let mut server = AuthorizationServer::new(ServerConfig::default());
+/// server.register_client(Client::new("web_app").with_redirect_uri("https://app.example.com/cb").with_scopes(&["read:*"]))?;
+///
+/// let response = server.authorize(&request, &user)?;
+/// let tokens = server.token(&TokenRequest {
+///     grant_type: "authorization_code".to_string(),
+///     code: Some(response.code),
+///     client_id: Some("web_app".to_string()),
+///     code_verifier: Some(verifier),
+///     ..Default::default()
+/// })?;
+/// let claims = server.validate_access_token(&tokens.access_token)?;
+/// ```
+pub struct AuthorizationServer {
+    config: ServerConfig,
+    clients: ClientRegistry,
+    codes: HashMap<String, PendingCode>,
+    tokens: HashMap<String, IssuedToken>,
+    devices: DeviceAuthorizations,
+    refresh: Option<RefreshTokenManager>,
+    oidc: Option<OidcProvider>,
+    exchange: TokenExchangePolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl AuthorizationServer {
+    /// Creates a server with the given configuration and the system clock.
+    pub fn new(config: ServerConfig) -> Self {
+        Self {
+            config,
+            clients: ClientRegistry::new(),
+            codes: HashMap::new(),
+            tokens: HashMap::new(),
+            devices: DeviceAuthorizations::new(),
+            refresh: None,
+            oidc: None,
+            exchange: TokenExchangePolicy::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for expiry checks.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enables refresh tokens for clients allowed to use the refresh token grant.
+    pub fn with_refresh_tokens(mut self, manager: RefreshTokenManager) -> Self {
+        self.refresh = Some(manager);
+        self
+    }
+
+    /// Enables OpenID Connect: ID tokens for the `openid` scope, userinfo and discovery.
+    pub fn with_oidc(mut self, provider: OidcProvider) -> Self {
+        self.oidc = Some(provider);
+        self
+    }
+
+    /// Sets which clients may exchange tokens for which audiences (RFC 8693).
+    pub fn with_exchange_policy(mut self, policy: TokenExchangePolicy) -> Self {
+        self.exchange = policy;
+        self
+    }
+
+    /// Returns the refresh token manager, if refresh tokens are enabled.
+    pub fn refresh_tokens(&mut self) -> Option<&mut RefreshTokenManager> {
+        self.refresh.as_mut()
+    }
+
+    /// Returns the server configuration.
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Registers a client.
+    pub fn register_client(&mut self, client: Client) -> Result<(), AuthError> {
+        self.clients.register(client)
+    }
+
+    /// Returns the client registry.
+    pub fn clients(&self) -> &ClientRegistry {
+        &self.clients
+    }
+
+    /// Validates an authorization request without issuing a code.
+    ///
+    /// Use this before showing a consent screen. Errors about the client or the
+    /// redirect URI must be shown to the user; other errors may be sent back with
+    /// [`error_redirect_url`].
+    pub fn validate_authorization_request(
+        &self,
+        request: &AuthorizationRequest,
+    ) -> Result<ValidatedAuthorization, AuthError> {
+        let client = self.clients.get(&request.client_id)?;
+        let redirect_uri = client.resolve_redirect_uri(request.redirect_uri.as_deref())?;
+        if !client.allows_grant(GrantType::AuthorizationCode) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the authorization code grant".to_string()).into());
+        }
+
+        if request.response_type != "code" {
+            return Err(OAuthError::UnsupportedResponseType(format!(
+                "response_type '{}' is not supported",
+                request.response_type
+            ))
+            .into());
+        }
+
+        let code_challenge = request
+            .code_challenge
+            .clone()
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| OAuthError::InvalidRequest("code_challenge is required".to_string()))?;
+        let method = CodeChallengeMethod::parse(request.code_challenge_method.as_deref())?;
+        if method == CodeChallengeMethod::Plain && !self.config.allow_plain_pkce {
+            return Err(OAuthError::InvalidRequest("code_challenge_method must be S256".to_string()).into());
+        }
+
+        let scopes = granted_scopes(client, request.scope.as_deref())?;
+
+        Ok(ValidatedAuthorization {
+            client_id: client.client_id.clone(),
+            redirect_uri,
+            scopes,
+            state: request.state.clone(),
+            nonce: request.nonce.clone(),
+            code_challenge,
+            method,
+            redirect_uri_supplied: request.redirect_uri.is_some(),
+        })
+    }
+
+    /// Issues an authorization code for an authenticated user who approved the request.
+    ///
+    /// # Arguments
+    /// * `request` - The authorization request parameters.
+    /// * `user` - The authenticated resource owner.
+    ///
+    /// # Returns
+    /// * `Ok(AuthorizationResponse)` with a one-time code bound to the client and PKCE challenge.
+    /// * `Err(AuthError::OAuth)` if the request is invalid.
+    /// * The status errors of `User::ensure_active` if the account is not active.
+    pub fn authorize(&mut self, request: &AuthorizationRequest, user: &User) -> Result<AuthorizationResponse, AuthError> {
+        let authentication = AuthenticationInfo::password(self.clock.now());
+        self.authorize_with(request, user, authentication)
+    }
+
+    /// Issues an authorization code, recording how the user authenticated.
+    ///
+    /// The authentication details are reported in the ID token as `auth_time`,
+    /// `acr` and `amr` when the `openid` scope is granted.
+    ///
+    /// # Example
+    /// ```code
+    /// let user = auth.login(&email)?.ok_or(AuthError::UserNotFound)?;
+    /// let info = AuthenticationInfo { auth_time: now, acr: None, amr: vec!["pwd".into(), "otp".into()] };
+    /// let response = server.authorize_with(&request, &user, info)?;
+    /// ```
+    pub fn authorize_with(
+        &mut self,
+        request: &AuthorizationRequest,
+        user: &User,
+        authentication: AuthenticationInfo,
+    ) -> Result<AuthorizationResponse, AuthError> {
+        let authorization = self.validate_authorization_request(request)?;
+        user.ensure_active()?;
+        let code = random_token(32);
+        let response = AuthorizationResponse {
+            code: code.clone(),
+            state: authorization.state.clone(),
+            redirect_uri: authorization.redirect_uri.clone(),
+        };
+
+        self.codes.insert(
+            hash_token(&code),
+            PendingCode {
+                authorization,
+                subject: user.identity(),
+                authentication,
+                email_verified: user.is_email_verified(),
+                expires_at: self.clock.now() + self.config.code_ttl,
+                consumed: false,
+                access_token_hash: None,
+                refresh_family: None,
+            },
+        );
+        Ok(response)
+    }
+
+    /// Handles a token request, dispatching on `grant_type`.
+    ///
+    /// # Returns
+    /// * `Ok(TokenResponse)` with a newly issued access token.
+    /// * `Err(AuthError::OAuth)` describing why the request was rejected.
+    pub fn token(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        match request.grant_type.as_str() {
+            "authorization_code" => self.exchange_code(request),
+            "client_credentials" => self.client_credentials(request),
+            "refresh_token" if self.refresh.is_some() => self.refresh(request),
+            grant if grant == GrantType::DeviceCode.as_str() => self.device_code(request),
+            grant if grant == GrantType::TokenExchange.as_str() => self.token_exchange(request),
+            other => Err(OAuthError::UnsupportedGrantType(format!("grant_type '{}' is not supported", other)).into()),
+        }
+    }
+
+    /// Handles a device authorization request (RFC 8628), issuing a device code and a user code.
+    ///
+    /// # Returns
+    /// * `Ok(DeviceAuthorizationResponse)` to be returned to the device as JSON.
+    /// * `Err(AuthError::OAuth)` if the client may not use the device grant or the scope is invalid.
+    ///
+    /// # Example
+    /// ```code
+    /// let started = server.device_authorization(&DeviceAuthorizationRequest {
+    ///     client_id: Some("tv_app".to_string()),
+    ///     ..Default::default()
+    /// })?;
+    /// println!("Visit {} and enter {}", started.verification_uri, started.user_code);
+    /// ```
+    pub fn device_authorization(&mut self, request: &DeviceAuthorizationRequest) -> Result<DeviceAuthorizationResponse, AuthError> {
+        let client = self.authenticate(request.client_id.as_deref(), request.client_secret.as_deref())?;
+        if !client.allows_grant(GrantType::DeviceCode) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the device authorization grant".to_string()).into());
+        }
+        let scopes = granted_scopes(&client, request.scope.as_deref())?;
+
+        let (device_code, authorization) = self.devices.start(
+            &client.client_id,
+            scopes,
+            self.config.device_code_ttl,
+            self.config.device_poll_interval,
+            self.clock.now(),
+        );
+        Ok(DeviceAuthorizationResponse {
+            device_code,
+            verification_uri: self.config.verification_uri.clone(),
+            verification_uri_complete: append_query(
+                &self.config.verification_uri,
+                &[("user_code", authorization.user_code.as_str())],
+            ),
+            user_code: authorization.user_code,
+            expires_in: self.config.device_code_ttl,
+            interval: authorization.interval,
+        })
+    }
+
+    /// Looks up a device authorization by user code, for the verification page.
+    pub fn device_verification(&self, user_code: &str) -> Result<&DeviceAuthorization, AuthError> {
+        self.devices.lookup(user_code, self.clock.now())
+    }
+
+    /// Approves a device authorization on behalf of an authenticated user.
+    ///
+    /// The device receives tokens for `user` on its next poll. Fails with the
+    /// status errors of `User::ensure_active` if the account is not active.
+    pub fn approve_device(&mut self, user_code: &str, user: &User) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        self.devices.approve(user_code, user, now)
+    }
+
+    /// Denies a device authorization; the device receives `access_denied` on its next poll.
+    pub fn deny_device(&mut self, user_code: &str) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        self.devices.deny(user_code, now)
+    }
+
+    /// Rotates a client's secret, keeping previous secrets valid for `overlap` seconds.
+    pub fn rotate_client_secret(&mut self, client_id: &str, new_secret_hash: &str, overlap: u64) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        self.clients.rotate_secret(client_id, new_secret_hash, overlap, now)
+    }
+
+    /// Authenticates the client making a token request.
+    ///
+    /// Confidential clients must present a valid, unexpired secret. Public clients
+    /// are identified by `client_id` alone.
+    ///
+    /// # Returns
+    /// * `Ok(Client)` with the authenticated client.
+    /// * `Err(AuthError::OAuth(InvalidClient))` if the client is unknown or authentication fails.
+    pub fn authenticate_client(&self, request: &TokenRequest) -> Result<Client, AuthError> {
+        self.authenticate(request.client_id.as_deref(), request.client_secret.as_deref())
+    }
+
+    /// Handles an introspection request (RFC 7662).
+    ///
+    /// Only confidential clients, typically resource servers, may introspect tokens.
+    /// Access tokens are looked up first unless the hint names a refresh token.
+    ///
+    /// # Returns
+    /// * `Ok(IntrospectionResponse)` describing the token; unknown, expired and
+    ///   revoked tokens yield an inactive response.
+    /// * `Err(AuthError::OAuth(InvalidClient))` if client authentication fails.
+    /// * `Err(AuthError::OAuth(UnauthorizedClient))` if the client is public.
+    ///
+    /// # Example
+    /// ```code
+    /// let mut request = IntrospectionRequest { token, ..Default::default() };
+    /// request.apply_basic_auth(authorization_header)?;
+    /// let body = server.introspect(&request)?.to_json();
+    /// ```
+    pub fn introspect(&self, request: &IntrospectionRequest) -> Result<IntrospectionResponse, AuthError> {
+        let client = self.authenticate(request.client_id.as_deref(), request.client_secret.as_deref())?;
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient("public clients may not introspect tokens".to_string()).into());
+        }
+
+        let response = match TokenTypeHint::parse(request.token_type_hint.as_deref()) {
+            Some(TokenTypeHint::RefreshToken) => self
+                .introspect_refresh_token(&request.token)?
+                .or_else(|| self.introspect_access_token(&request.token)),
+            _ => match self.introspect_access_token(&request.token) {
+                Some(response) => Some(response),
+                None => self.introspect_refresh_token(&request.token)?,
+            },
+        };
+        Ok(response.unwrap_or_else(IntrospectionResponse::inactive))
+    }
+
+    /// Handles a revocation request (RFC 7009).
+    ///
+    /// Revoking a refresh token revokes its whole family together with the access
+    /// tokens issued from it. Unknown tokens are ignored, as the RFC requires.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the token was revoked or was not known.
+    /// * `Err(AuthError::OAuth(InvalidClient))` if client authentication fails.
+    /// * `Err(AuthError::OAuth(UnauthorizedClient))` if the token was issued to another client.
+    pub fn revoke(&mut self, request: &RevocationRequest) -> Result<(), AuthError> {
+        let client = self.authenticate(request.client_id.as_deref(), request.client_secret.as_deref())?;
+        let not_owner = || AuthError::from(OAuthError::UnauthorizedClient("token was issued to another client".to_string()));
+
+        let token_hash = hash_token(&request.token);
+        if let Some(issued) = self.tokens.get(&token_hash) {
+            if issued.client_id != client.client_id {
+                return Err(not_owner());
+            }
+            self.tokens.remove(&token_hash);
+            return Ok(());
+        }
+
+        let Some(manager) = self.refresh.as_mut() else {
+            return Ok(());
+        };
+        let Some(record) = manager.find(&request.token)? else {
+            return Ok(());
+        };
+        if record.client_id != client.client_id {
+            return Err(not_owner());
+        }
+        manager.revoke(&request.token)?;
+        self.tokens
+            .retain(|_, t| t.refresh_family.as_deref() != Some(record.family_id.as_str()));
+        Ok(())
+    }
+
+    /// Revokes every grant of a user: access tokens, refresh tokens, unredeemed
+    /// authorization codes and approved device authorizations.
+    ///
+    /// Call this whenever an account stops being active, so that tokens issued
+    /// earlier cannot outlive the account.
+    ///
+    /// # Example
+    /// ```code
+    /// auth.disable("user@example.com", Some("left the company"))?;
+    /// server.revoke_subject("user@example.com")?;
+    /// ```
+    pub fn revoke_subject(&mut self, subject: &str) -> Result<(), AuthError> {
+        if let Some(manager) = self.refresh.as_mut() {
+            manager.revoke_subject(subject)?;
+        }
+        self.tokens.retain(|_, t| t.claims.email != subject);
+        self.codes.retain(|_, c| c.subject != subject);
+        self.devices.revoke_subject(subject);
+        Ok(())
+    }
+
+    /// Builds the userinfo response for an access token.
+    ///
+    /// The caller loads `user` by the token's subject; claims are released
+    /// according to the `profile` and `email` scopes of the token.
+    ///
+    /// # Returns
+    /// * `Ok(UserInfo)` if the token is valid, carries the `openid` scope and belongs to `user`.
+    /// * `Err(AuthError::InvalidToken)` if the token is invalid or belongs to another user.
+    /// * `Err(AuthError::ScopeNotGranted)` if the token lacks the `openid` scope.
+    pub fn userinfo(&self, access_token: &str, user: &User) -> Result<UserInfo, AuthError> {
+        let claims = self.validate_access_token(access_token)?;
+        if !claims.scopes.iter().any(|s| s == OPENID_SCOPE) {
+            return Err(AuthError::ScopeNotGranted(OPENID_SCOPE.to_string()));
+        }
+        if claims.email != user.identity() {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(UserInfo::from_user(user, &claims.scopes))
+    }
+
+    /// Returns the JWKS document with the ID token verification keys, if OpenID Connect is enabled.
+    pub fn jwks(&self) -> Option<Jwks> {
+        self.oidc.as_ref().map(OidcProvider::jwks)
+    }
+
+    /// Returns the `.well-known/openid-configuration` document, if OpenID Connect is enabled.
+    ///
+    /// Endpoint URLs are derived from the issuer; adjust the returned fields if
+    /// the endpoints are mounted elsewhere.
+    pub fn openid_configuration(&self) -> Option<ProviderMetadata> {
+        let oidc = self.oidc.as_ref()?;
+        let issuer = self.config.issuer.trim_end_matches('/');
+        let endpoint = |path: &str| format!("{}/{}", issuer, path);
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        let mut grant_types = vec![
+            "authorization_code",
+            "client_credentials",
+            GrantType::DeviceCode.as_str(),
+            GrantType::TokenExchange.as_str(),
+        ];
+        if self.refresh.is_some() {
+            grant_types.push("refresh_token");
+        }
+        let pkce_methods: &[&str] = if self.config.allow_plain_pkce { &["S256", "plain"] } else { &["S256"] };
+
+        Some(ProviderMetadata {
+            issuer: self.config.issuer.clone(),
+            authorization_endpoint: endpoint("authorize"),
+            token_endpoint: endpoint("token"),
+            userinfo_endpoint: endpoint("userinfo"),
+            jwks_uri: endpoint(".well-known/jwks.json"),
+            introspection_endpoint: Some(endpoint("introspect")),
+            revocation_endpoint: Some(endpoint("revoke")),
+            device_authorization_endpoint: Some(endpoint("device_authorization")),
+            scopes_supported: strings(&[OPENID_SCOPE, "profile", "email"]),
+            response_types_supported: strings(&["code"]),
+            grant_types_supported: strings(&grant_types),
+            subject_types_supported: strings(&["public"]),
+            id_token_signing_alg_values_supported: strings(&[oidc.signing_key().algorithm().as_str()]),
+            token_endpoint_auth_methods_supported: strings(&["client_secret_basic", "client_secret_post", "none"]),
+            code_challenge_methods_supported: strings(pkce_methods),
+            claims_supported: strings(&[
+                "iss", "sub", "aud", "exp", "iat", "auth_time", "nonce", "acr", "amr", "at_hash", "email", "email_verified",
+                "role",
+                "department",
+            ]),
+        })
+    }
+
+    /// Validates an access token and returns its claims.
+    ///
+    /// # Returns
+    /// * `Ok(Claims)` if the token is known and not expired.
+    /// * `Err(AuthError::InvalidToken)` otherwise.
+    pub fn validate_access_token(&self, token: &str) -> Result<Claims, AuthError> {
+        match self.tokens.get(&hash_token(token)) {
+            Some(issued) if issued.expires_at > self.clock.now() => Ok(issued.claims.clone()),
+            _ => Err(AuthError::InvalidToken),
+        }
+    }
+
+    /// Removes expired codes and access tokens.
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.codes.retain(|_, code| code.expires_at > now);
+        self.tokens.retain(|_, token| token.expires_at > now);
+        self.devices.purge_expired(now);
+    }
+
+    fn authenticate(&self, client_id: Option<&str>, client_secret: Option<&str>) -> Result<Client, AuthError> {
+        let client_id = client_id
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| OAuthError::InvalidClient("client authentication is required".to_string()))?;
+        let client = self.clients.get(client_id)?;
+
+        if client.is_confidential() {
+            let secret = client_secret.unwrap_or_default();
+            if secret.is_empty() || !client.verify_secret(secret, self.clock.now())? {
+                return Err(OAuthError::InvalidClient("client authentication failed".to_string()).into());
+            }
+        }
+        Ok(client.clone())
+    }
+
+    fn introspect_access_token(&self, token: &str) -> Option<IntrospectionResponse> {
+        let issued = self.tokens.get(&hash_token(token))?;
+        if issued.expires_at <= self.clock.now() {
+            return None;
+        }
+        Some(IntrospectionResponse {
+            active: true,
+            scope: Some(issued.claims.scopes.join(" ")),
+            client_id: Some(issued.client_id.clone()),
+            sub: Some(issued.claims.email.clone()),
+            aud: issued.claims.audiences().into_iter().map(str::to_string).collect(),
+            iss: Some(self.config.issuer.clone()),
+            exp: Some(issued.expires_at),
+            iat: Some(issued.issued_at),
+            token_type: Some(TokenTypeHint::AccessToken.as_str().to_string()),
+            act: issued.act.clone(),
+        })
+    }
+
+    fn introspect_refresh_token(&self, token: &str) -> Result<Option<IntrospectionResponse>, AuthError> {
+        let Some(manager) = self.refresh.as_ref() else {
+            return Ok(None);
+        };
+        let response = manager.find(token)?.filter(|r| r.is_active(self.clock.now())).map(|record| {
+            IntrospectionResponse {
+                active: true,
+                scope: Some(record.scopes.join(" ")),
+                client_id: Some(record.client_id),
+                sub: Some(record.subject),
+                aud: Vec::new(),
+                iss: Some(self.config.issuer.clone()),
+                exp: Some(record.idle_expires_at.min(record.absolute_expires_at)),
+                iat: Some(record.issued_at),
+                token_type: Some(TokenTypeHint::RefreshToken.as_str().to_string()),
+                act: None,
+            }
+        });
+        Ok(response)
+    }
+
+    fn exchange_code(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        let client = self.authenticate_client(request)?;
+        let client_id = client.client_id.as_str();
+        if !client.allows_grant(GrantType::AuthorizationCode) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the authorization code grant".to_string()).into());
+        }
+        let code = require(&request.code, "code")?;
+        let verifier = require(&request.code_verifier, "code_verifier")?;
+
+        let now = self.clock.now();
+        let code_hash = hash_token(code);
+        let pending = self
+            .codes
+            .get_mut(&code_hash)
+            .ok_or_else(|| OAuthError::InvalidGrant("unknown authorization code".to_string()))?;
+
+        if pending.consumed {
+            // RFC 6749 section 4.1.2: revoke every token issued from a replayed code.
+            if let Some(token_hash) = pending.access_token_hash.take() {
+                self.tokens.remove(&token_hash);
+            }
+            if let Some(family) = pending.refresh_family.take() {
+                if let Some(manager) = self.refresh.as_mut() {
+                    manager.revoke_family(&family)?;
+                }
+                self.tokens.retain(|_, t| t.refresh_family.as_deref() != Some(family.as_str()));
+            }
+            return Err(OAuthError::InvalidGrant("authorization code was already used".to_string()).into());
+        }
+        if pending.expires_at <= now {
+            self.codes.remove(&code_hash);
+            return Err(OAuthError::InvalidGrant("authorization code has expired".to_string()).into());
+        }
+
+        let authorization = &pending.authorization;
+        if authorization.client_id != client_id {
+            return Err(OAuthError::InvalidGrant("authorization code was issued to another client".to_string()).into());
+        }
+        let redirect_matches = match request.redirect_uri.as_deref() {
+            Some(uri) => uri == authorization.redirect_uri,
+            None => !authorization.redirect_uri_supplied,
+        };
+        if !redirect_matches {
+            return Err(OAuthError::InvalidGrant("redirect_uri does not match the authorization request".to_string()).into());
+        }
+        if !pkce::verify(verifier, &authorization.code_challenge, authorization.method) {
+            return Err(OAuthError::InvalidGrant("PKCE verification failed".to_string()).into());
+        }
+
+        pending.consumed = true;
+        let subject = pending.subject.clone();
+        let scopes = authorization.scopes.clone();
+        let nonce = authorization.nonce.clone();
+        let authentication = pending.authentication.clone();
+        let email_verified = pending.email_verified;
+
+        let openid = scopes.iter().any(|s| s == OPENID_SCOPE);
+        let email = scopes.iter().any(|s| s == "email").then(|| subject.clone());
+        let (access_token, mut response) = self.issue_user_tokens(&subject, &client, scopes)?;
+        if let Some(oidc) = self.oidc.as_ref().filter(|_| openid) {
+            let claims = IdTokenClaims {
+                iss: self.config.issuer.clone(),
+                sub: subject,
+                aud: client.client_id.clone(),
+                exp: now + oidc.id_token_ttl(),
+                iat: now,
+                auth_time: Some(authentication.auth_time),
+                nonce,
+                acr: authentication.acr,
+                amr: authentication.amr,
+                at_hash: Some(oidc.at_hash(&access_token)),
+                email_verified: email.is_some().then_some(email_verified),
+                email,
+            };
+            response.id_token = Some(oidc.sign_id_token(&claims)?);
+        }
+        let access_token_hash = hash_token(&access_token);
+        let refresh_family = self.tokens.get(&access_token_hash).and_then(|t| t.refresh_family.clone());
+        if let Some(pending) = self.codes.get_mut(&code_hash) {
+            pending.access_token_hash = Some(access_token_hash);
+            pending.refresh_family = refresh_family;
+        }
+        Ok(response)
+    }
+
+    fn client_credentials(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        let client = self.authenticate_client(request)?;
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient("public clients may not use client credentials".to_string()).into());
+        }
+        if !client.allows_grant(GrantType::ClientCredentials) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the client credentials grant".to_string()).into());
+        }
+
+        let scopes = granted_scopes(&client, request.scope.as_deref())?;
+        let (_, response) = self.issue_access_token(&client.client_id, &client, scopes, None);
+        Ok(response)
+    }
+
+    fn device_code(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        let client = self.authenticate_client(request)?;
+        if !client.allows_grant(GrantType::DeviceCode) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the device authorization grant".to_string()).into());
+        }
+        let device_code = require(&request.device_code, "device_code")?;
+
+        let now = self.clock.now();
+        let authorization = self.devices.poll(device_code, &client.client_id, now)?;
+        let DeviceAuthorizationStatus::Approved { subject } = authorization.status else {
+            return Err(OAuthError::InvalidGrant("device authorization was not approved".to_string()).into());
+        };
+        let (_, response) = self.issue_user_tokens(&subject, &client, authorization.scopes)?;
+        Ok(response)
+    }
+
+    fn token_exchange(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        let client = self.authenticate_client(request)?;
+        if !client.is_confidential() || !client.allows_grant(GrantType::TokenExchange) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the token exchange grant".to_string()).into());
+        }
+        let access_token_type = |value: &Option<String>, name: &str| match value.as_deref() {
+            None | Some(ACCESS_TOKEN_TYPE) => Ok(()),
+            Some(other) => Err(AuthError::from(OAuthError::InvalidRequest(format!(
+                "{} '{}' is not supported",
+                name, other
+            )))),
+        };
+        let subject_token = require(&request.subject_token, "subject_token")?;
+        require(&request.subject_token_type, "subject_token_type")?;
+        access_token_type(&request.subject_token_type, "subject_token_type")?;
+        access_token_type(&request.actor_token_type, "actor_token_type")?;
+        access_token_type(&request.requested_token_type, "requested_token_type")?;
+
+        let audience = require(&request.audience, "audience")?;
+        if !self.exchange.allows_audience(&client.client_id, audience) {
+            return Err(OAuthError::InvalidTarget(format!("client may not request tokens for '{}'", audience)).into());
+        }
+
+        let now = self.clock.now();
+        let active = |token: &str| self.tokens.get(&hash_token(token)).filter(|t| t.expires_at > now).cloned();
+        let subject = active(subject_token)
+            .ok_or_else(|| OAuthError::InvalidGrant("subject_token is invalid or expired".to_string()))?;
+
+        // Delegation records the actor on top of the subject's chain; impersonation
+        // keeps the chain as is, so it needs an explicit grant in the policy.
+        let act = match request.actor_token.as_deref().filter(|t| !t.is_empty()) {
+            Some(actor_token) => {
+                if request.actor_token_type.is_none() {
+                    return Err(OAuthError::InvalidRequest("actor_token_type is required".to_string()).into());
+                }
+                let actor = active(actor_token)
+                    .ok_or_else(|| OAuthError::InvalidGrant("actor_token is invalid or expired".to_string()))?;
+                Some(ActorClaim::new(&actor.claims.email, subject.act.clone()))
+            }
+            None if self.exchange.allows_impersonation(&client.client_id, audience) => subject.act.clone(),
+            None => {
+                let reason = "client may not impersonate; an actor_token is required";
+                return Err(OAuthError::UnauthorizedClient(reason.to_string()).into());
+            }
+        };
+
+        let granted: ScopeSet = subject.claims.scopes.iter().cloned().collect();
+        let scopes: Vec<String> = match request.scope.as_deref() {
+            Some(scope) => granted
+                .down_scope(&ScopeSet::from_scope_string(scope))
+                .map_err(|_| OAuthError::InvalidScope("scope exceeds the subject token".to_string()))?
+                .iter()
+                .map(str::to_string)
+                .collect(),
+            None => subject.claims.scopes.clone(),
+        };
+
+        // The exchanged token never outlives the token it was derived from.
+        let (access_token, mut response) = self.issue_access_token(&subject.claims.email, &client, scopes, None);
+        if let Some(issued) = self.tokens.get_mut(&hash_token(&access_token)) {
+            issued.claims.service = audience.to_string();
+            issued.expires_at = issued.expires_at.min(subject.expires_at);
+            issued.act = act;
+            response.expires_in = issued.expires_at - now;
+        }
+        response.issued_token_type = Some(ACCESS_TOKEN_TYPE.to_string());
+        Ok(response)
+    }
+
+    fn refresh(&mut self, request: &TokenRequest) -> Result<TokenResponse, AuthError> {
+        let client = self.authenticate_client(request)?;
+        if !client.allows_grant(GrantType::RefreshToken) {
+            return Err(OAuthError::UnauthorizedClient("client may not use the refresh token grant".to_string()).into());
+        }
+        let token = require(&request.refresh_token, "refresh_token")?;
+
+        let now = self.clock.now();
+        let Some(manager) = self.refresh.as_mut() else {
+            return Err(OAuthError::UnsupportedGrantType("refresh tokens are not enabled".to_string()).into());
+        };
+        let (next, record) = match manager.rotate(token, Some(&client.client_id), now) {
+            Ok(rotated) => rotated,
+            Err(AuthError::RefreshTokenReused(family)) => {
+                self.tokens.retain(|_, t| t.refresh_family.as_deref() != Some(family.as_str()));
+                return Err(OAuthError::InvalidGrant("refresh token was already used; session revoked".to_string()).into());
+            }
+            Err(AuthError::InvalidToken) => {
+                return Err(OAuthError::InvalidGrant("refresh token is invalid or expired".to_string()).into());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let scopes = match request.scope.as_deref() {
+            Some(scope) => {
+                let granted: ScopeSet = record.scopes.iter().cloned().collect();
+                granted
+                    .down_scope(&ScopeSet::from_scope_string(scope))
+                    .map_err(|_| OAuthError::InvalidScope("scope exceeds the original grant".to_string()))?
+                    .iter()
+                    .map(str::to_string)
+                    .collect()
+            }
+            None => record.scopes.clone(),
+        };
+
+        let (_, mut response) = self.issue_access_token(&record.subject, &client, scopes, Some(record.family_id));
+        response.refresh_token = Some(next);
+        Ok(response)
+    }
+
+    /// Issues an access token for a user, plus a refresh token if the client may refresh.
+    fn issue_user_tokens(
+        &mut self,
+        subject: &str,
+        client: &Client,
+        scopes: Vec<String>,
+    ) -> Result<(String, TokenResponse), AuthError> {
+        let now = self.clock.now();
+        let refresh = match self.refresh.as_mut() {
+            Some(manager) if client.allows_grant(GrantType::RefreshToken) => {
+                Some(manager.issue(subject, &client.client_id, &scopes, now)?)
+            }
+            _ => None,
+        };
+        let family = refresh.as_ref().map(|(_, record)| record.family_id.clone());
+        let (access_token, mut response) = self.issue_access_token(subject, client, scopes, family);
+        response.refresh_token = refresh.map(|(token, _)| token);
+        Ok((access_token, response))
+    }
+
+    /// Issues and stores a new access token, returning the raw token and the response.
+    fn issue_access_token(
+        &mut self,
+        subject: &str,
+        client: &Client,
+        scopes: Vec<String>,
+        refresh_family: Option<String>,
+    ) -> (String, TokenResponse) {
+        let now = self.clock.now();
+        let ttl = client.access_token_ttl.unwrap_or(self.config.access_token_ttl);
+        let access_token = random_token(32);
+        let scope = scopes.join(" ");
+
+        self.tokens.insert(
+            hash_token(&access_token),
+            IssuedToken {
+                claims: Claims {
+                    email: subject.to_string(),
+                    service: self.config.audience.clone(),
+                    scopes,
+                    ..Default::default()
+                },
+                client_id: client.client_id.clone(),
+                issued_at: now,
+                expires_at: now + ttl,
+                refresh_family,
+                act: None,
+            },
+        );
+
+        let response = TokenResponse {
+            access_token: access_token.clone(),
+            token_type: "Bearer".to_string(),
+            expires_in: ttl,
+            scope,
+            refresh_token: None,
+            id_token: None,
+            issued_token_type: None,
+        };
+        (access_token, response)
+    }
+}
+
+/// Resolves the scopes granted for a request, limited to what the client may request.
+fn granted_scopes(client: &Client, requested: Option<&str>) -> Result<Vec<String>, AuthError> {
+    let allowed: ScopeSet = client.allowed_scopes.iter().cloned().collect();
+    let requested: ScopeSet = match requested {
+        Some(scope) => ScopeSet::from_scope_string(scope),
+        None => allowed.clone(),
+    };
+    if requested.is_empty() {
+        return Err(OAuthError::InvalidScope("no scope requested".to_string()).into());
+    }
+
+    allowed
+        .down_scope(&requested)
+        .map(|set| set.iter().map(str::to_string).collect())
+        .map_err(|e| match e {
+            AuthError::ScopeNotGranted(missing) => {
+                OAuthError::InvalidScope(format!("scope '{}' is not allowed for this client", missing)).into()
+            }
+            other => other,
+        })
+}
+
+fn require<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str, AuthError> {
+    value
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| OAuthError::InvalidRequest(format!("{} is required", name)).into())
+}
+
+pub(crate) fn append_query(uri: &str, params: &[(&str, &str)]) -> String {
+    let mut url = uri.to_string();
+    let mut separator = if uri.contains('?') { '&' } else { '?' };
+    for (key, value) in params {
+        url.push(separator);
+        url.push_str(key);
+        url.push('=');
+        url.push_str(&percent_encode(value));
+        separator = '&';
+    }
+    url
+}
+
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
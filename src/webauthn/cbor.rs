@@ -0,0 +1,230 @@
+use crate::error::AuthError;
+
+/// Nesting depth beyond which input is rejected, to bound recursion.
+const MAX_DEPTH: usize = 16;
+
+/// A CBOR (RFC 8949) data item, limited to the types WebAuthn uses.
+///
+/// Floats, tags and indefinite-length items are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// An unsigned or negative integer.
+    Integer(i128),
+    /// A byte string.
+    Bytes(Vec<u8>),
+    /// A UTF-8 text string.
+    Text(String),
+    /// An array of items.
+    Array(Vec<Value>),
+    /// A map, with entries in encoding order.
+    Map(Vec<(Value, Value)>),
+    /// `true` or `false`.
+    Bool(bool),
+    /// `null`.
+    Null,
+}
+
+impl Value {
+    /// Looks up `key` in a map. Returns `None` for other types or missing keys.
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is one.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the byte string, if this is one.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the text string, if this is one.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Integer(n as i128)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+/// Decodes a single CBOR item that spans all of `bytes`.
+///
+/// # Returns
+/// * `Ok(Value)` if `bytes` is exactly one well-formed item.
+/// * `Err(AuthError::InvalidWebAuthnResponse)` otherwise.
+pub fn decode(bytes: &[u8]) -> Result<Value, AuthError> {
+    let (value, used) = decode_prefix(bytes)?;
+    if used != bytes.len() {
+        return Err(malformed("trailing bytes"));
+    }
+    Ok(value)
+}
+
+/// Decodes the CBOR item at the start of `bytes`, returning it and its encoded length.
+pub fn decode_prefix(bytes: &[u8]) -> Result<(Value, usize), AuthError> {
+    let mut decoder = Decoder { bytes, pos: 0 };
+    let value = decoder.item(0)?;
+    Ok((value, decoder.pos))
+}
+
+/// Encodes an item using definite lengths, as CTAP2 canonical CBOR does.
+///
+/// Map entries are written in the order given.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_item(&mut out, value);
+    out
+}
+
+fn malformed(reason: &str) -> AuthError {
+    AuthError::InvalidWebAuthnResponse(format!("malformed CBOR: {}", reason))
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], AuthError> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len()).ok_or_else(|| malformed("unexpected end of input"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn argument(&mut self, info: u8) -> Result<u64, AuthError> {
+        let width = match info {
+            0..=23 => return Ok(info as u64),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(malformed("indefinite lengths are not supported")),
+        };
+        Ok(self.take(width)?.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+    }
+
+    fn length(&mut self, info: u8) -> Result<usize, AuthError> {
+        let len = usize::try_from(self.argument(info)?).map_err(|_| malformed("length too large"))?;
+        if len > self.bytes.len() - self.pos {
+            return Err(malformed("length exceeds input"));
+        }
+        Ok(len)
+    }
+
+    fn item(&mut self, depth: usize) -> Result<Value, AuthError> {
+        if depth > MAX_DEPTH {
+            return Err(malformed("nesting too deep"));
+        }
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        match major {
+            0 => Ok(Value::Integer(self.argument(info)? as i128)),
+            1 => Ok(Value::Integer(-1 - self.argument(info)? as i128)),
+            2 => {
+                let len = self.length(info)?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            3 => {
+                let len = self.length(info)?;
+                let text = std::str::from_utf8(self.take(len)?).map_err(|_| malformed("invalid UTF-8"))?;
+                Ok(Value::Text(text.to_string()))
+            }
+            4 => {
+                let len = self.length(info)?;
+                let items = (0..len).map(|_| self.item(depth + 1)).collect::<Result<_, _>>()?;
+                Ok(Value::Array(items))
+            }
+            5 => {
+                let len = self.length(info)?;
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.item(depth + 1)?;
+                    let value = self.item(depth + 1)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::Map(entries))
+            }
+            7 => match info {
+                20 => Ok(Value::Bool(false)),
+                21 => Ok(Value::Bool(true)),
+                22 => Ok(Value::Null),
+                _ => Err(malformed("unsupported simple value or float")),
+            },
+            _ => Err(malformed("tags are not supported")),
+        }
+    }
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => out.push(major | argument as u8),
+        24..=0xff => out.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((argument as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(argument.to_be_bytes());
+        }
+    }
+}
+
+fn write_item(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Integer(n) if *n >= 0 => write_head(out, 0, *n as u64),
+        Value::Integer(n) => write_head(out, 1, (-1 - *n) as u64),
+        Value::Bytes(b) => {
+            write_head(out, 2, b.len() as u64);
+            out.extend(b);
+        }
+        Value::Text(t) => {
+            write_head(out, 3, t.len() as u64);
+            out.extend(t.as_bytes());
+        }
+        Value::Array(items) => {
+            write_head(out, 4, items.len() as u64);
+            items.iter().for_each(|item| write_item(out, item));
+        }
+        Value::Map(entries) => {
+            write_head(out, 5, entries.len() as u64);
+            for (key, value) in entries {
+                write_item(out, key);
+                write_item(out, value);
+            }
+        }
+        Value::Bool(false) => out.push(0xf4),
+        Value::Bool(true) => out.push(0xf5),
+        Value::Null => out.push(0xf6),
+    }
+}
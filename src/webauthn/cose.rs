@@ -0,0 +1,105 @@
+use crate::error::AuthError;
+use crate::jose::{JwsAlgorithm, Jwk};
+use crate::webauthn::cbor::Value;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+/// COSE algorithm identifier of ES256 (RFC 9053 section 2.1).
+pub const COSE_ALG_ES256: i64 = -7;
+
+/// COSE algorithm identifier of EdDSA (RFC 9053 section 2.2).
+pub const COSE_ALG_EDDSA: i64 = -8;
+
+const KTY: i64 = 1;
+const ALG: i64 = 3;
+const CRV: i64 = -1;
+const X: i64 = -2;
+const Y: i64 = -3;
+
+const KTY_OKP: i128 = 1;
+const KTY_EC2: i128 = 2;
+const CRV_P256: i128 = 1;
+const CRV_ED25519: i128 = 6;
+
+/// Returns the COSE algorithm identifier of `algorithm`.
+pub fn cose_algorithm(algorithm: JwsAlgorithm) -> i64 {
+    match algorithm {
+        JwsAlgorithm::ES256 => COSE_ALG_ES256,
+        JwsAlgorithm::EdDSA => COSE_ALG_EDDSA,
+    }
+}
+
+/// Converts a COSE_Key (RFC 9052 section 7) into a JWK.
+///
+/// Only EC2 keys on P-256 with ES256 and OKP keys on Ed25519 with EdDSA are accepted.
+///
+/// # Returns
+/// * `Ok(Jwk)` with the public key.
+/// * `Err(AuthError::InvalidKey)` if the key is malformed or uses another algorithm.
+pub fn jwk_from_cose(key: &Value) -> Result<Jwk, AuthError> {
+    let int = |label: i64| key.get(&label.into()).and_then(Value::as_integer);
+    let coordinate = |label: i64| {
+        key.get(&label.into())
+            .and_then(Value::as_bytes)
+            .filter(|b| b.len() == 32)
+            .map(|b| URL_SAFE_NO_PAD.encode(b))
+            .ok_or_else(|| AuthError::InvalidKey("missing or malformed key coordinate".to_string()))
+    };
+
+    let (algorithm, y) = match (int(KTY), int(ALG), int(CRV)) {
+        (Some(KTY_EC2), Some(alg), Some(CRV_P256)) if alg == COSE_ALG_ES256 as i128 => (JwsAlgorithm::ES256, Some(coordinate(Y)?)),
+        (Some(KTY_OKP), Some(alg), Some(CRV_ED25519)) if alg == COSE_ALG_EDDSA as i128 => (JwsAlgorithm::EdDSA, None),
+        (kty, alg, crv) => {
+            return Err(AuthError::InvalidKey(format!(
+                "unsupported COSE key (kty {:?}, alg {:?}, crv {:?})",
+                kty, alg, crv
+            )));
+        }
+    };
+    let (kty, crv) = match algorithm {
+        JwsAlgorithm::ES256 => ("EC", "P-256"),
+        JwsAlgorithm::EdDSA => ("OKP", "Ed25519"),
+    };
+    Ok(Jwk {
+        kty: kty.to_string(),
+        crv: crv.to_string(),
+        x: coordinate(X)?,
+        y,
+        kid: None,
+        alg: Some(algorithm.as_str().to_string()),
+        key_use: None,
+    })
+}
+
+/// Converts a JWK into a COSE_Key, as an authenticator would report it.
+///
+/// # Returns
+/// * `Ok(Value)` with the COSE_Key map.
+/// * `Err(AuthError::InvalidKey)` if the JWK is malformed or unsupported.
+pub fn jwk_to_cose(jwk: &Jwk) -> Result<Value, AuthError> {
+    let algorithm = jwk.algorithm()?;
+    let decode = |coordinate: &str| {
+        URL_SAFE_NO_PAD
+            .decode(coordinate)
+            .map_err(|_| AuthError::InvalidKey("malformed public key".to_string()))
+    };
+    let entry = |label: i64, value: Value| (Value::from(label), value);
+
+    let mut entries = match algorithm {
+        JwsAlgorithm::ES256 => vec![entry(KTY, Value::Integer(KTY_EC2)), entry(ALG, COSE_ALG_ES256.into())],
+        JwsAlgorithm::EdDSA => vec![entry(KTY, Value::Integer(KTY_OKP)), entry(ALG, COSE_ALG_EDDSA.into())],
+    };
+    match algorithm {
+        JwsAlgorithm::ES256 => {
+            let y = jwk.y.as_deref().ok_or_else(|| AuthError::InvalidKey("missing y coordinate".to_string()))?;
+            entries.push(entry(CRV, Value::Integer(CRV_P256)));
+            entries.push(entry(X, Value::Bytes(decode(&jwk.x)?)));
+            entries.push(entry(Y, Value::Bytes(decode(y)?)));
+        }
+        JwsAlgorithm::EdDSA => {
+            entries.push(entry(CRV, Value::Integer(CRV_ED25519)));
+            entries.push(entry(X, Value::Bytes(decode(&jwk.x)?)));
+        }
+    }
+    Ok(Value::Map(entries))
+}
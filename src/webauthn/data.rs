@@ -0,0 +1,114 @@
+use crate::error::AuthError;
+use crate::jose::Jwk;
+use crate::webauthn::cbor;
+use crate::webauthn::cose::jwk_from_cose;
+use serde::{Deserialize, Serialize};
+
+/// Flag bit set when the user was present (touched the authenticator).
+pub const FLAG_USER_PRESENT: u8 = 0x01;
+
+/// Flag bit set when the user was verified (PIN, biometrics).
+pub const FLAG_USER_VERIFIED: u8 = 0x04;
+
+/// Flag bit set when attested credential data follows the sign count.
+pub const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// Flag bit set when extension data is present.
+pub const FLAG_EXTENSION_DATA: u8 = 0x80;
+
+/// The client data the browser signs over (WebAuthn section 5.8.1).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectedClientData {
+    /// `webauthn.create` or `webauthn.get`.
+    #[serde(rename = "type")]
+    pub ceremony: String,
+    /// The base64url challenge issued by the relying party.
+    pub challenge: String,
+    /// The origin of the page that called the WebAuthn API.
+    pub origin: String,
+    /// Whether the call was made from a cross-origin iframe.
+    #[serde(rename = "crossOrigin", default)]
+    pub cross_origin: bool,
+}
+
+/// A newly created credential, included in authenticator data during registration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestedCredential {
+    /// The authenticator model identifier.
+    pub aaguid: [u8; 16],
+    /// The raw credential id.
+    pub credential_id: Vec<u8>,
+    /// The credential public key.
+    pub public_key: Jwk,
+}
+
+/// Parsed authenticator data (WebAuthn section 6.1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatorData {
+    /// SHA-256 hash of the RP ID the credential is scoped to.
+    pub rp_id_hash: [u8; 32],
+    /// Flag bits, see the `FLAG_*` constants.
+    pub flags: u8,
+    /// The signature counter.
+    pub sign_count: u32,
+    /// The new credential, present during registration.
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+    /// Parses raw authenticator data.
+    ///
+    /// # Returns
+    /// * `Ok(AuthenticatorData)` if the data is well-formed.
+    /// * `Err(AuthError::InvalidWebAuthnResponse)` or `Err(AuthError::InvalidKey)` otherwise.
+    pub fn parse(bytes: &[u8]) -> Result<Self, AuthError> {
+        let truncated = || AuthError::InvalidWebAuthnResponse("authenticator data is truncated".to_string());
+        if bytes.len() < 37 {
+            return Err(truncated());
+        }
+        let mut rp_id_hash = [0u8; 32];
+        rp_id_hash.copy_from_slice(&bytes[..32]);
+        let flags = bytes[32];
+        let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+        let mut rest = &bytes[37..];
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+            if rest.len() < 18 {
+                return Err(truncated());
+            }
+            let mut aaguid = [0u8; 16];
+            aaguid.copy_from_slice(&rest[..16]);
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            rest = &rest[18..];
+            if rest.len() < id_len {
+                return Err(truncated());
+            }
+            let credential_id = rest[..id_len].to_vec();
+            let (key, used) = cbor::decode_prefix(&rest[id_len..])?;
+            rest = &rest[id_len + used..];
+            Some(AttestedCredential { aaguid, credential_id, public_key: jwk_from_cose(&key)? })
+        } else {
+            None
+        };
+
+        if flags & FLAG_EXTENSION_DATA != 0 {
+            let (_, used) = cbor::decode_prefix(rest)?;
+            rest = &rest[used..];
+        }
+        if !rest.is_empty() {
+            return Err(AuthError::InvalidWebAuthnResponse("trailing bytes in authenticator data".to_string()));
+        }
+
+        Ok(Self { rp_id_hash, flags, sign_count, attested_credential })
+    }
+
+    /// Returns `true` if the user-present flag is set.
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    /// Returns `true` if the user-verified flag is set.
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+}
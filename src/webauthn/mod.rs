@@ -0,0 +1,11 @@
+/// Provides a minimal CBOR encoder and decoder for WebAuthn structures.
+pub mod cbor;
+
+/// Converts between COSE keys and JSON Web Keys.
+pub mod cose;
+
+/// Parses authenticator data and client data.
+pub mod data;
+
+/// Implements the registration and authentication ceremonies.
+pub mod relying_party;
@@ -0,0 +1,483 @@
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::random_token;
+use crate::error::AuthError;
+use crate::jose::{JwsAlgorithm, Jwk};
+use crate::model::{Identifiable, User};
+use crate::webauthn::cbor::{self, Value};
+use crate::webauthn::cose::{cose_algorithm, COSE_ALG_EDDSA, COSE_ALG_ES256};
+use crate::webauthn::data::{AuthenticatorData, CollectedClientData};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Settings of a WebAuthn relying party.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebAuthnConfig {
+    /// The RP ID, usually the registrable domain (e.g. `example.com`).
+    pub rp_id: String,
+    /// Human-readable name shown by authenticators.
+    pub rp_name: String,
+    /// The origin the browser reports, e.g. `https://login.example.com`.
+    pub origin: String,
+    /// How long a challenge stays valid, in seconds.
+    pub challenge_ttl: u64,
+    /// Whether user verification (PIN or biometrics) is required.
+    pub require_user_verification: bool,
+}
+
+impl Default for WebAuthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            rp_name: "auth_kit".to_string(),
+            origin: "http://localhost".to_string(),
+            challenge_ttl: 300,
+            require_user_verification: false,
+        }
+    }
+}
+
+/// Options for `navigator.credentials.create()`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationOptions {
+    /// The base64url challenge.
+    pub challenge: String,
+    /// The RP ID.
+    pub rp_id: String,
+    /// The RP name.
+    pub rp_name: String,
+    /// The base64url user handle; an opaque id that does not reveal the user's email.
+    pub user_id: String,
+    /// The account name shown by authenticators.
+    pub user_name: String,
+    /// Accepted COSE algorithms, in order of preference.
+    pub algorithms: Vec<i64>,
+    /// Credentials the user already has, so the same authenticator is not registered twice.
+    pub exclude_credentials: Vec<String>,
+    /// `required` or `preferred`.
+    pub user_verification: String,
+    /// Timeout in milliseconds.
+    pub timeout: u64,
+}
+
+/// Options for `navigator.credentials.get()`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationOptions {
+    /// The base64url challenge.
+    pub challenge: String,
+    /// The RP ID.
+    pub rp_id: String,
+    /// Credentials the user may sign in with; empty for discoverable credentials (passkeys).
+    pub allow_credentials: Vec<String>,
+    /// `required` or `preferred`.
+    pub user_verification: String,
+    /// Timeout in milliseconds.
+    pub timeout: u64,
+}
+
+/// The result of `navigator.credentials.create()`, with binary fields base64url-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RegistrationResponse {
+    /// The credential id.
+    pub credential_id: String,
+    /// The `clientDataJSON` bytes.
+    pub client_data_json: String,
+    /// The CBOR attestation object.
+    pub attestation_object: String,
+}
+
+/// The result of `navigator.credentials.get()`, with binary fields base64url-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AssertionResponse {
+    /// The credential id.
+    pub credential_id: String,
+    /// The `clientDataJSON` bytes.
+    pub client_data_json: String,
+    /// The authenticator data.
+    pub authenticator_data: String,
+    /// The assertion signature.
+    pub signature: String,
+    /// The user handle, returned for discoverable credentials.
+    #[serde(default)]
+    pub user_handle: Option<String>,
+}
+
+/// A registered credential.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebAuthnCredential {
+    /// The base64url credential id.
+    pub credential_id: String,
+    /// The user identity (see `Identifiable`).
+    pub subject: String,
+    /// The random base64url user handle the authenticator stores for `subject`.
+    pub user_handle: String,
+    /// The credential public key.
+    pub public_key: Jwk,
+    /// The last signature counter seen.
+    pub sign_count: u32,
+    /// The authenticator model identifier.
+    pub aaguid: [u8; 16],
+    /// The attestation statement format used at registration: `none` or `packed`.
+    pub attestation_format: String,
+    /// When the credential was registered, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// When the credential was last used to sign in.
+    pub last_used_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ceremony {
+    Registration,
+    Authentication,
+}
+
+impl Ceremony {
+    fn client_data_type(&self) -> &'static str {
+        match self {
+            Ceremony::Registration => "webauthn.create",
+            Ceremony::Authentication => "webauthn.get",
+        }
+    }
+}
+
+struct PendingChallenge {
+    ceremony: Ceremony,
+    subject: Option<String>,
+    expires_at: u64,
+}
+
+/// A WebAuthn relying party for passkey and security-key sign-in.
+///
+/// Supports ES256 and EdDSA credentials with `none` or `packed` self-attestation.
+/// Challenges are single-use and expire after `challenge_ttl`.
+///
+/// # Example
+/// ```code
+/// let mut webauthn = WebAuthn::new(WebAuthnConfig {
+///     rp_id: "example.com".to_string(),
+///     origin: "https://example.com".to_string(),
+///     ..WebAuthnConfig::default()
+/// });
+/// let options = webauthn.start_registration(&user);
+/// // pass options to navigator.credentials.create(), then:
+/// let credential = webauthn.finish_registration(&response)?;
+///
+/// let options = webauthn.start_authentication(None);
+/// let credential = webauthn.finish_authentication(&assertion)?;
+/// let user = auth.login(&credential.subject)?;
+/// ```
+pub struct WebAuthn {
+    config: WebAuthnConfig,
+    credentials: HashMap<String, WebAuthnCredential>,
+    user_handles: HashMap<String, String>,
+    challenges: HashMap<String, PendingChallenge>,
+    clock: Arc<dyn Clock>,
+}
+
+impl WebAuthn {
+    /// Creates a relying party with no registered credentials.
+    pub fn new(config: WebAuthnConfig) -> Self {
+        Self {
+            config,
+            credentials: HashMap::new(),
+            user_handles: HashMap::new(),
+            challenges: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replaces the clock used for challenge expiry.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the relying party settings.
+    pub fn config(&self) -> &WebAuthnConfig {
+        &self.config
+    }
+
+    /// Returns the user handle of `subject`, if one was assigned.
+    ///
+    /// Handles are random, so they cannot be linked back to the user's email,
+    /// and are stored with each of the user's credentials.
+    pub fn user_handle(&self, subject: &str) -> Option<&str> {
+        self.user_handles.get(subject).map(String::as_str)
+    }
+
+    /// Starts registering a new credential for `user`.
+    ///
+    /// The first registration assigns the user a random handle; later ones reuse it.
+    pub fn start_registration(&mut self, user: &User) -> RegistrationOptions {
+        let subject = user.identity();
+        let user_id = self.user_handles.entry(subject.clone()).or_insert_with(|| random_token(32)).clone();
+        let challenge = self.issue_challenge(Ceremony::Registration, Some(subject.clone()));
+        RegistrationOptions {
+            challenge,
+            rp_id: self.config.rp_id.clone(),
+            rp_name: self.config.rp_name.clone(),
+            user_id,
+            exclude_credentials: self.credentials(&subject).into_iter().map(|c| c.credential_id.clone()).collect(),
+            user_name: subject,
+            algorithms: vec![COSE_ALG_ES256, COSE_ALG_EDDSA],
+            user_verification: self.user_verification().to_string(),
+            timeout: self.config.challenge_ttl * 1000,
+        }
+    }
+
+    /// Verifies a registration response and stores the new credential.
+    ///
+    /// # Returns
+    /// * `Ok(WebAuthnCredential)` with the stored credential.
+    /// * `Err(AuthError::InvalidWebAuthnResponse)` if the challenge, origin, RP ID,
+    ///   flags or attestation statement do not check out, or the credential is already registered.
+    /// * `Err(AuthError::InvalidKey)` if the credential key is unsupported.
+    pub fn finish_registration(&mut self, response: &RegistrationResponse) -> Result<WebAuthnCredential, AuthError> {
+        let client_data_json = decode_field(&response.client_data_json, "client_data_json")?;
+        let subject = self
+            .consume_challenge(&client_data_json, Ceremony::Registration)?
+            .ok_or_else(|| invalid("registration challenge has no user"))?;
+
+        let attestation = cbor::decode(&decode_field(&response.attestation_object, "attestation_object")?)?;
+        let auth_data_bytes = attestation
+            .get(&"authData".into())
+            .and_then(Value::as_bytes)
+            .ok_or_else(|| invalid("attestation object has no authData"))?;
+        let format = attestation
+            .get(&"fmt".into())
+            .and_then(Value::as_text)
+            .ok_or_else(|| invalid("attestation object has no fmt"))?;
+        let statement = attestation.get(&"attStmt".into()).ok_or_else(|| invalid("attestation object has no attStmt"))?;
+
+        let auth_data = AuthenticatorData::parse(auth_data_bytes)?;
+        self.check_authenticator_data(&auth_data)?;
+        let attested = auth_data
+            .attested_credential
+            .clone()
+            .ok_or_else(|| invalid("authenticator data has no attested credential"))?;
+        let credential_id = URL_SAFE_NO_PAD.encode(&attested.credential_id);
+        if credential_id != response.credential_id {
+            return Err(invalid("credential id does not match authenticator data"));
+        }
+        if self.credentials.contains_key(&credential_id) {
+            return Err(invalid("credential is already registered"));
+        }
+
+        let mut signed = auth_data_bytes.to_vec();
+        signed.extend(Sha256::digest(&client_data_json));
+        match format {
+            "none" => {
+                if *statement != Value::Map(vec![]) {
+                    return Err(invalid("none attestation must have an empty statement"));
+                }
+            }
+            "packed" => verify_packed_self_attestation(statement, &attested.public_key, &signed)?,
+            other => return Err(invalid(&format!("unsupported attestation format '{}'", other))),
+        }
+
+        let user_handle = self
+            .user_handles
+            .get(&subject)
+            .cloned()
+            .ok_or_else(|| invalid("registration challenge has no user handle"))?;
+        let credential = WebAuthnCredential {
+            credential_id: credential_id.clone(),
+            subject,
+            user_handle,
+            public_key: attested.public_key,
+            sign_count: auth_data.sign_count,
+            aaguid: attested.aaguid,
+            attestation_format: format.to_string(),
+            created_at: self.clock.now(),
+            last_used_at: None,
+        };
+        self.credentials.insert(credential_id, credential.clone());
+        Ok(credential)
+    }
+
+    /// Starts a sign-in.
+    ///
+    /// # Arguments
+    /// * `subject` - The user signing in, or `None` to let the authenticator offer
+    ///   its discoverable credentials (passkeys).
+    pub fn start_authentication(&mut self, subject: Option<&str>) -> AuthenticationOptions {
+        let allow_credentials = subject
+            .map(|s| self.credentials(s).into_iter().map(|c| c.credential_id.clone()).collect())
+            .unwrap_or_default();
+        AuthenticationOptions {
+            challenge: self.issue_challenge(Ceremony::Authentication, subject.map(str::to_string)),
+            rp_id: self.config.rp_id.clone(),
+            allow_credentials,
+            user_verification: self.user_verification().to_string(),
+            timeout: self.config.challenge_ttl * 1000,
+        }
+    }
+
+    /// Verifies an assertion and updates the credential's signature counter.
+    ///
+    /// # Returns
+    /// * `Ok(WebAuthnCredential)` with the updated credential; its `subject` is the signed-in user.
+    /// * `Err(AuthError::SignCountRegression)` if the counter did not increase, which
+    ///   suggests a cloned authenticator.
+    /// * `Err(AuthError::InvalidWebAuthnResponse)` if any other check fails.
+    pub fn finish_authentication(&mut self, response: &AssertionResponse) -> Result<WebAuthnCredential, AuthError> {
+        let client_data_json = decode_field(&response.client_data_json, "client_data_json")?;
+        let expected_subject = self.consume_challenge(&client_data_json, Ceremony::Authentication)?;
+
+        let credential = self
+            .credentials
+            .get(&response.credential_id)
+            .cloned()
+            .ok_or_else(|| invalid("unknown credential"))?;
+        if expected_subject.is_some_and(|s| s != credential.subject) {
+            return Err(invalid("credential belongs to another user"));
+        }
+        if response.user_handle.as_ref().is_some_and(|h| *h != credential.user_handle) {
+            return Err(invalid("user handle does not match credential"));
+        }
+
+        let auth_data_bytes = decode_field(&response.authenticator_data, "authenticator_data")?;
+        let auth_data = AuthenticatorData::parse(&auth_data_bytes)?;
+        self.check_authenticator_data(&auth_data)?;
+
+        let mut signed = auth_data_bytes;
+        signed.extend(Sha256::digest(&client_data_json));
+        let signature = decode_field(&response.signature, "signature")?;
+        if !verify_signature(&credential.public_key, &signed, &signature)? {
+            return Err(invalid("signature verification failed"));
+        }
+
+        if (auth_data.sign_count != 0 || credential.sign_count != 0) && auth_data.sign_count <= credential.sign_count {
+            return Err(AuthError::SignCountRegression(credential.credential_id));
+        }
+
+        let stored = self.credentials.get_mut(&response.credential_id).ok_or_else(|| invalid("unknown credential"))?;
+        stored.sign_count = auth_data.sign_count;
+        stored.last_used_at = Some(self.clock.now());
+        Ok(stored.clone())
+    }
+
+    /// Returns the credentials registered by `subject`.
+    pub fn credentials(&self, subject: &str) -> Vec<&WebAuthnCredential> {
+        let mut credentials: Vec<&WebAuthnCredential> = self.credentials.values().filter(|c| c.subject == subject).collect();
+        credentials.sort_by_key(|c| c.created_at);
+        credentials
+    }
+
+    /// Returns a credential by its base64url id.
+    pub fn credential(&self, credential_id: &str) -> Option<&WebAuthnCredential> {
+        self.credentials.get(credential_id)
+    }
+
+    /// Restores a credential, e.g. loaded from a database, along with its user handle.
+    pub fn insert_credential(&mut self, credential: WebAuthnCredential) {
+        self.user_handles.entry(credential.subject.clone()).or_insert_with(|| credential.user_handle.clone());
+        self.credentials.insert(credential.credential_id.clone(), credential);
+    }
+
+    /// Removes a credential, returning it if it existed.
+    pub fn remove_credential(&mut self, credential_id: &str) -> Option<WebAuthnCredential> {
+        self.credentials.remove(credential_id)
+    }
+
+    /// Removes expired challenges.
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.challenges.retain(|_, c| c.expires_at > now);
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.config.require_user_verification { "required" } else { "preferred" }
+    }
+
+    fn issue_challenge(&mut self, ceremony: Ceremony, subject: Option<String>) -> String {
+        let challenge = random_token(32);
+        self.challenges.insert(
+            challenge.clone(),
+            PendingChallenge {
+                ceremony,
+                subject,
+                expires_at: self.clock.now() + self.config.challenge_ttl,
+            },
+        );
+        challenge
+    }
+
+    fn consume_challenge(&mut self, client_data_json: &[u8], ceremony: Ceremony) -> Result<Option<String>, AuthError> {
+        let client_data: CollectedClientData =
+            serde_json::from_slice(client_data_json).map_err(|_| invalid("malformed client data"))?;
+        if client_data.ceremony != ceremony.client_data_type() {
+            return Err(invalid("unexpected client data type"));
+        }
+        if client_data.origin != self.config.origin || client_data.cross_origin {
+            return Err(invalid("unexpected origin"));
+        }
+        let pending = self
+            .challenges
+            .remove(&client_data.challenge)
+            .filter(|p| p.ceremony == ceremony && p.expires_at > self.clock.now())
+            .ok_or_else(|| invalid("unknown or expired challenge"))?;
+        Ok(pending.subject)
+    }
+
+    fn check_authenticator_data(&self, auth_data: &AuthenticatorData) -> Result<(), AuthError> {
+        if auth_data.rp_id_hash[..] != Sha256::digest(self.config.rp_id.as_bytes())[..] {
+            return Err(invalid("credential is scoped to another RP ID"));
+        }
+        if !auth_data.user_present() {
+            return Err(invalid("user presence flag is not set"));
+        }
+        if self.config.require_user_verification && !auth_data.user_verified() {
+            return Err(invalid("user verification is required"));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: &str) -> AuthError {
+    AuthError::InvalidWebAuthnResponse(reason.to_string())
+}
+
+fn decode_field(value: &str, name: &str) -> Result<Vec<u8>, AuthError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| invalid(&format!("{} is not base64url", name)))
+}
+
+/// Verifies a WebAuthn signature; ES256 signatures arrive DER-encoded.
+fn verify_signature(key: &Jwk, message: &[u8], signature: &[u8]) -> Result<bool, AuthError> {
+    match key.algorithm()? {
+        JwsAlgorithm::ES256 => match p256::ecdsa::Signature::from_der(signature) {
+            Ok(signature) => key.verify(message, &signature.to_bytes()),
+            Err(_) => Ok(false),
+        },
+        JwsAlgorithm::EdDSA => key.verify(message, signature),
+    }
+}
+
+/// Checks a `packed` statement signed by the credential key itself (WebAuthn section 8.2).
+fn verify_packed_self_attestation(statement: &Value, key: &Jwk, signed: &[u8]) -> Result<(), AuthError> {
+    if statement.get(&"x5c".into()).is_some() {
+        return Err(invalid("packed attestation with a certificate chain is not supported"));
+    }
+    let alg = statement
+        .get(&"alg".into())
+        .and_then(Value::as_integer)
+        .ok_or_else(|| invalid("packed attestation has no alg"))?;
+    if alg != cose_algorithm(key.algorithm()?) as i128 {
+        return Err(invalid("packed attestation alg does not match the credential key"));
+    }
+    let signature = statement
+        .get(&"sig".into())
+        .and_then(Value::as_bytes)
+        .ok_or_else(|| invalid("packed attestation has no sig"))?;
+    if !verify_signature(key, signed, signature)? {
+        return Err(invalid("packed attestation signature is invalid"));
+    }
+    Ok(())
+}
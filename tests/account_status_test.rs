@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::{hash_password_with_cost, Authentication};
+    use auth_kit::auth::auth_z::Authorization;
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use auth_kit::model::{AccountStatus, AuthContext, Permission, Role, User};
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn auth(activation_required: bool) -> Authentication {
+        let mut auth = Authentication::new()
+            .with_activation_required(activation_required)
+            .with_clock(Arc::new(ManualClock::new(1_000)));
+        auth.register(EMAIL, &hash_password_with_cost("secret123", 4).unwrap()).unwrap();
+        auth
+    }
+
+    #[test]
+    fn test_pending_account_must_be_activated() {
+        let mut auth = auth(true);
+        assert_eq!(auth.users[EMAIL].status, AccountStatus::Pending);
+        assert_eq!(auth.login(EMAIL).unwrap_err(), AuthError::AccountPending);
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap_err(), AuthError::AccountPending);
+        assert_eq!(
+            auth.enable(EMAIL, None),
+            Err(AuthError::InvalidStatusTransition { from: AccountStatus::Pending, to: AccountStatus::Active })
+        );
+
+        auth.activate(EMAIL).unwrap();
+        assert!(auth.login(EMAIL).unwrap().is_some());
+        assert_eq!(
+            auth.activate(EMAIL),
+            Err(AuthError::InvalidStatusTransition { from: AccountStatus::Active, to: AccountStatus::Active })
+        );
+    }
+
+    #[test]
+    fn test_disable_and_enable_record_reason_and_time() {
+        let mut auth = auth(false);
+        assert!(auth.login(EMAIL).unwrap().is_some());
+
+        auth.disable(EMAIL, Some("left the company")).unwrap();
+        let user = &auth.users[EMAIL];
+        assert_eq!(user.status, AccountStatus::Disabled);
+        assert_eq!(user.status_reason.as_deref(), Some("left the company"));
+        assert_eq!(user.status_changed_at, Some(1_000));
+        assert_eq!(auth.login(EMAIL).unwrap_err(), AuthError::AccountDisabled);
+
+        auth.enable(EMAIL, None).unwrap();
+        assert!(auth.login(EMAIL).unwrap().is_some());
+
+        auth.lock(EMAIL, Some("suspected compromise")).unwrap();
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap_err(), AuthError::AccountSuspended);
+        assert_eq!(auth.login_with_password(EMAIL, "wrong", None).unwrap_err(), AuthError::InvalidPassword);
+        auth.enable(EMAIL, Some("password rotated")).unwrap();
+        assert!(auth.login(EMAIL).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_deletion_is_final() {
+        let mut auth = auth(false);
+        auth.delete(EMAIL, Some("user request")).unwrap();
+
+        assert_eq!(auth.login(EMAIL).unwrap_err(), AuthError::AccountDeleted);
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap_err(), AuthError::InvalidPassword);
+        assert!(auth.users[EMAIL].password_hash.is_empty());
+        assert_eq!(auth.register(EMAIL, "hash"), Err(AuthError::EmailAlreadyRegistered));
+        for status in [AccountStatus::Active, AccountStatus::Disabled, AccountStatus::Locked] {
+            assert_eq!(
+                auth.set_status(EMAIL, status, None),
+                Err(AuthError::InvalidStatusTransition { from: AccountStatus::Deleted, to: status })
+            );
+        }
+    }
+
+    #[test]
+    fn test_transition_table() {
+        use AccountStatus::*;
+        let all = [Pending, Active, Disabled, Locked, Deleted];
+        let allowed: Vec<(AccountStatus, AccountStatus)> = all
+            .iter()
+            .flat_map(|from| all.iter().map(move |to| (*from, *to)))
+            .filter(|(from, to)| from.can_transition_to(*to))
+            .collect();
+        assert_eq!(
+            allowed,
+            vec![
+                (Pending, Active),
+                (Pending, Deleted),
+                (Active, Disabled),
+                (Active, Locked),
+                (Active, Deleted),
+                (Disabled, Active),
+                (Disabled, Deleted),
+                (Locked, Active),
+                (Locked, Disabled),
+                (Locked, Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_authorization_refuses_inactive_users() {
+        let mut user = User {
+            email: EMAIL.to_string(),
+            role: Role { name: "editor".to_string(), permissions: vec![Permission::Read] },
+            ..Default::default()
+        };
+        let mut rbac = Authorization::new("RBAC").unwrap();
+        let context = |user: &User| AuthContext { user: Some(user.clone()), claims: None, resource: None };
+        assert!(rbac.authorize(&context(&user), "docs", "read", None).is_ok());
+
+        user.transition(AccountStatus::Disabled, 1_000, None).unwrap();
+        assert_eq!(rbac.authorize(&context(&user), "docs", "read", None), Err(AuthError::AccountDisabled));
+
+        let mut abac = Authorization::new("ABAC").unwrap();
+        assert_eq!(abac.authorize(&context(&user), "docs", "read", None), Err(AuthError::AccountDisabled));
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::audience::AudienceBinding;
+    use auth_kit::auth::auth_z::Authorization;
+    use auth_kit::error::AuthError;
+    use auth_kit::model::{AuthContext, Claims};
+
+    fn context(service: &str, audiences: &[&str]) -> AuthContext {
+        AuthContext {
+            user: None,
+            claims: Some(Claims {
+                email: "jwt@example.com".to_string(),
+                service: service.to_string(),
+                scopes: vec!["*".to_string()],
+                audiences: audiences.iter().map(|s| s.to_string()).collect(),
+            }),
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn test_token_for_other_service_is_rejected() {
+        let mut auth = Authorization::new("SBA").unwrap();
+        assert_eq!(
+            auth.authorize(&context("admin_service", &[]), "user_service", "read", None),
+            Err(AuthError::WrongAudience {
+                service: "user_service".to_string(),
+                audiences: "admin_service".to_string(),
+            })
+        );
+        assert!(auth.authorize(&context("admin_service", &[]), "admin_service", "read", None).is_ok());
+    }
+
+    #[test]
+    fn test_multiple_audiences() {
+        let mut auth = Authorization::new("SBA").unwrap();
+        let ctx = context("admin_service", &["user_service", "billing"]);
+        assert!(auth.authorize(&ctx, "user_service", "read", None).is_ok());
+        assert!(auth.authorize(&ctx, "billing", "read", None).is_ok());
+        assert!(auth.authorize(&ctx, "shipping", "read", None).is_err());
+    }
+
+    #[test]
+    fn test_token_without_audience_is_rejected() {
+        let mut auth = Authorization::new("SBA").unwrap();
+        assert_eq!(
+            auth.authorize(&context("", &[]), "user_service", "read", None),
+            Err(AuthError::WrongAudience {
+                service: "user_service".to_string(),
+                audiences: "".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_wildcard_audiences_per_engine() {
+        let binding = AudienceBinding::default().accept("internal-*");
+        let mut auth = Authorization::new("SBA").unwrap().with_audience_binding(binding);
+        assert!(auth.authorize(&context("internal-gateway", &[]), "user_service", "read", None).is_ok());
+        assert!(auth.authorize(&context("external-gateway", &[]), "user_service", "read", None).is_err());
+    }
+
+    #[test]
+    fn test_wildcard_token_audience_needs_engine_opt_in() {
+        let mut strict = Authorization::new("SBA").unwrap();
+        assert!(strict.authorize(&context("*", &[]), "user_service", "read", None).is_err());
+
+        let mut lenient = Authorization::new("SBA").unwrap().with_audience_binding(AudienceBinding::default().accept("*"));
+        assert!(lenient.authorize(&context("*", &[]), "user_service", "read", None).is_ok());
+    }
+
+    #[test]
+    fn test_disabled_binding() {
+        let binding = AudienceBinding::disabled();
+        assert!(!binding.is_enforced());
+        let mut auth = Authorization::new("SBA").unwrap().with_audience_binding(binding);
+        assert!(auth.authorize(&context("admin_service", &[]), "user_service", "read", None).is_ok());
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_z::Authorization;
+    use auth_kit::auth::candidate::CandidateTemplates;
+    use auth_kit::error::AuthError;
+    use auth_kit::model::{AuthContext, Claims, Resource};
+
+    fn context(scopes: &[&str], resource: Option<Resource>) -> AuthContext {
+        AuthContext {
+            user: None,
+            claims: Some(Claims {
+                email: "jwt@example.com".to_string(),
+                service: "billing".to_string(),
+                scopes: scopes.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            }),
+            resource,
+        }
+    }
+
+    fn invoice(id: Option<&str>) -> Resource {
+        Resource {
+            department: "finance".to_string(),
+            resource_type: Some("invoice".to_string()),
+            resource_id: id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_templates_keep_bare_fallback() {
+        let mut auth = Authorization::new("SBA").unwrap();
+        assert!(auth.authorize(&context(&["read"], None), "billing", "read", None).is_ok());
+    }
+
+    #[test]
+    fn test_strict_mode_requires_fully_qualified_scope() {
+        let mut auth = Authorization::new("SBA").unwrap().with_candidate_templates(CandidateTemplates::strict());
+        let resource = Resource { department: "finance".to_string(), ..Default::default() };
+
+        assert!(auth.authorize(&context(&["read"], Some(resource.clone())), "billing", "read", None).is_err());
+        assert!(auth.authorize(&context(&["billing.read"], Some(resource.clone())), "billing", "read", None).is_err());
+        assert!(auth.authorize(&context(&["billing.finance.read"], Some(resource)), "billing", "read", None).is_ok());
+    }
+
+    #[test]
+    fn test_fallback_levels() {
+        let templates = CandidateTemplates::default().fallback_levels(1);
+        assert_eq!(
+            templates.active(),
+            ["{service}{delim}{department}{delim}{permission}", "{service}{delim}{permission}"]
+        );
+        assert!(!templates.is_strict());
+        assert!(CandidateTemplates::strict().is_strict());
+
+        let mut auth = Authorization::new("SBA").unwrap().with_candidate_templates(templates);
+        assert!(auth.authorize(&context(&["read"], None), "billing", "read", None).is_err());
+        assert!(auth.authorize(&context(&["billing.read"], None), "billing", "read", None).is_ok());
+    }
+
+    #[test]
+    fn test_custom_templates() {
+        let templates = CandidateTemplates::new([
+            "{service}:{resource_type}:{resource_id}:{action}",
+            "{service}:{resource_type}:{action}",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            templates.render("billing", &invoice(Some("42")), "read", "."),
+            vec!["billing:invoice:42:read", "billing:invoice:read"]
+        );
+        assert_eq!(templates.render("billing", &invoice(None), "read", "."), vec!["billing:invoice:read"]);
+
+        let mut auth = Authorization::new("SBA").unwrap().with_candidate_templates(templates.fallback_levels(0));
+        let ctx = context(&["billing:invoice:42:*"], Some(invoice(Some("42"))));
+        assert!(auth.authorize(&ctx, "billing", "read", None).is_ok());
+        let ctx = context(&["billing:invoice:42:*"], Some(invoice(Some("43"))));
+        assert!(auth.authorize(&ctx, "billing", "read", None).is_err());
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(matches!(CandidateTemplates::new(Vec::<&str>::new()), Err(AuthError::InvalidTemplate(_))));
+        assert!(matches!(CandidateTemplates::new([""]), Err(AuthError::InvalidTemplate(_))));
+        assert!(matches!(CandidateTemplates::new(["{service"]), Err(AuthError::InvalidTemplate(_))));
+        assert!(matches!(CandidateTemplates::new(["service}"]), Err(AuthError::InvalidTemplate(_))));
+        assert_eq!(
+            CandidateTemplates::new(["{service}:{tenant}"]),
+            Err(AuthError::InvalidTemplate("unknown placeholder '{tenant}' in '{service}:{tenant}'".to_string()))
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Fixtures shared by the OAuth2 integration tests.
+#![allow(dead_code)]
+
+use auth_kit::auth::auth_n::hash_password_with_cost;
+use auth_kit::clock::ManualClock;
+use auth_kit::error::{AuthError, OAuthError};
+use auth_kit::model::{Role, User};
+use auth_kit::oauth2::pkce::{code_challenge, CodeChallengeMethod};
+use auth_kit::oauth2::refresh::{InMemoryRefreshTokenStore, RefreshTokenManager, RefreshTokenPolicy};
+use auth_kit::oauth2::server::{AuthorizationRequest, AuthorizationServer, ServerConfig, TokenRequest, TokenResponse};
+use std::sync::Arc;
+
+/// The PKCE verifier of RFC 7636 Appendix B.
+pub const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+pub const REDIRECT: &str = "https://app.example.com/callback";
+
+/// A server running on `clock`, without any clients.
+pub fn server(config: ServerConfig, clock: &ManualClock) -> AuthorizationServer {
+    AuthorizationServer::new(config).with_clock(Arc::new(clock.clone()))
+}
+
+/// An in-memory refresh token manager.
+pub fn refresh_tokens(policy: RefreshTokenPolicy) -> RefreshTokenManager {
+    RefreshTokenManager::new(Box::new(InMemoryRefreshTokenStore::new()), policy)
+}
+
+/// A user with no role, department or clearance.
+pub fn user(email: &str) -> User {
+    User {
+        email: email.to_string(),
+        password_hash: "".to_string(),
+        role: Role { name: "".to_string(), permissions: vec![] },
+        department: "".to_string(),
+        clearance_level: 0,
+        ..Default::default()
+    }
+}
+
+/// Hashes a client secret at the lowest bcrypt cost, to keep tests fast.
+pub fn secret_hash(secret: &str) -> String {
+    hash_password_with_cost(secret, 4).unwrap()
+}
+
+/// Runs the authorization code grant with PKCE and the client's only redirect URI.
+pub fn login(server: &mut AuthorizationServer, client_id: &str, scope: &str, user: &User) -> TokenResponse {
+    let response = server
+        .authorize(
+            &AuthorizationRequest {
+                response_type: "code".to_string(),
+                client_id: client_id.to_string(),
+                scope: Some(scope.to_string()),
+                code_challenge: Some(code_challenge(VERIFIER, CodeChallengeMethod::S256)),
+                code_challenge_method: Some("S256".to_string()),
+                ..Default::default()
+            },
+            user,
+        )
+        .unwrap();
+    server
+        .token(&TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            code: Some(response.code),
+            client_id: Some(client_id.to_string()),
+            code_verifier: Some(VERIFIER.to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+}
+
+/// Runs the refresh token grant for a public client.
+pub fn refresh(server: &mut AuthorizationServer, client_id: &str, token: &str) -> Result<TokenResponse, AuthError> {
+    server.token(&TokenRequest {
+        grant_type: "refresh_token".to_string(),
+        client_id: Some(client_id.to_string()),
+        refresh_token: Some(token.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Unwraps the OAuth2 error of a failed call.
+pub fn oauth_err(result: Result<impl std::fmt::Debug, AuthError>) -> OAuthError {
+    match result {
+        Err(AuthError::OAuth(e)) => e,
+        other => panic!("expected OAuth error, got {:?}", other),
+    }
+}
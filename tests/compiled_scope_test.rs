@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::compiled_scope::CompiledScopes;
+    use auth_kit::auth::scope::{authorize_with_matcher, FlexibleMatcher};
+
+    fn matches(user_scopes: &str, required: &str) -> bool {
+        CompiledScopes::from_scope_string(user_scopes).matches(required)
+    }
+
+    #[test]
+    fn test_exact_match() {
+        assert!(matches("read:users", "read:users"));
+    }
+
+    #[test]
+    fn test_wildcard_permission() {
+        assert!(matches("read:*", "read:users"));
+        assert!(!matches("read:*", "write:users"));
+    }
+
+    #[test]
+    fn test_wildcard_service() {
+        assert!(matches("*:users", "read:users"));
+        assert!(!matches("*:admin", "read:users"));
+    }
+
+    #[test]
+    fn test_multiple_scopes_space_separated() {
+        let user_scopes = "read:posts write:users";
+        assert!(matches(user_scopes, "write:users"));
+        assert!(!matches(user_scopes, "admin:users"));
+    }
+
+    #[test]
+    fn test_different_delimiters() {
+        assert!(matches("read.users", "read.users"));
+        assert!(matches("read@users", "read@users"));
+        assert!(matches("read/users", "read/users"));
+    }
+
+    #[test]
+    fn test_fallback_to_wildcard() {
+        assert!(matches("*", "read:users"));
+    }
+
+    #[test]
+    fn test_three_part_scope() {
+        assert!(matches("user_service:user:read", "user_service:user:read"));
+        assert!(matches("user_service:*:read", "user_service:user:read"));
+        assert!(matches("*:*:read", "user_service:user:read"));
+        assert!(!matches("user_service:user:write", "user_service:user:read"));
+    }
+
+    #[test]
+    fn test_segment_count_must_agree() {
+        assert!(!matches("read:*", "read:users:all"));
+        assert!(!matches("*:*:read", "user:read"));
+    }
+
+    #[test]
+    fn test_agrees_with_flexible_matcher() {
+        let user_scopes = "read:* user_service:*:read a.b:c *.x @y x/*/z billing.invoice";
+        let required = [
+            "read:users", "read:users:all", "user_service:user:read", "user_service:user:write",
+            "a.b:c", "a.q:c", "a.b", "q.x", "x@y", "x/y/z", "x/y/q", "billing.invoice",
+            "billing", "*", "", "read", "write:users",
+        ];
+
+        let compiled = CompiledScopes::from_scope_string(user_scopes);
+        for r in required {
+            assert_eq!(
+                compiled.matches(r),
+                authorize_with_matcher::<FlexibleMatcher>(user_scopes, r),
+                "mismatch for required scope {:?}",
+                r
+            );
+        }
+    }
+}
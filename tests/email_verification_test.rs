@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::auth::verification::{EmailVerification, UnverifiedAccess, VerificationPolicy};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use auth_kit::mail::{validate_email, Email, FileMailer, InMemoryMailer, Mailer};
+    use auth_kit::model::AccountStatus;
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn verification(mailer: &InMemoryMailer, clock: &ManualClock, access: UnverifiedAccess) -> EmailVerification {
+        let policy = VerificationPolicy {
+            unverified_access: access,
+            verify_url: "https://app.example.com/verify?token={token}".to_string(),
+            max_sends: 3,
+            ..VerificationPolicy::default()
+        };
+        EmailVerification::new(Box::new(mailer.clone()), policy).with_clock(Arc::new(clock.clone()))
+    }
+
+    fn token(mailer: &InMemoryMailer) -> String {
+        let body = mailer.last_to(EMAIL).unwrap().body;
+        let start = body.find("token=").unwrap() + "token=".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_blocked_until_verified() {
+        let (mailer, clock) = (InMemoryMailer::new(), ManualClock::new(1_000));
+        let mut verification = verification(&mailer, &clock, UnverifiedAccess::Block);
+        let mut auth = Authentication::new();
+
+        verification.register(&mut auth, EMAIL, "hash").unwrap();
+        assert_eq!(auth.users[EMAIL].status, AccountStatus::Pending);
+        assert_eq!(auth.login(EMAIL).unwrap_err(), AuthError::AccountPending);
+        assert_eq!(mailer.sent().len(), 1);
+
+        let token = token(&mailer);
+        assert_eq!(verification.confirm(&mut auth, &token).unwrap(), EMAIL);
+        let user = auth.login(EMAIL).unwrap().unwrap();
+        assert_eq!(user.email_verified_at, Some(1_000));
+
+        assert_eq!(verification.confirm(&mut auth, &token), Err(AuthError::InvalidToken));
+        assert_eq!(verification.send(&auth, EMAIL), Err(AuthError::EmailAlreadyVerified));
+    }
+
+    #[test]
+    fn test_restricted_accounts_can_log_in_unverified() {
+        let (mailer, clock) = (InMemoryMailer::new(), ManualClock::new(1_000));
+        let mut verification = verification(&mailer, &clock, UnverifiedAccess::Restrict);
+        let mut auth = Authentication::new();
+
+        verification.register(&mut auth, EMAIL, "hash").unwrap();
+        assert!(!auth.login(EMAIL).unwrap().unwrap().is_email_verified());
+
+        verification.confirm(&mut auth, &token(&mailer)).unwrap();
+        assert!(auth.login(EMAIL).unwrap().unwrap().is_email_verified());
+    }
+
+    #[test]
+    fn test_tokens_expire_and_resend_replaces_them() {
+        let (mailer, clock) = (InMemoryMailer::new(), ManualClock::new(1_000));
+        let mut verification = verification(&mailer, &clock, UnverifiedAccess::Block);
+        let mut auth = Authentication::new();
+        verification.register(&mut auth, EMAIL, "hash").unwrap();
+        let first = token(&mailer);
+
+        clock.advance(24 * 3600);
+        assert_eq!(verification.confirm(&mut auth, &first), Err(AuthError::InvalidToken));
+
+        verification.send(&auth, EMAIL).unwrap();
+        let second = token(&mailer);
+        clock.advance(60);
+        verification.send(&auth, EMAIL).unwrap();
+        assert_eq!(verification.confirm(&mut auth, &second), Err(AuthError::InvalidToken));
+        verification.confirm(&mut auth, &token(&mailer)).unwrap();
+    }
+
+    #[test]
+    fn test_resend_is_rate_limited() {
+        let (mailer, clock) = (InMemoryMailer::new(), ManualClock::new(1_000));
+        let mut verification = verification(&mailer, &clock, UnverifiedAccess::Block);
+        let mut auth = Authentication::new();
+        verification.register(&mut auth, EMAIL, "hash").unwrap();
+
+        assert_eq!(verification.send(&auth, EMAIL), Err(AuthError::TooManyAttempts { retry_at: 1_060 }));
+        clock.advance(60);
+        verification.send(&auth, EMAIL).unwrap();
+        clock.advance(60);
+        verification.send(&auth, EMAIL).unwrap();
+        clock.advance(60);
+        assert_eq!(verification.send(&auth, EMAIL), Err(AuthError::TooManyAttempts { retry_at: 1_000 + 24 * 3600 }));
+        assert_eq!(mailer.sent().len(), 3);
+
+        clock.set(1_000 + 24 * 3600);
+        verification.send(&auth, EMAIL).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_emails_are_rejected() {
+        for email in ["", "alice", "alice@", "@example.com", "alice@localhost", "al ice@example.com", "a@b@example.com", "alice@-example.com"] {
+            assert_eq!(validate_email(email), Err(AuthError::InvalidEmail(email.to_string())), "{}", email);
+        }
+        validate_email("alice+tag@mail.example.co.uk").unwrap();
+
+        let mut auth = Authentication::new();
+        assert_eq!(auth.register("not an email", "hash"), Err(AuthError::InvalidEmail("not an email".to_string())));
+    }
+
+    #[test]
+    fn test_file_mailer_appends_emails() {
+        let path = std::env::temp_dir().join(format!("auth_kit_mail_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mailer = FileMailer::new(&path);
+        for subject in ["first", "second"] {
+            mailer.send(&Email { to: EMAIL.to_string(), subject: subject.to_string(), body: "hello".to_string() }).unwrap();
+        }
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.contains("To: alice@example.com\nSubject: first\n\nhello\n"));
+        assert!(contents.contains("Subject: second"));
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::scope::{authorize_with_matcher, GlobMatcher};
+
+    fn matches(user_scopes: &str, required: &str) -> bool {
+        authorize_with_matcher::<GlobMatcher>(user_scopes, required)
+    }
+
+    #[test]
+    fn test_flexible_matcher_cases_still_match() {
+        assert!(matches("read:users", "read:users"));
+        assert!(matches("read:*", "read:users"));
+        assert!(!matches("read:*", "write:users"));
+        assert!(matches("*:users", "read:users"));
+        assert!(matches("read.users", "read.users"));
+        assert!(matches("*", "read:users"));
+        assert!(matches("*:*:read", "user_service:user:read"));
+        assert!(!matches("user_service:user:write", "user_service:user:read"));
+    }
+
+    #[test]
+    fn test_single_wildcard_is_one_segment() {
+        assert!(!matches("billing:*", "billing:invoice:read"));
+    }
+
+    #[test]
+    fn test_recursive_wildcard() {
+        assert!(matches("billing:**", "billing:invoice"));
+        assert!(matches("billing:**", "billing:invoice:read"));
+        assert!(matches("billing:**", "billing:invoice:line:read"));
+        assert!(!matches("billing:**", "billing"));
+        assert!(!matches("billing:**", "shipping:invoice:read"));
+    }
+
+    #[test]
+    fn test_recursive_wildcard_in_middle() {
+        assert!(matches("billing:**:read", "billing:invoice:read"));
+        assert!(matches("billing:**:read", "billing:invoice:line:read"));
+        assert!(!matches("billing:**:read", "billing:read"));
+        assert!(!matches("billing:**:read", "billing:invoice:line:write"));
+    }
+
+    #[test]
+    fn test_prefix_glob() {
+        assert!(matches("report.read_*", "report.read_summary"));
+        assert!(matches("report.read_*", "report.read_"));
+        assert!(!matches("report.read_*", "report.write_summary"));
+        assert!(!matches("report.read_*", "report.read_summary.extra"));
+        assert!(matches("report.*_summary", "report.read_summary"));
+    }
+
+    #[test]
+    fn test_alternation() {
+        assert!(matches("user:{read,list}", "user:read"));
+        assert!(matches("user:{read,list}", "user:list"));
+        assert!(!matches("user:{read,list}", "user:delete"));
+        assert!(matches("{user,group}:read", "group:read"));
+        assert!(matches("user:{read,list}_*", "user:list_all"));
+    }
+
+    #[test]
+    fn test_pattern_without_delimiter() {
+        assert!(matches("read_*", "read_users"));
+        assert!(!matches("read_*", "read_users:all"));
+        assert!(matches("**", "anything:at:all"));
+    }
+
+    #[test]
+    fn test_malformed_pattern_only_matches_itself() {
+        assert!(matches("user:{read", "user:{read"));
+        assert!(!matches("user:{read", "user:read"));
+        assert!(!matches("user:read}", "user:read"));
+    }
+
+    #[test]
+    fn test_many_wildcards_do_not_backtrack() {
+        let segment = format!("{}b", "*a".repeat(30));
+        let input = "a".repeat(200);
+        assert!(!matches(&segment, &input));
+        assert!(matches(&segment, &format!("{}b", input)));
+
+        let pattern = vec!["**"; 30].join(":") + ":end";
+        let required = vec!["x"; 200].join(":");
+        assert!(!matches(&pattern, &required));
+        assert!(matches(&pattern, &format!("{}:end", required)));
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::error::AuthError;
+    use auth_kit::mfa::hotp::{hotp, HotpAuthenticator, HotpConfig, OtpAlgorithm};
+
+    const SECRET: &[u8] = b"12345678901234567890";
+    const EMAIL: &str = "alice@example.com";
+
+    const RFC_4226_CODES: [&str; 10] = [
+        "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489",
+    ];
+
+    fn authenticator() -> HotpAuthenticator {
+        let mut hotp = HotpAuthenticator::new(HotpConfig { look_ahead: 3, resync_window: 50, ..HotpConfig::default() });
+        hotp.register(EMAIL, SECRET.to_vec(), 0);
+        hotp
+    }
+
+    fn code(counter: u64) -> String {
+        hotp(SECRET, counter, 6, OtpAlgorithm::Sha1)
+    }
+
+    #[test]
+    fn test_rfc_4226_vectors() {
+        for (counter, expected) in RFC_4226_CODES.iter().enumerate() {
+            assert_eq!(hotp(SECRET, counter as u64, 6, OtpAlgorithm::Sha1), *expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_advances_counter() {
+        let mut hotp = authenticator();
+        hotp.verify(EMAIL, RFC_4226_CODES[0]).unwrap();
+        assert_eq!(hotp.credential(EMAIL).unwrap().counter, 1);
+        assert_eq!(hotp.verify(EMAIL, RFC_4226_CODES[0]), Err(AuthError::InvalidOtp));
+
+        hotp.verify(EMAIL, RFC_4226_CODES[3]).unwrap();
+        assert_eq!(hotp.credential(EMAIL).unwrap().counter, 4);
+        assert_eq!(hotp.verify(EMAIL, RFC_4226_CODES[2]), Err(AuthError::InvalidOtp));
+
+        assert_eq!(hotp.verify(EMAIL, RFC_4226_CODES[8]), Err(AuthError::InvalidOtp));
+        assert_eq!(hotp.verify("bob@example.com", RFC_4226_CODES[0]), Err(AuthError::MfaNotEnrolled));
+    }
+
+    #[test]
+    fn test_resync_after_drift() {
+        let mut hotp = authenticator();
+        assert_eq!(hotp.verify(EMAIL, &code(20)), Err(AuthError::InvalidOtp));
+
+        assert_eq!(hotp.resync(EMAIL, &code(20), &code(22)), Err(AuthError::InvalidOtp));
+        hotp.resync(EMAIL, &code(20), &code(21)).unwrap();
+        assert_eq!(hotp.credential(EMAIL).unwrap().counter, 22);
+        hotp.verify(EMAIL, &code(22)).unwrap();
+
+        assert_eq!(hotp.resync(EMAIL, &code(80), &code(81)), Err(AuthError::InvalidOtp));
+        hotp.disable(EMAIL);
+        assert!(!hotp.is_enrolled(EMAIL));
+    }
+
+    #[test]
+    fn test_token_locks_after_repeated_failures() {
+        let mut hotp = authenticator();
+        for _ in 0..4 {
+            assert_eq!(hotp.verify(EMAIL, "000000"), Err(AuthError::InvalidOtp));
+        }
+        hotp.verify(EMAIL, RFC_4226_CODES[0]).unwrap();
+        assert_eq!(hotp.credential(EMAIL).unwrap().failures, 0);
+
+        for _ in 0..5 {
+            assert_eq!(hotp.verify(EMAIL, "000000"), Err(AuthError::InvalidOtp));
+        }
+        assert_eq!(hotp.verify(EMAIL, RFC_4226_CODES[1]), Err(AuthError::OtpLocked));
+
+        hotp.resync(EMAIL, RFC_4226_CODES[1], RFC_4226_CODES[2]).unwrap();
+        hotp.verify(EMAIL, RFC_4226_CODES[3]).unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(hotp.verify(EMAIL, "000000"), Err(AuthError::InvalidOtp));
+        }
+        hotp.unlock(EMAIL).unwrap();
+        hotp.verify(EMAIL, RFC_4226_CODES[4]).unwrap();
+    }
+}
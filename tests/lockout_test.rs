@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::{hash_password_with_cost, Authentication};
+    use auth_kit::auth::lockout::{InMemoryAttemptStore, LockoutPolicy, LoginThrottle};
+    use auth_kit::auth::password::PasswordPolicy;
+    use auth_kit::clock::{Clock, ManualClock};
+    use auth_kit::error::AuthError;
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+    const IP: &str = "203.0.113.7";
+
+    fn policy() -> LockoutPolicy {
+        LockoutPolicy { max_account_failures: 3, lockout_duration: 600, max_ip_failures: 5, base_delay: 2, max_delay: 5, ..LockoutPolicy::default() }
+    }
+
+    fn auth(clock: &ManualClock, policy: LockoutPolicy) -> Authentication {
+        let throttle = LoginThrottle::new(Box::new(InMemoryAttemptStore::new()), policy).with_clock(Arc::new(clock.clone()));
+        let passwords = PasswordPolicy { cost: 4, ..PasswordPolicy::default() };
+        let mut auth = Authentication::new().with_throttle(throttle).with_password_policy(passwords);
+        auth.register(EMAIL, &hash_password_with_cost("secret123", 4).unwrap()).unwrap();
+        auth
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_maximum() {
+        let policy = policy();
+        assert_eq!((0..5).map(|n| policy.delay(n)).collect::<Vec<_>>(), vec![0, 2, 4, 5, 5]);
+        assert_eq!(policy.delay(200), 5);
+
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, policy);
+        assert_eq!(auth.login_with_password(EMAIL, "wrong", None).unwrap_err(), AuthError::InvalidPassword);
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap_err(), AuthError::TooManyAttempts { retry_at: 1_002 });
+
+        clock.advance(2);
+        assert_eq!(auth.login_with_password(EMAIL, "wrong", None).unwrap_err(), AuthError::InvalidPassword);
+        assert_eq!(auth.login_with_password(EMAIL, "wrong", None).unwrap_err(), AuthError::TooManyAttempts { retry_at: 1_006 });
+
+        clock.advance(4);
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap().email, EMAIL);
+        assert_eq!(auth.throttle().unwrap().account(EMAIL).unwrap(), None);
+    }
+
+    #[test]
+    fn test_account_locks_and_unlocks_after_cool_down() {
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, LockoutPolicy { base_delay: 0, ..policy() });
+
+        for _ in 0..3 {
+            assert_eq!(auth.login_with_password(EMAIL, "wrong", None).unwrap_err(), AuthError::InvalidPassword);
+        }
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap_err(), AuthError::AccountLocked { until: 1_600 });
+        assert_eq!(auth.login(EMAIL).unwrap_err(), AuthError::AccountLocked { until: 1_600 });
+
+        clock.set(1_600);
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap().email, EMAIL);
+        assert!(auth.login(EMAIL).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_admin_unlock_lifts_the_lock() {
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, LockoutPolicy { base_delay: 0, ..policy() });
+        for _ in 0..3 {
+            auth.login_with_password(EMAIL, "wrong", None).unwrap_err();
+        }
+        assert!(matches!(auth.login(EMAIL), Err(AuthError::AccountLocked { .. })));
+
+        auth.unlock(EMAIL).unwrap();
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", None).unwrap().email, EMAIL);
+    }
+
+    #[test]
+    fn test_ip_is_throttled_across_accounts() {
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, policy());
+
+        for n in 0..5 {
+            let email = format!("user{}@example.com", n);
+            assert_eq!(auth.login_with_password(&email, "guess", Some(IP)).unwrap_err(), AuthError::InvalidPassword);
+        }
+        assert_eq!(auth.throttle().unwrap().account("user0@example.com").unwrap().unwrap().failures, 1);
+        let retry_at = clock.now() + 900;
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", Some(IP)).unwrap_err(), AuthError::TooManyAttempts { retry_at });
+        assert_eq!(auth.login_with_password(EMAIL, "secret123", Some("198.51.100.1")).unwrap().email, EMAIL);
+    }
+
+    #[test]
+    fn test_failures_are_forgotten_after_a_quiet_period() {
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, LockoutPolicy { reset_after: 100, ..policy() });
+
+        auth.login_with_password(EMAIL, "wrong", None).unwrap_err();
+        clock.advance(10);
+        auth.login_with_password(EMAIL, "wrong", None).unwrap_err();
+        assert_eq!(auth.throttle().unwrap().account(EMAIL).unwrap().unwrap().failures, 2);
+
+        clock.advance(100);
+        assert_eq!(auth.throttle().unwrap().account(EMAIL).unwrap(), None);
+        auth.login_with_password(EMAIL, "wrong", None).unwrap_err();
+        assert_eq!(auth.throttle().unwrap().account(EMAIL).unwrap().unwrap().failures, 1);
+    }
+
+    #[test]
+    fn test_accounts_without_password_hash_still_lock() {
+        let clock = ManualClock::new(1_000);
+        let mut auth = auth(&clock, LockoutPolicy { base_delay: 0, ..policy() });
+        auth.register("sso@example.com", "").unwrap();
+        auth.register("legacy@example.com", "not-a-bcrypt-hash").unwrap();
+
+        for email in ["sso@example.com", "legacy@example.com"] {
+            for _ in 0..3 {
+                assert_eq!(auth.login_with_password(email, "", None).unwrap_err(), AuthError::InvalidPassword);
+            }
+            assert_eq!(auth.login_with_password(email, "", None).unwrap_err(), AuthError::AccountLocked { until: 1_600 });
+        }
+    }
+}
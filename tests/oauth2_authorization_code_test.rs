@@ -0,0 +1,237 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{self, oauth_err, refresh, refresh_tokens, user, REDIRECT, VERIFIER};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::model::{AccountStatus, User};
+    use auth_kit::oauth2::client::{Client, GrantType};
+    use auth_kit::oauth2::pkce::{code_challenge, CodeChallengeMethod};
+    use auth_kit::oauth2::refresh::RefreshTokenPolicy;
+    use auth_kit::oauth2::server::{error_redirect_url, AuthorizationRequest, AuthorizationServer, ServerConfig, TokenRequest};
+
+    const EMAIL: &str = "user@example.com";
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let config = ServerConfig { audience: "user_service".to_string(), ..ServerConfig::default() };
+        let mut server = common::server(config, clock).with_refresh_tokens(refresh_tokens(RefreshTokenPolicy::default()));
+        server
+            .register_client(
+                Client::new("web_app")
+                    .with_redirect_uri(REDIRECT)
+                    .with_grant_types(&[GrantType::AuthorizationCode, GrantType::RefreshToken])
+                    .with_scopes(&["user_service:user:*", "profile"]),
+            )
+            .unwrap();
+        server
+    }
+
+    fn auth_request(method: Option<&str>) -> AuthorizationRequest {
+        let challenge = match method {
+            Some("S256") => code_challenge(VERIFIER, CodeChallengeMethod::S256),
+            _ => VERIFIER.to_string(),
+        };
+        AuthorizationRequest {
+            response_type: "code".to_string(),
+            client_id: "web_app".to_string(),
+            redirect_uri: Some(REDIRECT.to_string()),
+            scope: Some("user_service:user:read profile".to_string()),
+            state: Some("xyz 123".to_string()),
+            code_challenge: Some(challenge),
+            code_challenge_method: method.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn token_request(code: &str) -> TokenRequest {
+        TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            code: Some(code.to_string()),
+            redirect_uri: Some(REDIRECT.to_string()),
+            client_id: Some("web_app".to_string()),
+            code_verifier: Some(VERIFIER.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pkce_rfc7636_vector() {
+        assert_eq!(
+            code_challenge(VERIFIER, CodeChallengeMethod::S256),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_full_flow_with_s256() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+        assert_eq!(response.state.as_deref(), Some("xyz 123"));
+        assert!(response.redirect_url().starts_with("https://app.example.com/callback?code="));
+        assert!(response.redirect_url().ends_with("&state=xyz%20123"));
+
+        let tokens = server.token(&token_request(&response.code)).unwrap();
+        assert_eq!(tokens.token_type, "Bearer");
+        assert_eq!(tokens.scope, "user_service:user:read profile");
+
+        let claims = server.validate_access_token(&tokens.access_token).unwrap();
+        assert_eq!(claims.email, "user@example.com");
+        assert_eq!(claims.service, "user_service");
+        assert_eq!(claims.scopes, vec!["user_service:user:read", "profile"]);
+    }
+
+    #[test]
+    fn test_plain_pkce_can_be_disabled() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let response = server.authorize(&auth_request(None), &user(EMAIL)).unwrap();
+        assert!(server.token(&token_request(&response.code)).is_ok());
+
+        let mut strict = AuthorizationServer::new(ServerConfig { allow_plain_pkce: false, ..ServerConfig::default() });
+        strict.register_client(Client::new("web_app").with_redirect_uri(REDIRECT).with_scopes(&["profile"])).unwrap();
+        let mut request = auth_request(Some("plain"));
+        request.scope = Some("profile".to_string());
+        assert!(matches!(oauth_err(strict.authorize(&request, &user(EMAIL))), OAuthError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn test_wrong_verifier_is_rejected() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+
+        let mut request = token_request(&response.code);
+        request.code_verifier = Some("x".repeat(43));
+        assert!(matches!(oauth_err(server.token(&request)), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_code_is_one_time_and_replay_revokes_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+
+        let tokens = server.token(&token_request(&response.code)).unwrap();
+        let rotated = refresh(&mut server, "web_app", &tokens.refresh_token.unwrap()).unwrap();
+
+        assert!(matches!(oauth_err(server.token(&token_request(&response.code))), OAuthError::InvalidGrant(_)));
+        assert_eq!(server.validate_access_token(&tokens.access_token), Err(AuthError::InvalidToken));
+        assert_eq!(server.validate_access_token(&rotated.access_token), Err(AuthError::InvalidToken));
+        assert!(matches!(oauth_err(refresh(&mut server, "web_app", &rotated.refresh_token.unwrap())), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_inactive_users_get_no_codes() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let disabled = User { status: AccountStatus::Disabled, ..user(EMAIL) };
+        assert_eq!(server.authorize(&auth_request(Some("S256")), &disabled).unwrap_err(), AuthError::AccountDisabled);
+
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+        server.revoke_subject(EMAIL).unwrap();
+        assert!(matches!(oauth_err(server.token(&token_request(&response.code))), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_code_expires() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+
+        clock.advance(61);
+        assert_eq!(
+            oauth_err(server.token(&token_request(&response.code))),
+            OAuthError::InvalidGrant("authorization code has expired".to_string())
+        );
+    }
+
+    #[test]
+    fn test_access_token_expires() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+        let tokens = server.token(&token_request(&response.code)).unwrap();
+
+        clock.advance(3_600);
+        assert_eq!(server.validate_access_token(&tokens.access_token), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_redirect_uri_validation() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let mut request = auth_request(Some("S256"));
+        request.redirect_uri = Some("https://evil.example.com/callback".to_string());
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::InvalidRequest(_)));
+
+        let response = server.authorize(&auth_request(Some("S256")), &user(EMAIL)).unwrap();
+        let mut token = token_request(&response.code);
+        token.redirect_uri = None;
+        assert!(matches!(oauth_err(server.token(&token)), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_client_registration_rejects_bad_redirect_uris() {
+        let mut server = AuthorizationServer::new(ServerConfig::default());
+        for uri in ["not a uri", "http://example.com/cb", "https://app.example.com/cb#frag", "https:///cb"] {
+            let result = server.register_client(Client::new(uri).with_redirect_uri(uri));
+            assert!(matches!(oauth_err(result), OAuthError::InvalidRequest(_)), "{} should be rejected", uri);
+        }
+        assert!(server.register_client(Client::new("a").with_redirect_uri("http://127.0.0.1:8080/cb")).is_ok());
+        assert!(server.register_client(Client::new("b").with_redirect_uri("com.example.app:/oauth")).is_ok());
+        assert!(server.register_client(Client::new("a")).is_err());
+    }
+
+    #[test]
+    fn test_scope_validation() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let mut request = auth_request(Some("S256"));
+        request.scope = Some("admin_service:read".to_string());
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::InvalidScope(_)));
+
+        request.scope = None;
+        let validated = server.validate_authorization_request(&request).unwrap();
+        assert_eq!(validated.scopes, vec!["user_service:user:*", "profile"]);
+    }
+
+    #[test]
+    fn test_request_validation_errors() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let mut request = auth_request(Some("S256"));
+        request.client_id = "unknown".to_string();
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::InvalidClient(_)));
+
+        let mut request = auth_request(Some("S256"));
+        request.response_type = "token".to_string();
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::UnsupportedResponseType(_)));
+
+        let mut request = auth_request(Some("S256"));
+        request.code_challenge = None;
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::InvalidRequest(_)));
+
+        let mut request = auth_request(Some("S512"));
+        request.code_challenge_method = Some("S512".to_string());
+        assert!(matches!(oauth_err(server.authorize(&request, &user(EMAIL))), OAuthError::InvalidRequest(_)));
+
+        let token = TokenRequest { grant_type: "password".to_string(), ..Default::default() };
+        assert!(matches!(oauth_err(server.token(&token)), OAuthError::UnsupportedGrantType(_)));
+    }
+
+    #[test]
+    fn test_error_redirect_url() {
+        let error = OAuthError::AccessDenied("user denied consent".to_string());
+        assert_eq!(
+            error_redirect_url(REDIRECT, &error, Some("abc")),
+            "https://app.example.com/callback?error=access_denied&error_description=user%20denied%20consent&state=abc"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{self, oauth_err, secret_hash};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::oauth2::client::{Client, ClientCredentials, GrantType};
+    use auth_kit::oauth2::server::{AuthorizationServer, ServerConfig, TokenRequest};
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let config = ServerConfig { audience: "user_service".to_string(), ..ServerConfig::default() };
+        let mut server = common::server(config, clock);
+        server
+            .register_client(
+                Client::new("billing_worker")
+                    .with_secret_hash(&secret_hash("s3cret"))
+                    .with_grant_types(&[GrantType::ClientCredentials])
+                    .with_scopes(&["user_service:user:read", "user_service:invoice:*"])
+                    .with_access_token_ttl(300),
+            )
+            .unwrap();
+        server
+            .register_client(Client::new("spa").with_redirect_uri("https://spa.example.com/cb").with_scopes(&["profile"]))
+            .unwrap();
+        server
+    }
+
+    fn request(client_id: &str, secret: Option<&str>, scope: Option<&str>) -> TokenRequest {
+        TokenRequest {
+            grant_type: "client_credentials".to_string(),
+            client_id: Some(client_id.to_string()),
+            client_secret: secret.map(str::to_string),
+            scope: scope.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_client_credentials_issues_limited_claims() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let tokens = server
+            .token(&request("billing_worker", Some("s3cret"), Some("user_service:invoice:read")))
+            .unwrap();
+        assert_eq!(tokens.expires_in, 300);
+        assert_eq!(tokens.scope, "user_service:invoice:read");
+
+        let claims = server.validate_access_token(&tokens.access_token).unwrap();
+        assert_eq!(claims.email, "billing_worker");
+        assert_eq!(claims.service, "user_service");
+        assert_eq!(claims.scopes, vec!["user_service:invoice:read"]);
+
+        clock.advance(300);
+        assert_eq!(server.validate_access_token(&tokens.access_token), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_default_scope_is_client_allowed_scopes() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let tokens = server.token(&request("billing_worker", Some("s3cret"), None)).unwrap();
+        assert_eq!(tokens.scope, "user_service:user:read user_service:invoice:*");
+    }
+
+    #[test]
+    fn test_scope_outside_allowed_is_rejected() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let result = server.token(&request("billing_worker", Some("s3cret"), Some("user_service:user:write")));
+        assert!(matches!(oauth_err(result), OAuthError::InvalidScope(_)));
+    }
+
+    #[test]
+    fn test_client_authentication() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        assert!(matches!(oauth_err(server.token(&request("billing_worker", Some("wrong"), None))), OAuthError::InvalidClient(_)));
+        assert!(matches!(oauth_err(server.token(&request("billing_worker", None, None))), OAuthError::InvalidClient(_)));
+        assert!(matches!(oauth_err(server.token(&request("unknown", Some("s3cret"), None))), OAuthError::InvalidClient(_)));
+    }
+
+    #[test]
+    fn test_grant_type_restrictions() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        assert!(matches!(oauth_err(server.token(&request("spa", None, None))), OAuthError::UnauthorizedClient(_)));
+
+        let code_request = TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            client_id: Some("billing_worker".to_string()),
+            client_secret: Some("s3cret".to_string()),
+            code: Some("abc".to_string()),
+            code_verifier: Some("v".repeat(43)),
+            ..Default::default()
+        };
+        assert!(matches!(oauth_err(server.token(&code_request)), OAuthError::UnauthorizedClient(_)));
+    }
+
+    #[test]
+    fn test_basic_auth_header() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let mut token_request = request("", None, None);
+        token_request.apply_basic_auth("Basic YmlsbGluZ193b3JrZXI6czNjcmV0").unwrap();
+        assert_eq!(token_request.client_id.as_deref(), Some("billing_worker"));
+        assert!(server.token(&token_request).is_ok());
+
+        assert!(matches!(oauth_err(token_request.apply_basic_auth("Bearer abc")), OAuthError::InvalidClient(_)));
+    }
+
+    #[test]
+    fn test_secret_rotation_with_overlap() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        server.rotate_client_secret("billing_worker", &secret_hash("n3w"), 600).unwrap();
+        assert!(server.token(&request("billing_worker", Some("s3cret"), None)).is_ok());
+        assert!(server.token(&request("billing_worker", Some("n3w"), None)).is_ok());
+
+        clock.advance(600);
+        assert!(matches!(oauth_err(server.token(&request("billing_worker", Some("s3cret"), None))), OAuthError::InvalidClient(_)));
+        assert!(server.token(&request("billing_worker", Some("n3w"), None)).is_ok());
+        assert_eq!(server.clients().get("billing_worker").unwrap().secrets.len(), 2);
+
+        server.rotate_client_secret("billing_worker", &secret_hash("n3w2"), 0).unwrap();
+        assert_eq!(server.clients().get("billing_worker").unwrap().secrets.len(), 2);
+        assert!(matches!(oauth_err(server.token(&request("billing_worker", Some("n3w"), None))), OAuthError::InvalidClient(_)));
+        assert!(server.rotate_client_secret("unknown", &secret_hash("x"), 0).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{self, oauth_err, refresh_tokens, user};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::model::{AccountStatus, User};
+    use auth_kit::oauth2::client::{Client, GrantType};
+    use auth_kit::oauth2::device::{
+        generate_user_code, normalize_user_code, DeviceAuthorizationRequest, DeviceAuthorizationResponse,
+        USER_CODE_ALPHABET,
+    };
+    use auth_kit::oauth2::refresh::RefreshTokenPolicy;
+    use auth_kit::oauth2::server::{AuthorizationServer, ServerConfig, TokenRequest, TokenResponse};
+
+    const EMAIL: &str = "viewer@example.com";
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let config = ServerConfig { verification_uri: "https://auth.example.com/device".to_string(), ..ServerConfig::default() };
+        let mut server = common::server(config, clock).with_refresh_tokens(refresh_tokens(RefreshTokenPolicy::default()));
+        server
+            .register_client(
+                Client::new("tv_app")
+                    .with_grant_types(&[GrantType::DeviceCode, GrantType::RefreshToken])
+                    .with_scopes(&["media:read", "media:write"]),
+            )
+            .unwrap();
+        server
+            .register_client(Client::new("web_app").with_redirect_uri("https://app.example.com/cb").with_scopes(&["media:read"]))
+            .unwrap();
+        server
+    }
+
+    fn start(server: &mut AuthorizationServer) -> DeviceAuthorizationResponse {
+        server
+            .device_authorization(&DeviceAuthorizationRequest {
+                client_id: Some("tv_app".to_string()),
+                scope: Some("media:read".to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    fn poll(server: &mut AuthorizationServer, device_code: &str) -> Result<TokenResponse, AuthError> {
+        server.token(&TokenRequest {
+            grant_type: "urn:ietf:params:oauth:grant-type:device_code".to_string(),
+            client_id: Some("tv_app".to_string()),
+            device_code: Some(device_code.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_user_code_format() {
+        for _ in 0..100 {
+            let code = generate_user_code();
+            assert_eq!(code.len(), 9);
+            assert_eq!(&code[4..5], "-");
+            assert!(normalize_user_code(&code).bytes().all(|b| USER_CODE_ALPHABET.contains(&b)));
+        }
+        for ambiguous in [b'0', b'O', b'1', b'I', b'A', b'E', b'U'] {
+            assert!(!USER_CODE_ALPHABET.contains(&ambiguous));
+        }
+        assert_eq!(normalize_user_code(" wdjb mjht "), "WDJBMJHT");
+        assert_eq!(normalize_user_code("WDJB-MJHT"), "WDJBMJHT");
+    }
+
+    #[test]
+    fn test_device_flow_approved() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let started = start(&mut server);
+        assert_eq!(started.expires_in, 600);
+        assert_eq!(started.interval, 5);
+        assert_eq!(started.verification_uri, "https://auth.example.com/device");
+        assert_eq!(
+            started.verification_uri_complete,
+            format!("https://auth.example.com/device?user_code={}", started.user_code)
+        );
+
+        assert!(matches!(
+            oauth_err(poll(&mut server, &started.device_code)),
+            OAuthError::AuthorizationPending(_)
+        ));
+
+        let typed = started.user_code.to_lowercase().replace('-', " ");
+        let pending = server.device_verification(&typed).unwrap();
+        assert_eq!(pending.client_id, "tv_app");
+        assert_eq!(pending.scopes, vec!["media:read"]);
+        server.approve_device(&typed, &user(EMAIL)).unwrap();
+        assert!(server.approve_device(&typed, &user(EMAIL)).is_err());
+
+        clock.advance(5);
+        let tokens = poll(&mut server, &started.device_code).unwrap();
+        assert_eq!(tokens.scope, "media:read");
+        assert!(tokens.refresh_token.is_some());
+        assert_eq!(server.validate_access_token(&tokens.access_token).unwrap().email, EMAIL);
+
+        clock.advance(5);
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_slow_down_increases_interval() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let started = start(&mut server);
+
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::AuthorizationPending(_)));
+        clock.advance(2);
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::SlowDown(_)));
+        assert_eq!(server.device_verification(&started.user_code).unwrap().interval, 10);
+
+        clock.advance(5);
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::SlowDown(_)));
+        clock.advance(15);
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::AuthorizationPending(_)));
+    }
+
+    #[test]
+    fn test_denied_and_expired() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let denied = start(&mut server);
+        server.deny_device(&denied.user_code).unwrap();
+        assert!(matches!(oauth_err(poll(&mut server, &denied.device_code)), OAuthError::AccessDenied(_)));
+
+        let expired = start(&mut server);
+        clock.advance(600);
+        assert!(server.device_verification(&expired.user_code).is_err());
+        assert!(server.approve_device(&expired.user_code, &user(EMAIL)).is_err());
+        assert!(matches!(oauth_err(poll(&mut server, &expired.device_code)), OAuthError::ExpiredToken(_)));
+    }
+
+    #[test]
+    fn test_inactive_users_cannot_approve() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let started = start(&mut server);
+
+        let locked = User { status: AccountStatus::Locked, ..user(EMAIL) };
+        assert_eq!(server.approve_device(&started.user_code, &locked), Err(AuthError::AccountSuspended));
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::AuthorizationPending(_)));
+
+        server.approve_device(&started.user_code, &user(EMAIL)).unwrap();
+        server.revoke_subject(EMAIL).unwrap();
+        clock.advance(5);
+        assert!(matches!(oauth_err(poll(&mut server, &started.device_code)), OAuthError::AccessDenied(_)));
+    }
+
+    #[test]
+    fn test_device_grant_requires_client_permission() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+
+        let result = server.device_authorization(&DeviceAuthorizationRequest {
+            client_id: Some("web_app".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(oauth_err(result), OAuthError::UnauthorizedClient(_)));
+
+        let result = server.device_authorization(&DeviceAuthorizationRequest {
+            client_id: Some("tv_app".to_string()),
+            scope: Some("admin".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(oauth_err(result), OAuthError::InvalidScope(_)));
+
+        let started = start(&mut server);
+        server.approve_device(&started.user_code, &user(EMAIL)).unwrap();
+        let stolen = server.token(&TokenRequest {
+            grant_type: "urn:ietf:params:oauth:grant-type:device_code".to_string(),
+            client_id: Some("web_app".to_string()),
+            device_code: Some(started.device_code.clone()),
+            ..Default::default()
+        });
+        assert!(matches!(oauth_err(stolen), OAuthError::UnauthorizedClient(_)));
+        assert!(poll(&mut server, &started.device_code).is_ok());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(OAuthError::AuthorizationPending(String::new()).error_code(), "authorization_pending");
+        assert_eq!(OAuthError::SlowDown(String::new()).error_code(), "slow_down");
+        assert_eq!(OAuthError::ExpiredToken(String::new()).error_code(), "expired_token");
+    }
+}
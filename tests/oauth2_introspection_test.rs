@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::hash_password_with_cost;
+    use auth_kit::auth::auth_z::Authorization;
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::model::{AuthContext, Resource, Role, User};
+    use auth_kit::oauth2::client::{Client, ClientCredentials, GrantType};
+    use auth_kit::oauth2::introspection::{IntrospectionRequest, IntrospectionResponse, RevocationRequest};
+    use auth_kit::oauth2::pkce::{code_challenge, CodeChallengeMethod};
+    use auth_kit::oauth2::refresh::{InMemoryRefreshTokenStore, RefreshTokenManager, RefreshTokenPolicy};
+    use auth_kit::oauth2::server::{
+        AuthorizationRequest, AuthorizationServer, ServerConfig, TokenRequest, TokenResponse,
+    };
+    use std::sync::Arc;
+
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let hash = |secret: &str| hash_password_with_cost(secret, 4).unwrap();
+        let refresh = RefreshTokenManager::new(Box::new(InMemoryRefreshTokenStore::new()), RefreshTokenPolicy::default());
+        let mut server = AuthorizationServer::new(ServerConfig {
+            issuer: "https://auth.example.com".to_string(),
+            audience: "user_service".to_string(),
+            ..ServerConfig::default()
+        })
+        .with_clock(Arc::new(clock.clone()))
+        .with_refresh_tokens(refresh);
+
+        server
+            .register_client(Client::new("resource_server").with_secret_hash(&hash("rs-secret")).with_grant_types(&[]))
+            .unwrap();
+        server
+            .register_client(
+                Client::new("billing_worker")
+                    .with_secret_hash(&hash("s3cret"))
+                    .with_grant_types(&[GrantType::ClientCredentials])
+                    .with_scopes(&["user_service:user:read"])
+                    .with_access_token_ttl(300),
+            )
+            .unwrap();
+        server
+            .register_client(
+                Client::new("web_app")
+                    .with_redirect_uri("https://app.example.com/callback")
+                    .with_grant_types(&[GrantType::AuthorizationCode, GrantType::RefreshToken])
+                    .with_scopes(&["user_service:user:read"]),
+            )
+            .unwrap();
+        server
+    }
+
+    fn worker_token(server: &mut AuthorizationServer) -> TokenResponse {
+        server
+            .token(&TokenRequest {
+                grant_type: "client_credentials".to_string(),
+                client_id: Some("billing_worker".to_string()),
+                client_secret: Some("s3cret".to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    fn user_tokens(server: &mut AuthorizationServer) -> TokenResponse {
+        let user = User {
+            email: "user@example.com".to_string(),
+            password_hash: "".to_string(),
+            role: Role { name: "".to_string(), permissions: vec![] },
+            department: "".to_string(),
+            clearance_level: 0,
+            ..Default::default()
+        };
+        let response = server
+            .authorize(
+                &AuthorizationRequest {
+                    response_type: "code".to_string(),
+                    client_id: "web_app".to_string(),
+                    code_challenge: Some(code_challenge(VERIFIER, CodeChallengeMethod::S256)),
+                    code_challenge_method: Some("S256".to_string()),
+                    ..Default::default()
+                },
+                &user,
+            )
+            .unwrap();
+        server
+            .token(&TokenRequest {
+                grant_type: "authorization_code".to_string(),
+                code: Some(response.code),
+                client_id: Some("web_app".to_string()),
+                code_verifier: Some(VERIFIER.to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    fn introspection(token: &str) -> IntrospectionRequest {
+        IntrospectionRequest {
+            token: token.to_string(),
+            client_id: Some("resource_server".to_string()),
+            client_secret: Some("rs-secret".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn revocation(token: &str, client_id: &str) -> RevocationRequest {
+        RevocationRequest {
+            token: token.to_string(),
+            client_id: Some(client_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_introspect_active_access_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = worker_token(&mut server).access_token;
+
+        let response = server.introspect(&introspection(&token)).unwrap();
+        assert!(response.active);
+        assert_eq!(response.scope.as_deref(), Some("user_service:user:read"));
+        assert_eq!(response.sub.as_deref(), Some("billing_worker"));
+        assert_eq!(response.client_id.as_deref(), Some("billing_worker"));
+        assert_eq!(response.exp, Some(1_300));
+        assert_eq!(response.token_type.as_deref(), Some("access_token"));
+
+        clock.advance(300);
+        assert_eq!(server.introspect(&introspection(&token)).unwrap(), IntrospectionResponse::inactive());
+    }
+
+    #[test]
+    fn test_introspect_refresh_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let refresh = user_tokens(&mut server).refresh_token.unwrap();
+
+        let mut request = introspection(&refresh);
+        request.token_type_hint = Some("refresh_token".to_string());
+        let response = server.introspect(&request).unwrap();
+        assert!(response.active);
+        assert_eq!(response.sub.as_deref(), Some("user@example.com"));
+        assert_eq!(response.token_type.as_deref(), Some("refresh_token"));
+
+        assert!(!server.introspect(&introspection("unknown")).unwrap().active);
+    }
+
+    #[test]
+    fn test_introspection_requires_client_authentication() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = worker_token(&mut server).access_token;
+
+        let mut request = introspection(&token);
+        request.client_secret = Some("wrong".to_string());
+        assert!(matches!(server.introspect(&request), Err(AuthError::OAuth(OAuthError::InvalidClient(_)))));
+
+        request.client_id = Some("web_app".to_string());
+        request.client_secret = None;
+        assert!(matches!(
+            server.introspect(&request),
+            Err(AuthError::OAuth(OAuthError::UnauthorizedClient(_)))
+        ));
+
+        let mut request = introspection(&token);
+        request.client_id = None;
+        request.client_secret = None;
+        request.apply_basic_auth("Basic cmVzb3VyY2Vfc2VydmVyOnJzLXNlY3JldA==").unwrap();
+        assert!(server.introspect(&request).unwrap().active);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = worker_token(&mut server).access_token;
+
+        assert_eq!(IntrospectionResponse::inactive().to_json(), r#"{"active":false}"#);
+
+        let json = server.introspect(&introspection(&token)).unwrap().to_json();
+        assert!(json.starts_with(r#"{"active":true,"scope":"user_service:user:read""#));
+        assert_eq!(
+            IntrospectionResponse::from_json(&json).unwrap(),
+            server.introspect(&introspection(&token)).unwrap()
+        );
+        assert!(IntrospectionResponse::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_claims_from_introspection_authorize_sba() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = worker_token(&mut server).access_token;
+        let json = server.introspect(&introspection(&token)).unwrap().to_json();
+
+        let claims = IntrospectionResponse::from_json(&json).unwrap().to_claims().unwrap();
+        assert_eq!(claims.email, "billing_worker");
+        assert_eq!(claims.service, "user_service");
+        assert_eq!(claims.scopes, vec!["user_service:user:read"]);
+
+        let mut authz = Authorization::new("SBA").unwrap();
+        let context = AuthContext {
+            user: None,
+            claims: Some(claims),
+            resource: Some(Resource { department: "user".to_string(), ..Default::default() }),
+        };
+        assert!(authz.authorize(&context, "user_service", "read", Some(":")).is_ok());
+        assert!(authz.authorize(&context, "user_service", "delete", Some(":")).is_err());
+
+        assert_eq!(IntrospectionResponse::inactive().to_claims(), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_aud_accepts_string_or_array() {
+        let single = IntrospectionResponse::from_json(r#"{"active":true,"sub":"a","aud":"user_service"}"#).unwrap();
+        assert_eq!(single.aud, vec!["user_service"]);
+        assert!(single.to_json().contains(r#""aud":"user_service""#));
+
+        let json = r#"{"active":true,"sub":"a","aud":["user_service","billing_service"]}"#;
+        let many = IntrospectionResponse::from_json(json).unwrap();
+        assert_eq!(many.aud, vec!["user_service", "billing_service"]);
+        assert!(many.to_json().contains(r#""aud":["user_service","billing_service"]"#));
+
+        let claims = many.to_claims().unwrap();
+        assert_eq!(claims.service, "user_service");
+        assert_eq!(claims.audiences, vec!["billing_service"]);
+        assert!(claims.audiences().contains(&"billing_service"));
+    }
+
+    #[test]
+    fn test_revoke_access_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = worker_token(&mut server).access_token;
+
+        let mut request = revocation(&token, "web_app");
+        assert!(matches!(server.revoke(&request), Err(AuthError::OAuth(OAuthError::UnauthorizedClient(_)))));
+        assert!(server.validate_access_token(&token).is_ok());
+
+        request.client_id = Some("billing_worker".to_string());
+        assert!(matches!(server.revoke(&request), Err(AuthError::OAuth(OAuthError::InvalidClient(_)))));
+
+        request.client_secret = Some("s3cret".to_string());
+        server.revoke(&request).unwrap();
+        assert_eq!(server.validate_access_token(&token), Err(AuthError::InvalidToken));
+        assert!(server.revoke(&request).is_ok());
+    }
+
+    #[test]
+    fn test_revoke_refresh_token_revokes_family() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let tokens = user_tokens(&mut server);
+        let refresh = tokens.refresh_token.unwrap();
+
+        server.revoke(&revocation(&refresh, "web_app")).unwrap();
+        assert_eq!(server.validate_access_token(&tokens.access_token), Err(AuthError::InvalidToken));
+        assert!(!server.introspect(&introspection(&refresh)).unwrap().active);
+
+        let result = server.token(&TokenRequest {
+            grant_type: "refresh_token".to_string(),
+            client_id: Some("web_app".to_string()),
+            refresh_token: Some(refresh),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(AuthError::OAuth(OAuthError::InvalidGrant(_)))));
+    }
+}
@@ -0,0 +1,167 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{self, oauth_err, refresh_tokens, user, REDIRECT};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::oauth2::client::{Client, GrantType};
+    use auth_kit::oauth2::refresh::RefreshTokenPolicy;
+    use auth_kit::oauth2::server::{AuthorizationServer, ServerConfig, TokenRequest, TokenResponse};
+
+    const POLICY: RefreshTokenPolicy = RefreshTokenPolicy { idle_ttl: 1_000, absolute_ttl: 2_500 };
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let mut server = common::server(ServerConfig::default(), clock).with_refresh_tokens(refresh_tokens(POLICY));
+        server
+            .register_client(
+                Client::new("web_app")
+                    .with_redirect_uri(REDIRECT)
+                    .with_grant_types(&[GrantType::AuthorizationCode, GrantType::RefreshToken])
+                    .with_scopes(&["read", "write"]),
+            )
+            .unwrap();
+        server
+            .register_client(Client::new("other_app").with_redirect_uri(REDIRECT).with_scopes(&["read"]))
+            .unwrap();
+        server
+    }
+
+    fn login(server: &mut AuthorizationServer, client_id: &str) -> TokenResponse {
+        common::login(server, client_id, "read", &user("user@example.com"))
+    }
+
+    fn refresh(server: &mut AuthorizationServer, token: &str) -> Result<TokenResponse, AuthError> {
+        common::refresh(server, "web_app", token)
+    }
+
+    #[test]
+    fn test_refresh_token_only_for_allowed_clients() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        assert!(login(&mut server, "web_app").refresh_token.is_some());
+        assert!(login(&mut server, "other_app").refresh_token.is_none());
+    }
+
+    #[test]
+    fn test_rotation_issues_new_tokens() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let first = login(&mut server, "web_app");
+        let first_refresh = first.refresh_token.unwrap();
+
+        let second = refresh(&mut server, &first_refresh).unwrap();
+        let second_refresh = second.refresh_token.clone().unwrap();
+        assert_ne!(first_refresh, second_refresh);
+        assert_eq!(second.scope, "read");
+        assert_eq!(server.validate_access_token(&second.access_token).unwrap().email, "user@example.com");
+
+        assert!(refresh(&mut server, &second_refresh).is_ok());
+    }
+
+    #[test]
+    fn test_reuse_revokes_family() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let first = login(&mut server, "web_app");
+        let first_refresh = first.refresh_token.unwrap();
+        let second = refresh(&mut server, &first_refresh).unwrap();
+
+        assert!(matches!(oauth_err(refresh(&mut server, &first_refresh)), OAuthError::InvalidGrant(_)));
+        assert!(matches!(
+            oauth_err(refresh(&mut server, &second.refresh_token.unwrap())),
+            OAuthError::InvalidGrant(_)
+        ));
+        assert_eq!(server.validate_access_token(&first.access_token), Err(AuthError::InvalidToken));
+        assert_eq!(server.validate_access_token(&second.access_token), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_revoke_subject_ends_every_session() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let first = login(&mut server, "web_app");
+        let second = login(&mut server, "web_app");
+
+        server.revoke_subject("user@example.com").unwrap();
+        for tokens in [first, second] {
+            assert_eq!(server.validate_access_token(&tokens.access_token), Err(AuthError::InvalidToken));
+            assert!(matches!(oauth_err(refresh(&mut server, &tokens.refresh_token.unwrap())), OAuthError::InvalidGrant(_)));
+        }
+    }
+
+    #[test]
+    fn test_idle_and_absolute_expiry() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let mut token = login(&mut server, "web_app").refresh_token.unwrap();
+
+        clock.advance(900);
+        token = refresh(&mut server, &token).unwrap().refresh_token.unwrap();
+        clock.advance(900);
+        token = refresh(&mut server, &token).unwrap().refresh_token.unwrap();
+
+        clock.advance(700);
+        assert_eq!(
+            oauth_err(refresh(&mut server, &token)),
+            OAuthError::InvalidGrant("refresh token is invalid or expired".to_string())
+        );
+
+        let idle = login(&mut server, "web_app").refresh_token.unwrap();
+        clock.advance(1_000);
+        assert!(matches!(oauth_err(refresh(&mut server, &idle)), OAuthError::InvalidGrant(_)));
+    }
+
+    #[test]
+    fn test_refresh_with_narrower_scope() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = login(&mut server, "web_app").refresh_token.unwrap();
+
+        let mut request = TokenRequest {
+            grant_type: "refresh_token".to_string(),
+            client_id: Some("web_app".to_string()),
+            refresh_token: Some(token),
+            scope: Some("write".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(oauth_err(server.token(&request)), OAuthError::InvalidScope(_)));
+
+        request.refresh_token = Some(login(&mut server, "web_app").refresh_token.unwrap());
+        request.scope = Some("read".to_string());
+        assert_eq!(server.token(&request).unwrap().scope, "read");
+    }
+
+    #[test]
+    fn test_refresh_bound_to_client() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let token = login(&mut server, "web_app").refresh_token.unwrap();
+
+        let request = TokenRequest {
+            grant_type: "refresh_token".to_string(),
+            client_id: Some("other_app".to_string()),
+            refresh_token: Some(token),
+            ..Default::default()
+        };
+        assert!(matches!(oauth_err(server.token(&request)), OAuthError::UnauthorizedClient(_)));
+    }
+
+    #[test]
+    fn test_manager_standalone() {
+        let mut manager = refresh_tokens(POLICY);
+        let scopes = vec!["read".to_string()];
+        let (token, record) = manager.issue("user@example.com", "web_app", &scopes, 0).unwrap();
+        assert_ne!(record.token_hash, token);
+        assert_eq!(manager.rotate(&token, Some("other"), 10), Err(AuthError::InvalidToken));
+
+        let (next, next_record) = manager.rotate(&token, Some("web_app"), 10).unwrap();
+        assert_eq!(next_record.family_id, record.family_id);
+        assert!(matches!(manager.rotate(&token, None, 20), Err(AuthError::RefreshTokenReused(_))));
+        assert_eq!(manager.rotate(&next, None, 20), Err(AuthError::InvalidToken));
+
+        let (token, _) = manager.issue("user@example.com", "web_app", &scopes, 0).unwrap();
+        manager.revoke_subject("user@example.com").unwrap();
+        assert_eq!(manager.rotate(&token, None, 10), Err(AuthError::InvalidToken));
+    }
+}
@@ -0,0 +1,242 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{self, oauth_err, secret_hash, user};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::{AuthError, OAuthError};
+    use auth_kit::oauth2::client::{Client, GrantType};
+    use auth_kit::oauth2::exchange::{ActorClaim, TokenExchangePolicy, ACCESS_TOKEN_TYPE};
+    use auth_kit::oauth2::introspection::IntrospectionRequest;
+    use auth_kit::oauth2::server::{
+        AuthorizationServer, ServerConfig, TokenRequest, TokenResponse,
+    };
+
+    fn service(client_id: &str) -> Client {
+        Client::new(client_id)
+            .with_secret_hash(&secret_hash(&format!("{}-secret", client_id)))
+            .with_grant_types(&[GrantType::ClientCredentials, GrantType::TokenExchange])
+            .with_scopes(&["service"])
+            .with_access_token_ttl(600)
+    }
+
+    fn server(clock: &ManualClock) -> AuthorizationServer {
+        let policy = TokenExchangePolicy::new()
+            .allow("orders_service", &["billing_service", "inventory-*"])
+            .allow("billing_service", &["ledger_service"])
+            .allow("support_console", &["billing_service"])
+            .allow_impersonation("support_console", &["orders_service"]);
+        let config = ServerConfig {
+            issuer: "https://auth.example.com".to_string(),
+            audience: "orders_service".to_string(),
+            ..ServerConfig::default()
+        };
+        let mut server = common::server(config, clock).with_exchange_policy(policy);
+
+        server
+            .register_client(
+                Client::new("web_app")
+                    .with_redirect_uri("https://app.example.com/callback")
+                    .with_scopes(&["orders:read", "orders:write", "billing:read"]),
+            )
+            .unwrap();
+        for client_id in ["orders_service", "billing_service", "support_console", "resource_server"] {
+            server.register_client(service(client_id)).unwrap();
+        }
+        server
+    }
+
+    fn user_token(server: &mut AuthorizationServer) -> TokenResponse {
+        common::login(server, "web_app", "orders:read orders:write billing:read", &user("alice@example.com"))
+    }
+
+    fn service_token(server: &mut AuthorizationServer, client_id: &str) -> String {
+        server
+            .token(&TokenRequest {
+                grant_type: "client_credentials".to_string(),
+                client_id: Some(client_id.to_string()),
+                client_secret: Some(format!("{}-secret", client_id)),
+                ..Default::default()
+            })
+            .unwrap()
+            .access_token
+    }
+
+    fn exchange(client_id: &str, subject_token: &str, actor_token: Option<&str>, audience: &str) -> TokenRequest {
+        TokenRequest {
+            grant_type: GrantType::TokenExchange.as_str().to_string(),
+            client_id: Some(client_id.to_string()),
+            client_secret: Some(format!("{}-secret", client_id)),
+            subject_token: Some(subject_token.to_string()),
+            subject_token_type: Some(ACCESS_TOKEN_TYPE.to_string()),
+            actor_token: actor_token.map(str::to_string),
+            actor_token_type: actor_token.map(|_| ACCESS_TOKEN_TYPE.to_string()),
+            audience: Some(audience.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn introspection(token: &str) -> IntrospectionRequest {
+        IntrospectionRequest {
+            token: token.to_string(),
+            client_id: Some("resource_server".to_string()),
+            client_secret: Some("resource_server-secret".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_delegation_records_actor_chain() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+        let orders = service_token(&mut server, "orders_service");
+
+        let response = server.token(&exchange("orders_service", &subject, Some(&orders), "billing_service")).unwrap();
+        assert_eq!(response.issued_token_type.as_deref(), Some(ACCESS_TOKEN_TYPE));
+        assert_eq!(response.token_type, "Bearer");
+
+        let claims = server.validate_access_token(&response.access_token).unwrap();
+        assert_eq!(claims.email, "alice@example.com");
+        assert_eq!(claims.service, "billing_service");
+
+        let billing = service_token(&mut server, "billing_service");
+        let onward = server
+            .token(&exchange("billing_service", &response.access_token, Some(&billing), "ledger_service"))
+            .unwrap();
+        let introspected = server.introspect(&introspection(&onward.access_token)).unwrap();
+        assert_eq!(introspected.sub.as_deref(), Some("alice@example.com"));
+        assert_eq!(introspected.aud, vec!["ledger_service"]);
+        assert!(introspected
+            .to_json()
+            .contains(r#""act":{"sub":"billing_service","act":{"sub":"orders_service"}}"#));
+        let act = introspected.act.unwrap();
+        assert_eq!(act, ActorClaim::new("billing_service", Some(ActorClaim::new("orders_service", None))));
+        assert_eq!(act.chain(), vec!["billing_service", "orders_service"]);
+    }
+
+    #[test]
+    fn test_impersonation_requires_policy() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+
+        let response = server.token(&exchange("support_console", &subject, None, "orders_service")).unwrap();
+        let introspected = server.introspect(&introspection(&response.access_token)).unwrap();
+        assert_eq!(introspected.sub.as_deref(), Some("alice@example.com"));
+        assert_eq!(introspected.act, None);
+
+        assert!(matches!(
+            oauth_err(server.token(&exchange("orders_service", &subject, None, "billing_service"))),
+            OAuthError::UnauthorizedClient(_)
+        ));
+    }
+
+    #[test]
+    fn test_impersonation_is_granted_per_audience() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+        let console = service_token(&mut server, "support_console");
+
+        assert!(server.token(&exchange("support_console", &subject, Some(&console), "billing_service")).is_ok());
+        assert!(matches!(
+            oauth_err(server.token(&exchange("support_console", &subject, None, "billing_service"))),
+            OAuthError::UnauthorizedClient(_)
+        ));
+
+        let policy = TokenExchangePolicy::new().allow("c", &["billing"]).allow_impersonation("c", &["users-*"]);
+        assert!(policy.allows_audience("c", "billing") && policy.allows_audience("c", "users-eu"));
+        assert!(!policy.allows_impersonation("c", "billing"));
+        assert!(policy.allows_impersonation("c", "users-eu"));
+    }
+
+    #[test]
+    fn test_audience_policy() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+        let orders = service_token(&mut server, "orders_service");
+
+        assert!(server.token(&exchange("orders_service", &subject, Some(&orders), "inventory-eu")).is_ok());
+        assert!(matches!(
+            oauth_err(server.token(&exchange("orders_service", &subject, Some(&orders), "ledger_service"))),
+            OAuthError::InvalidTarget(_)
+        ));
+
+        server.register_client(service("reporting")).unwrap();
+        let reporting = service_token(&mut server, "reporting");
+        assert!(matches!(
+            oauth_err(server.token(&exchange("reporting", &subject, Some(&reporting), "billing_service"))),
+            OAuthError::InvalidTarget(_)
+        ));
+
+        let mut no_audience = exchange("orders_service", &subject, Some(&orders), "billing_service");
+        no_audience.audience = None;
+        assert!(matches!(oauth_err(server.token(&no_audience)), OAuthError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn test_scopes_narrowed_to_subject_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+        let orders = service_token(&mut server, "orders_service");
+
+        let full = server.token(&exchange("orders_service", &subject, Some(&orders), "billing_service")).unwrap();
+        assert_eq!(full.scope, "orders:read orders:write billing:read");
+
+        let mut narrowed = exchange("orders_service", &subject, Some(&orders), "billing_service");
+        narrowed.scope = Some("billing:read".to_string());
+        assert_eq!(server.token(&narrowed).unwrap().scope, "billing:read");
+
+        narrowed.scope = Some("billing:read billing:write".to_string());
+        assert!(matches!(oauth_err(server.token(&narrowed)), OAuthError::InvalidScope(_)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_tokens_and_types() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server).access_token;
+        let orders = service_token(&mut server, "orders_service");
+
+        let unknown = exchange("orders_service", "not-a-token", Some(&orders), "billing_service");
+        assert!(matches!(oauth_err(server.token(&unknown)), OAuthError::InvalidGrant(_)));
+
+        let bad_actor = exchange("orders_service", &subject, Some("not-a-token"), "billing_service");
+        assert!(matches!(oauth_err(server.token(&bad_actor)), OAuthError::InvalidGrant(_)));
+
+        let mut id_token_type = exchange("orders_service", &subject, Some(&orders), "billing_service");
+        id_token_type.subject_token_type = Some("urn:ietf:params:oauth:token-type:id_token".to_string());
+        assert!(matches!(oauth_err(server.token(&id_token_type)), OAuthError::InvalidRequest(_)));
+
+        let mut public = exchange("web_app", &subject, None, "billing_service");
+        public.client_secret = None;
+        assert!(matches!(oauth_err(server.token(&public)), OAuthError::UnauthorizedClient(_)));
+
+        let mut wrong_secret = exchange("orders_service", &subject, Some(&orders), "billing_service");
+        wrong_secret.client_secret = Some("guess".to_string());
+        assert!(matches!(oauth_err(server.token(&wrong_secret)), OAuthError::InvalidClient(_)));
+    }
+
+    #[test]
+    fn test_expiry_capped_by_subject_token() {
+        let clock = ManualClock::new(1_000);
+        let mut server = server(&clock);
+        let subject = user_token(&mut server);
+        assert_eq!(subject.expires_in, 3_600);
+
+        clock.advance(3_300);
+        let orders = service_token(&mut server, "orders_service");
+        let response = server
+            .token(&exchange("orders_service", &subject.access_token, Some(&orders), "billing_service"))
+            .unwrap();
+        assert_eq!(response.expires_in, 300);
+
+        clock.advance(300);
+        assert_eq!(server.validate_access_token(&response.access_token), Err(AuthError::InvalidToken));
+        let expired = exchange("orders_service", &subject.access_token, Some(&orders), "billing_service");
+        assert!(matches!(oauth_err(server.token(&expired)), OAuthError::InvalidGrant(_)));
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use auth_kit::jose::{self, JwsAlgorithm, Jwks, SigningKey};
+    use auth_kit::model::{Role, User};
+    use auth_kit::oauth2::client::Client;
+    use auth_kit::oauth2::oidc::{at_hash, AuthenticationInfo, IdTokenClaims, OidcProvider};
+    use auth_kit::oauth2::pkce::{code_challenge, CodeChallengeMethod};
+    use auth_kit::oauth2::server::{
+        AuthorizationRequest, AuthorizationServer, ServerConfig, TokenRequest, TokenResponse,
+    };
+    use std::sync::Arc;
+
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    fn user() -> User {
+        User {
+            email: "alice@example.com".to_string(),
+            password_hash: "".to_string(),
+            role: Role { name: "editor".to_string(), permissions: vec![] },
+            department: "engineering".to_string(),
+            clearance_level: 2,
+            ..Default::default()
+        }
+    }
+
+    fn server(clock: &ManualClock, algorithm: JwsAlgorithm) -> AuthorizationServer {
+        let mut server = AuthorizationServer::new(ServerConfig {
+            issuer: "https://auth.example.com".to_string(),
+            ..ServerConfig::default()
+        })
+        .with_clock(Arc::new(clock.clone()))
+        .with_oidc(OidcProvider::new(SigningKey::generate(algorithm, "key-1")).with_id_token_ttl(600));
+        server
+            .register_client(
+                Client::new("web_app")
+                    .with_redirect_uri("https://app.example.com/cb")
+                    .with_scopes(&["openid", "profile", "email", "api:read"]),
+            )
+            .unwrap();
+        server
+    }
+
+    fn login(server: &mut AuthorizationServer, scope: &str, info: AuthenticationInfo) -> TokenResponse {
+        let response = server
+            .authorize_with(
+                &AuthorizationRequest {
+                    response_type: "code".to_string(),
+                    client_id: "web_app".to_string(),
+                    scope: Some(scope.to_string()),
+                    nonce: Some("n-0S6_WzA2Mj".to_string()),
+                    code_challenge: Some(code_challenge(VERIFIER, CodeChallengeMethod::S256)),
+                    code_challenge_method: Some("S256".to_string()),
+                    ..Default::default()
+                },
+                &user(),
+                info,
+            )
+            .unwrap();
+        server
+            .token(&TokenRequest {
+                grant_type: "authorization_code".to_string(),
+                code: Some(response.code),
+                client_id: Some("web_app".to_string()),
+                code_verifier: Some(VERIFIER.to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_id_token_claims() {
+        for algorithm in [JwsAlgorithm::ES256, JwsAlgorithm::EdDSA] {
+            let clock = ManualClock::new(10_000);
+            let mut server = server(&clock, algorithm);
+            let info = AuthenticationInfo {
+                auth_time: 9_990,
+                acr: Some("urn:example:loa:2".to_string()),
+                amr: vec!["pwd".to_string(), "otp".to_string()],
+            };
+            let tokens = login(&mut server, "openid email", info);
+
+            let claims: IdTokenClaims = jose::verify(&tokens.id_token.unwrap(), &server.jwks().unwrap()).unwrap();
+            assert_eq!(claims.iss, "https://auth.example.com");
+            assert_eq!(claims.sub, "alice@example.com");
+            assert_eq!(claims.aud, "web_app");
+            assert_eq!(claims.iat, 10_000);
+            assert_eq!(claims.exp, 10_600);
+            assert_eq!(claims.auth_time, Some(9_990));
+            assert_eq!(claims.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+            assert_eq!(claims.acr.as_deref(), Some("urn:example:loa:2"));
+            assert_eq!(claims.amr, vec!["pwd", "otp"]);
+            assert_eq!(claims.at_hash, Some(at_hash(&tokens.access_token, algorithm)));
+            assert_eq!(claims.email.as_deref(), Some("alice@example.com"));
+        }
+    }
+
+    #[test]
+    fn test_no_id_token_without_openid_scope() {
+        let clock = ManualClock::new(10_000);
+        let mut server = server(&clock, JwsAlgorithm::ES256);
+        let tokens = login(&mut server, "api:read", AuthenticationInfo::password(10_000));
+        assert!(tokens.id_token.is_none());
+    }
+
+    #[test]
+    fn test_at_hash_vector() {
+        assert_eq!(at_hash("jHkWEdUXMU1BwAsC4vtUsZwnNs", JwsAlgorithm::ES256), "W5SpNe0Cj-V_u4hyFtTNZw");
+        assert_eq!(at_hash("jHkWEdUXMU1BwAsC4vtUsZwnNs", JwsAlgorithm::EdDSA).len(), 43);
+    }
+
+    #[test]
+    fn test_tampered_or_foreign_id_token_rejected() {
+        let clock = ManualClock::new(10_000);
+        let mut server = server(&clock, JwsAlgorithm::ES256);
+        let id_token = login(&mut server, "openid", AuthenticationInfo::password(10_000)).id_token.unwrap();
+        let jwks = server.jwks().unwrap();
+
+        let mut parts: Vec<&str> = id_token.split('.').collect();
+        let attacker = SigningKey::generate(JwsAlgorithm::ES256, "key-1");
+        let forged = jose::sign(&IdTokenClaims { sub: "mallory".to_string(), ..Default::default() }, &attacker).unwrap();
+        let forged_payload = forged.split('.').nth(1).unwrap().to_string();
+        parts[1] = &forged_payload;
+        assert_eq!(jose::verify::<IdTokenClaims>(&parts.join("."), &jwks), Err(AuthError::InvalidToken));
+        assert_eq!(jose::verify::<IdTokenClaims>(&forged, &jwks), Err(AuthError::InvalidToken));
+        assert_eq!(jose::verify::<IdTokenClaims>(&id_token, &Jwks::default()), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_userinfo_filtered_by_scope() {
+        let clock = ManualClock::new(10_000);
+        let mut server = server(&clock, JwsAlgorithm::ES256);
+
+        let tokens = login(&mut server, "openid profile", AuthenticationInfo::password(10_000));
+        let info = server.userinfo(&tokens.access_token, &user()).unwrap();
+        assert_eq!(info.sub, "alice@example.com");
+        assert_eq!(info.email, None);
+        assert_eq!(info.role.as_deref(), Some("editor"));
+        assert_eq!(info.department.as_deref(), Some("engineering"));
+
+        let tokens = login(&mut server, "openid email", AuthenticationInfo::password(10_000));
+        assert_eq!(
+            server.userinfo(&tokens.access_token, &user()).unwrap().to_json(),
+            r#"{"sub":"alice@example.com","email":"alice@example.com"}"#
+        );
+
+        let mut other = user();
+        other.email = "bob@example.com".to_string();
+        assert_eq!(server.userinfo(&tokens.access_token, &other), Err(AuthError::InvalidToken));
+
+        let tokens = login(&mut server, "api:read", AuthenticationInfo::password(10_000));
+        assert!(matches!(server.userinfo(&tokens.access_token, &user()), Err(AuthError::ScopeNotGranted(_))));
+    }
+
+    #[test]
+    fn test_discovery_and_jwks() {
+        let clock = ManualClock::new(10_000);
+        let old = SigningKey::generate(JwsAlgorithm::EdDSA, "key-0");
+        let server = AuthorizationServer::new(ServerConfig {
+            issuer: "https://auth.example.com/".to_string(),
+            allow_plain_pkce: false,
+            ..ServerConfig::default()
+        })
+        .with_clock(Arc::new(clock.clone()))
+        .with_oidc(OidcProvider::new(SigningKey::generate(JwsAlgorithm::ES256, "key-1")).with_published_key(old));
+
+        let metadata = server.openid_configuration().unwrap();
+        assert_eq!(metadata.token_endpoint, "https://auth.example.com/token");
+        assert_eq!(metadata.jwks_uri, "https://auth.example.com/.well-known/jwks.json");
+        assert_eq!(metadata.id_token_signing_alg_values_supported, vec!["ES256"]);
+        assert_eq!(metadata.code_challenge_methods_supported, vec!["S256"]);
+        assert!(metadata.to_json().contains(r#""response_types_supported":["code"]"#));
+
+        let jwks = Jwks::from_json(&server.jwks().unwrap().to_json()).unwrap();
+        assert_eq!(jwks.keys.len(), 2);
+        assert_eq!(jwks.find("key-1").unwrap().crv, "P-256");
+        assert_eq!(jwks.find("key-0").unwrap().kty, "OKP");
+
+        assert!(AuthorizationServer::new(ServerConfig::default()).openid_configuration().is_none());
+    }
+}
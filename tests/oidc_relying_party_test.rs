@@ -0,0 +1,319 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::clock::{Clock, ManualClock};
+    use auth_kit::error::AuthError;
+    use auth_kit::jose::{self, JwsAlgorithm, Jwks, SigningKey};
+    use auth_kit::model::{AccountStatus, Permission, Role, User};
+    use auth_kit::oauth2::client::Client;
+    use auth_kit::oauth2::oidc::OidcProvider;
+    use auth_kit::oauth2::relying_party::{RelyingParty, RelyingPartyConfig, RoleMapping};
+    use auth_kit::oauth2::server::{AuthorizationRequest, AuthorizationServer, ServerConfig, TokenRequest};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    const ISSUER: &str = "https://idp.example.com/realms/main";
+
+    fn config() -> RelyingPartyConfig {
+        RelyingPartyConfig {
+            issuer: ISSUER.to_string(),
+            authorization_endpoint: format!("{}/authorize", ISSUER),
+            client_id: "portal".to_string(),
+            redirect_uri: "https://portal.example.com/callback".to_string(),
+            ..RelyingPartyConfig::default()
+        }
+    }
+
+    fn role(name: &str, permissions: Vec<Permission>) -> Role {
+        Role { name: name.to_string(), permissions }
+    }
+
+    fn mapping() -> RoleMapping {
+        RoleMapping::new()
+            .with_group("idp-admins", role("admin", vec![Permission::Create, Permission::Delete]))
+            .with_group("idp-staff", role("staff", vec![Permission::Read]))
+    }
+
+    fn query_param(url: &str, name: &str) -> Option<String> {
+        let query = url.split_once('?')?.1;
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| percent_decode(value))
+        })
+    }
+
+    fn percent_decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                out.push(u8::from_str_radix(&value[i + 1..i + 3], 16).unwrap());
+                i += 3;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    struct StubIdp {
+        key: SigningKey,
+        clock: ManualClock,
+    }
+
+    impl StubIdp {
+        fn new() -> Self {
+            Self {
+                key: SigningKey::generate(JwsAlgorithm::ES256, "stub-1"),
+                clock: ManualClock::new(50_000),
+            }
+        }
+
+        fn jwks(&self) -> Jwks {
+            Jwks { keys: vec![self.key.public_jwk()] }
+        }
+
+        fn relying_party(&self) -> RelyingParty {
+            RelyingParty::new(config(), self.jwks())
+                .with_role_mapping(mapping())
+                .with_clock(Arc::new(self.clock.clone()))
+        }
+
+        fn id_token(&self, nonce: &str, extra: serde_json::Value) -> String {
+            let now = self.clock.now();
+            let mut claims = json!({
+                "iss": ISSUER,
+                "sub": "f:1234",
+                "aud": "portal",
+                "exp": now + 300,
+                "iat": now,
+                "nonce": nonce,
+                "email": "carol@example.com",
+                "email_verified": true,
+                "groups": ["idp-staff", "everyone"],
+            });
+            for (key, value) in extra.as_object().unwrap() {
+                claims[key] = value.clone();
+            }
+            jose::sign(&claims, &self.key).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_authorization_url_and_state() {
+        let idp = StubIdp::new();
+        let mut rp = idp.relying_party();
+        let redirect = rp.begin_login();
+
+        assert!(redirect.url.starts_with("https://idp.example.com/realms/main/authorize?response_type=code&"));
+        assert_eq!(query_param(&redirect.url, "client_id").as_deref(), Some("portal"));
+        assert_eq!(query_param(&redirect.url, "scope").as_deref(), Some("openid email profile"));
+        assert_eq!(query_param(&redirect.url, "state"), Some(redirect.state.clone()));
+        assert_eq!(query_param(&redirect.url, "code_challenge_method").as_deref(), Some("S256"));
+
+        let login = rp.complete_login(&redirect.state).unwrap();
+        assert_eq!(query_param(&redirect.url, "nonce"), Some(login.nonce));
+        assert_eq!(rp.complete_login(&redirect.state), Err(AuthError::InvalidToken));
+
+        let expired = rp.begin_login();
+        idp.clock.advance(600);
+        assert_eq!(rp.complete_login(&expired.state), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_validates_id_token_claims() {
+        let idp = StubIdp::new();
+        let mut rp = idp.relying_party();
+        let state = rp.begin_login().state;
+        let nonce = rp.complete_login(&state).unwrap().nonce;
+
+        let identity = rp.validate_id_token(&idp.id_token(&nonce, json!({})), &nonce).unwrap();
+        assert_eq!(identity.sub, "f:1234");
+        assert_eq!(identity.email.as_deref(), Some("carol@example.com"));
+        assert_eq!(identity.groups, vec!["idp-staff", "everyone"]);
+
+        let rejected = |extra: serde_json::Value, expected: &str| {
+            let result = rp.validate_id_token(&idp.id_token(&nonce, extra), &nonce);
+            assert_eq!(result, Err(AuthError::InvalidIdToken(expected.to_string())));
+        };
+        rejected(json!({ "iss": "https://evil.example.com" }), "unexpected issuer");
+        rejected(json!({ "aud": "other_app" }), "token is not intended for this client");
+        rejected(json!({ "aud": ["portal", "other_app"] }), "authorized party does not match this client");
+        rejected(json!({ "nonce": "replayed" }), "nonce does not match");
+        rejected(json!({ "exp": 49_000 }), "token has expired");
+        rejected(json!({ "iat": 60_000 }), "token was issued in the future");
+        rejected(json!({ "email_verified": false }), "email is not verified");
+        rejected(json!({ "email_verified": null }), "email is not verified");
+
+        let multi = idp.id_token(&nonce, json!({ "aud": ["portal", "other_app"], "azp": "portal" }));
+        assert!(rp.validate_id_token(&multi, &nonce).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_untrusted_signature() {
+        let idp = StubIdp::new();
+        let mut rp = idp.relying_party();
+        let token = idp.id_token("n", json!({}));
+        assert!(rp.validate_id_token(&token, "n").is_ok());
+
+        rp.set_jwks(StubIdp::new().jwks());
+        assert_eq!(
+            rp.validate_id_token(&token, "n"),
+            Err(AuthError::InvalidIdToken("signature verification failed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_group_mapping_and_provisioning() {
+        let idp = StubIdp::new();
+        let rp = idp.relying_party();
+        let mut auth = Authentication::new();
+
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({})), "n").unwrap();
+        let user = rp.provision(&mut auth, &identity).unwrap();
+        assert_eq!(user.email, "carol@example.com");
+        assert_eq!(user.role.name, "staff");
+        assert_eq!(auth.login("carol@example.com").unwrap().unwrap().role.name, "staff");
+
+        let promoted = idp.id_token("n", json!({ "groups": ["idp-staff", "idp-admins"] }));
+        let identity = rp.validate_id_token(&promoted, "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap().role.name, "admin");
+
+        let unmapped = rp.validate_id_token(&idp.id_token("n", json!({ "groups": [] })), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &unmapped).unwrap().role.name, "admin");
+        assert_eq!(auth.users.len(), 1);
+
+        let no_email = rp.validate_id_token(&idp.id_token("n", json!({ "email": null })), "n").unwrap();
+        assert!(matches!(rp.map_user(&no_email), Err(AuthError::InvalidIdToken(_))));
+
+        let guest = RoleMapping::new().with_default(role("guest", vec![]));
+        assert_eq!(guest.resolve(&["anything".to_string()]).unwrap().name, "guest");
+    }
+
+    #[test]
+    fn test_provisioning_never_takes_over_local_accounts() {
+        let idp = StubIdp::new();
+        let rp = idp.relying_party();
+        let mut auth = Authentication::new();
+        auth.register("carol@example.com", "local_hash").unwrap();
+
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({ "groups": ["idp-admins"] })), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap_err(), AuthError::AccountLinkRequired);
+        assert_eq!(auth.users["carol@example.com"].role.name, "");
+
+        let mut auth = Authentication::new();
+        rp.provision(&mut auth, &identity).unwrap();
+        auth.disable("carol@example.com", None).unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap_err(), AuthError::AccountDisabled);
+        auth.delete("carol@example.com", None).unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap_err(), AuthError::AccountDeleted);
+    }
+
+    #[test]
+    fn test_provisioning_matches_issuer_and_subject() {
+        let idp = StubIdp::new();
+        let rp = idp.relying_party();
+        let mut auth = Authentication::new();
+
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({})), "n").unwrap();
+        let linked = rp.provision(&mut auth, &identity).unwrap().federated_identity.unwrap();
+        assert_eq!((linked.issuer.as_str(), linked.subject.as_str()), (ISSUER, "f:1234"));
+
+        let reassigned = rp.validate_id_token(&idp.id_token("n", json!({ "sub": "f:9999" })), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &reassigned).unwrap_err(), AuthError::AccountLinkRequired);
+
+        let renamed = idp.id_token("n", json!({ "email": "carol.jones@example.com" }));
+        let renamed = rp.validate_id_token(&renamed, "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &renamed).unwrap_err(), AuthError::AccountLinkRequired);
+        assert_eq!(auth.users.len(), 1);
+        assert!(rp.provision(&mut auth, &identity).is_ok());
+    }
+
+    #[test]
+    fn test_provisioned_users_skip_activation() {
+        let idp = StubIdp::new();
+        let rp = idp.relying_party();
+        let mut auth = Authentication::new().with_activation_required(true);
+
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({})), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap().status, AccountStatus::Active);
+        assert!(auth.login("carol@example.com").is_ok());
+    }
+
+    #[test]
+    fn test_provisioning_records_verified_email() {
+        let idp = StubIdp::new();
+        let rp = idp.relying_party();
+        let mut auth = Authentication::new();
+        auth.register("carol@example.com", "").unwrap();
+        assert!(!auth.users["carol@example.com"].is_email_verified());
+
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({})), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap().email_verified_at, Some(50_000));
+        idp.clock.advance(100);
+        let identity = rp.validate_id_token(&idp.id_token("n", json!({})), "n").unwrap();
+        assert_eq!(rp.provision(&mut auth, &identity).unwrap().email_verified_at, Some(50_000));
+    }
+
+    #[test]
+    fn test_round_trip_with_auth_kit_provider() {
+        let clock = ManualClock::new(50_000);
+        let mut idp = AuthorizationServer::new(ServerConfig { issuer: ISSUER.to_string(), ..ServerConfig::default() })
+            .with_clock(Arc::new(clock.clone()))
+            .with_oidc(OidcProvider::new(SigningKey::generate(JwsAlgorithm::EdDSA, "idp-1")));
+        idp.register_client(
+            Client::new("portal")
+                .with_redirect_uri("https://portal.example.com/callback")
+                .with_scopes(&["openid", "email", "profile"]),
+        )
+        .unwrap();
+
+        let mut rp = RelyingParty::new(config(), idp.jwks().unwrap()).with_clock(Arc::new(clock.clone()));
+        let redirect = rp.begin_login();
+
+        let param = |name: &str| query_param(&redirect.url, name);
+        let user = User {
+            email: "dave@example.com".to_string(),
+            password_hash: "".to_string(),
+            role: role("", vec![]),
+            department: "".to_string(),
+            clearance_level: 0,
+            email_verified_at: Some(40_000),
+            ..Default::default()
+        };
+        let response = idp
+            .authorize(
+                &AuthorizationRequest {
+                    response_type: param("response_type").unwrap(),
+                    client_id: param("client_id").unwrap(),
+                    redirect_uri: param("redirect_uri"),
+                    scope: param("scope"),
+                    state: param("state"),
+                    code_challenge: param("code_challenge"),
+                    code_challenge_method: param("code_challenge_method"),
+                    nonce: param("nonce"),
+                },
+                &user,
+            )
+            .unwrap();
+
+        let login = rp.complete_login(response.state.as_deref().unwrap()).unwrap();
+        let tokens = idp
+            .token(&TokenRequest {
+                grant_type: "authorization_code".to_string(),
+                code: Some(response.code),
+                redirect_uri: Some(rp.config().redirect_uri.clone()),
+                client_id: Some("portal".to_string()),
+                code_verifier: Some(login.code_verifier.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let identity = rp.validate_id_token(&tokens.id_token.unwrap(), &login.nonce).unwrap();
+        assert_eq!(identity.sub, "dave@example.com");
+        assert_eq!(identity.email.as_deref(), Some("dave@example.com"));
+        assert_eq!(identity.email_verified, Some(true));
+    }
+}
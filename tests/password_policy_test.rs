@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::{verify_password, Authentication};
+    use auth_kit::auth::password::{email_inputs, estimate_entropy, PasswordPolicy};
+    use auth_kit::auth::reset::{InMemoryResetTokenStore, PasswordResets, ResetPolicy};
+    use auth_kit::error::{AuthError, PasswordViolation};
+
+    const EMAIL: &str = "alice.smith@example.com";
+    const STRONG: &str = "correct horse battery staple";
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy { history_size: 2, cost: 4, ..PasswordPolicy::default() }
+    }
+
+    #[test]
+    fn test_violations_are_structured() {
+        let policy = PasswordPolicy { require_uppercase: true, require_digit: true, require_symbol: true, ..policy() };
+        assert_eq!(
+            policy.violations("short", &[]),
+            vec![
+                PasswordViolation::TooShort { min: 10, found: 5 },
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSymbol,
+                PasswordViolation::TooWeak { bits: estimate_entropy("short", &[]) as u32, min_bits: 40 },
+            ]
+        );
+        assert_eq!(policy.violations("Correct-horse-battery-staple-9", &[]), vec![]);
+        assert!(matches!(policy.check(&"x".repeat(80), &[]), Err(AuthError::PasswordRejected(v)) if v[0] == PasswordViolation::TooLong { max: 72, found: 80 }));
+        let cyrillic = "пароль конь батарея скрепка сорок слонов";
+        assert_eq!(cyrillic.chars().count(), 40);
+        assert!(policy.violations(cyrillic, &[]).contains(&PasswordViolation::TooLong { max: 72, found: 75 }));
+    }
+
+    #[test]
+    fn test_entropy_estimate() {
+        assert!(estimate_entropy("password123", &[]) < 15.0);
+        assert!(estimate_entropy("P@ssw0rd", &[]) < 15.0);
+        assert!(estimate_entropy("qwertyuiop", &[]) < 15.0);
+        assert!(estimate_entropy("aaaaaaaaaaaa", &[]) < 15.0);
+        assert!(estimate_entropy(STRONG, &[]) > 60.0);
+        assert!(estimate_entropy("acme2024!", &["acme"]) < estimate_entropy("acme2024!", &[]));
+        assert!(policy().violations("password1234", &[]).iter().any(|v| matches!(v, PasswordViolation::TooWeak { .. })));
+    }
+
+    #[test]
+    fn test_banned_words_and_email_parts() {
+        let policy = PasswordPolicy { banned_words: vec!["acme".to_string()], ..policy() };
+        assert_eq!(email_inputs(EMAIL), vec!["alice", "smith"]);
+
+        let violations = policy.violations("my 4cme horse battery", &email_inputs(EMAIL));
+        assert_eq!(violations, vec![PasswordViolation::BannedWord("acme".to_string())]);
+        let violations = policy.violations("Smith horse battery staple", &email_inputs(EMAIL));
+        assert_eq!(violations, vec![PasswordViolation::BannedWord("smith".to_string())]);
+    }
+
+    #[test]
+    fn test_register_with_password() {
+        let mut auth = Authentication::new().with_password_policy(policy());
+        assert!(matches!(
+            auth.register_with_password(EMAIL, "alice12345"),
+            Err(AuthError::PasswordRejected(v)) if v.contains(&PasswordViolation::BannedWord("alice".to_string()))
+        ));
+        assert!(!auth.users.contains_key(EMAIL));
+
+        auth.register_with_password(EMAIL, STRONG).unwrap();
+        assert!(verify_password(STRONG, &auth.users[EMAIL].password_hash).unwrap());
+        assert_eq!(auth.register_with_password(EMAIL, STRONG), Err(AuthError::EmailAlreadyRegistered));
+    }
+
+    #[test]
+    fn test_recent_passwords_cannot_be_reused() {
+        let mut auth = Authentication::new().with_password_policy(policy());
+        auth.register_with_password(EMAIL, STRONG).unwrap();
+        let reused = Err(AuthError::PasswordRejected(vec![PasswordViolation::Reused { history: 2 }]));
+
+        assert_eq!(auth.set_password(EMAIL, STRONG), reused);
+        auth.set_password(EMAIL, "purple monkey dishwasher").unwrap();
+        auth.set_password(EMAIL, "lunar tractor violin").unwrap();
+        assert_eq!(auth.users[EMAIL].password_history.len(), 2);
+        assert_eq!(auth.set_password(EMAIL, STRONG), reused);
+
+        auth.set_password(EMAIL, "glacier pencil orbit").unwrap();
+        auth.set_password(EMAIL, STRONG).unwrap();
+        assert_eq!(auth.set_password("bob@example.com", STRONG), Err(AuthError::UserNotFound));
+    }
+
+    #[test]
+    fn test_reset_with_password() {
+        let mut auth = Authentication::new().with_password_policy(policy());
+        auth.register_with_password(EMAIL, STRONG).unwrap();
+        let mut resets = PasswordResets::new(Box::new(InMemoryResetTokenStore::new()), ResetPolicy::default());
+        let token = resets.issue(&auth, EMAIL).unwrap().unwrap();
+
+        assert!(matches!(resets.reset_with_password(&mut auth, &token, "short", None), Err(AuthError::PasswordRejected(_))));
+        assert_eq!(
+            resets.reset_with_password(&mut auth, &token, STRONG, None),
+            Err(AuthError::PasswordRejected(vec![PasswordViolation::Reused { history: 2 }]))
+        );
+        assert_eq!(resets.reset_with_password(&mut auth, &token, "purple monkey dishwasher", None).unwrap(), EMAIL);
+        assert!(verify_password("purple monkey dishwasher", &auth.users[EMAIL].password_hash).unwrap());
+        assert_eq!(auth.users[EMAIL].password_history.len(), 1);
+        assert_eq!(resets.reset_with_password(&mut auth, &token, "lunar tractor violin", None), Err(AuthError::InvalidToken));
+    }
+}
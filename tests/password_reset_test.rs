@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::auth::reset::{InMemoryResetTokenStore, PasswordResets, ResetPolicy};
+    use auth_kit::auth::session::{AuthLevel, ClientInfo, InMemorySessionStore, SessionManager, SessionPolicy};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn setup(policy: ResetPolicy) -> (Authentication, PasswordResets, ManualClock) {
+        let clock = ManualClock::new(1_000);
+        let mut auth = Authentication::new();
+        auth.register(EMAIL, "old_hash").unwrap();
+        let resets = PasswordResets::new(Box::new(InMemoryResetTokenStore::new()), policy).with_clock(Arc::new(clock.clone()));
+        (auth, resets, clock)
+    }
+
+    #[test]
+    fn test_reset_with_issued_token() {
+        let (mut auth, mut resets, _) = setup(ResetPolicy::default());
+        let token = resets.issue(&auth, EMAIL).unwrap().unwrap();
+        assert_eq!(token.len(), 43);
+        assert_eq!(resets.verify(&auth, &token).unwrap(), EMAIL);
+
+        assert_eq!(resets.reset(&mut auth, &token, "new_hash", None).unwrap(), EMAIL);
+        assert_eq!(auth.users[EMAIL].password_hash, "new_hash");
+        assert_eq!(resets.reset(&mut auth, &token, "other_hash", None), Err(AuthError::InvalidToken));
+        assert_eq!(auth.users[EMAIL].password_hash, "new_hash");
+    }
+
+    #[test]
+    fn test_tokens_expire_and_are_superseded() {
+        let (mut auth, mut resets, clock) = setup(ResetPolicy::default());
+        let first = resets.issue(&auth, EMAIL).unwrap().unwrap();
+        let second = resets.issue(&auth, EMAIL).unwrap().unwrap();
+        assert_eq!(resets.verify(&auth, &first), Err(AuthError::InvalidToken));
+        assert_eq!(resets.verify(&auth, &second).unwrap(), EMAIL);
+
+        clock.advance(15 * 60);
+        assert_eq!(resets.reset(&mut auth, &second, "new_hash", None), Err(AuthError::InvalidToken));
+        assert_eq!(resets.verify(&auth, "made-up-token"), Err(AuthError::InvalidToken));
+        assert_eq!(auth.users[EMAIL].password_hash, "old_hash");
+        assert_eq!(resets.issue(&auth, "bob@example.com"), Ok(None));
+    }
+
+    #[test]
+    fn test_token_is_bound_to_the_current_password() {
+        let (mut auth, mut resets, _) = setup(ResetPolicy::default());
+        let token = resets.issue(&auth, EMAIL).unwrap().unwrap();
+        auth.reset_password(EMAIL, "custom", "changed_hash", |t| t == "custom").unwrap();
+        assert_eq!(resets.reset(&mut auth, &token, "new_hash", None), Err(AuthError::InvalidToken));
+        assert_eq!(auth.users[EMAIL].password_hash, "changed_hash");
+
+        auth.disable(EMAIL, None).unwrap();
+        assert_eq!(resets.issue(&auth, EMAIL), Ok(None));
+    }
+
+    #[test]
+    fn test_reset_revokes_sessions_when_configured() {
+        for revoke_sessions in [true, false] {
+            let (mut auth, mut resets, _) = setup(ResetPolicy { revoke_sessions, ..ResetPolicy::default() });
+            let mut sessions = SessionManager::new(Box::new(InMemorySessionStore::new()), SessionPolicy::default());
+            let user = auth.login(EMAIL).unwrap().unwrap();
+            let (session_id, _) = sessions.create(&user, ClientInfo::default(), AuthLevel::SingleFactor).unwrap();
+
+            let token = resets.issue(&auth, EMAIL).unwrap().unwrap();
+            resets.reset(&mut auth, &token, "new_hash", Some(&mut sessions)).unwrap();
+            assert_eq!(sessions.validate(&session_id).is_err(), revoke_sessions);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::auth::passwordless::{Passwordless, PasswordlessMethod, PasswordlessPolicy};
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use auth_kit::mail::InMemoryMailer;
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+    const BROWSER: &str = "browser-cookie-1";
+
+    fn setup() -> (Authentication, Passwordless, InMemoryMailer, ManualClock) {
+        let (mailer, clock) = (InMemoryMailer::new(), ManualClock::new(1_000));
+        let mut auth = Authentication::new();
+        auth.register(EMAIL, "hash").unwrap();
+        let policy = PasswordlessPolicy { login_url: "https://app.example.com/magic?token={token}".to_string(), max_attempts: 3, ..PasswordlessPolicy::default() };
+        let passwordless = Passwordless::new(Box::new(mailer.clone()), policy).with_clock(Arc::new(clock.clone()));
+        (auth, passwordless, mailer, clock)
+    }
+
+    fn link_token(mailer: &InMemoryMailer) -> String {
+        let body = mailer.last_to(EMAIL).unwrap().body;
+        let start = body.find("token=").unwrap() + "token=".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    fn code(mailer: &InMemoryMailer) -> String {
+        let body = mailer.last_to(EMAIL).unwrap().body;
+        body.split_whitespace().find(|w| w.len() == 6 && w.bytes().all(|b| b.is_ascii_digit())).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_magic_link_login() {
+        let (auth, mut passwordless, mailer, _) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::MagicLink, BROWSER).unwrap();
+        assert_eq!(mailer.last_to(EMAIL).unwrap().subject, "Your sign-in link");
+        let token = link_token(&mailer);
+
+        assert_eq!(passwordless.complete_link(&auth, &token, "other-browser").unwrap_err(), AuthError::LoginBindingMismatch);
+        assert_eq!(passwordless.complete_link(&auth, &token, BROWSER).unwrap().email, EMAIL);
+        assert_eq!(passwordless.complete_link(&auth, &token, BROWSER).unwrap_err(), AuthError::InvalidToken);
+    }
+
+    #[test]
+    fn test_email_code_login() {
+        let (auth, mut passwordless, mailer, _) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        let code = code(&mailer);
+
+        assert_eq!(passwordless.complete_link(&auth, &code, BROWSER).unwrap_err(), AuthError::InvalidToken);
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, &format!(" {} ", code), BROWSER).unwrap().email, EMAIL);
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, &code, BROWSER).unwrap_err(), AuthError::InvalidToken);
+    }
+
+    #[test]
+    fn test_attempts_are_limited() {
+        let (auth, mut passwordless, mailer, _) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        let code = code(&mailer);
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        for _ in 0..3 {
+            assert_eq!(passwordless.complete_code(&auth, EMAIL, wrong, BROWSER).unwrap_err(), AuthError::InvalidOtp);
+        }
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, &code, BROWSER).unwrap_err(), AuthError::InvalidToken);
+    }
+
+    #[test]
+    fn test_binding_mismatches_do_not_cancel_the_login() {
+        let (auth, mut passwordless, mailer, _) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        let code = code(&mailer);
+
+        for _ in 0..10 {
+            assert_eq!(passwordless.complete_code(&auth, EMAIL, "123456", "attacker").unwrap_err(), AuthError::LoginBindingMismatch);
+        }
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, &code, BROWSER).unwrap().email, EMAIL);
+    }
+
+    #[test]
+    fn test_expiry_and_replacement() {
+        let (auth, mut passwordless, mailer, clock) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::MagicLink, BROWSER).unwrap();
+        let first = link_token(&mailer);
+        clock.advance(60);
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::MagicLink, BROWSER).unwrap();
+        assert_eq!(passwordless.complete_link(&auth, &first, BROWSER).unwrap_err(), AuthError::InvalidToken);
+
+        clock.advance(15 * 60);
+        assert_eq!(passwordless.complete_link(&auth, &link_token(&mailer), BROWSER).unwrap_err(), AuthError::InvalidToken);
+    }
+
+    #[test]
+    fn test_login_emails_are_rate_limited() {
+        let (auth, mut passwordless, mailer, clock) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        assert_eq!(
+            passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, "attacker"),
+            Err(AuthError::TooManyAttempts { retry_at: 1_060 })
+        );
+
+        for _ in 0..4 {
+            clock.advance(60);
+            passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, "attacker").unwrap();
+        }
+        clock.advance(60);
+        assert_eq!(
+            passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER),
+            Err(AuthError::TooManyAttempts { retry_at: 1_000 + 3600 })
+        );
+        assert_eq!(mailer.sent().len(), 5);
+
+        clock.set(1_000 + 3600);
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, &code(&mailer), BROWSER).unwrap().email, EMAIL);
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::MagicLink, BROWSER).unwrap();
+    }
+
+    #[test]
+    fn test_inactive_accounts_cannot_use_passwordless_login() {
+        let (mut auth, mut passwordless, mailer, clock) = setup();
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::MagicLink, BROWSER).unwrap();
+        auth.disable(EMAIL, None).unwrap();
+        assert_eq!(passwordless.complete_link(&auth, &link_token(&mailer), BROWSER).unwrap_err(), AuthError::AccountDisabled);
+
+        clock.advance(60);
+        passwordless.start(&auth, EMAIL, PasswordlessMethod::EmailCode, BROWSER).unwrap();
+        assert_eq!(passwordless.complete_code(&auth, EMAIL, "000000", BROWSER).unwrap_err(), AuthError::InvalidToken);
+        assert_eq!(mailer.sent().len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_addresses_look_like_known_ones() {
+        let (mut auth, mut passwordless, mailer, clock) = setup();
+        auth.register("carol@example.com", "hash").unwrap();
+        auth.lock("carol@example.com", None).unwrap();
+
+        for _ in 0..2 {
+            let results: Vec<_> = [EMAIL, "bob@example.com", "carol@example.com"]
+                .iter()
+                .map(|email| passwordless.start(&auth, email, PasswordlessMethod::EmailCode, BROWSER))
+                .collect();
+            assert!(results.windows(2).all(|pair| pair[0] == pair[1]), "{:?}", results);
+            clock.advance(30);
+        }
+        let recipients: Vec<_> = mailer.sent().into_iter().map(|email| email.to).collect();
+        assert_eq!(recipients, vec![EMAIL]);
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::error::AuthError;
+    use auth_kit::mfa::recovery::{
+        generate_recovery_codes, normalize_recovery_code, remaining_recovery_codes, RecoveryCodePolicy,
+        RECOVERY_CODE_ALPHABET,
+    };
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn policy() -> RecoveryCodePolicy {
+        RecoveryCodePolicy { count: 4, cost: 4, ..RecoveryCodePolicy::default() }
+    }
+
+    fn auth() -> Authentication {
+        let mut auth = Authentication::new();
+        auth.register(EMAIL, "hash").unwrap();
+        auth
+    }
+
+    #[test]
+    fn test_generated_codes_are_hashed() {
+        let (codes, hashed) = generate_recovery_codes(&policy()).unwrap();
+        assert_eq!(codes.len(), 4);
+        assert_eq!(hashed.len(), 4);
+        for (code, stored) in codes.iter().zip(&hashed) {
+            assert_eq!(code.len(), 11);
+            assert_eq!(&code[5..6], "-");
+            assert!(normalize_recovery_code(code).bytes().all(|b| RECOVERY_CODE_ALPHABET.contains(&b)));
+            assert!(stored.hash.starts_with("$2"));
+            assert!(!stored.hash.contains(&normalize_recovery_code(code)));
+            assert!(!stored.used);
+        }
+        assert_eq!(normalize_recovery_code(" ABCDE-fghjk "), "abcdefghjk");
+    }
+
+    #[test]
+    fn test_codes_are_single_use() {
+        let mut auth = auth();
+        let codes = auth.regenerate_recovery_codes(EMAIL, &policy()).unwrap();
+
+        auth.consume_recovery_code(EMAIL, &codes[1].to_uppercase()).unwrap();
+        assert_eq!(auth.consume_recovery_code(EMAIL, &codes[1]), Err(AuthError::RecoveryCodeUsed));
+        assert_eq!(auth.consume_recovery_code(EMAIL, "aaaaa-aaaaa"), Err(AuthError::InvalidRecoveryCode));
+        assert_eq!(auth.consume_recovery_code(EMAIL, "-"), Err(AuthError::InvalidRecoveryCode));
+        assert_eq!(remaining_recovery_codes(&auth.users[EMAIL].recovery_codes), 3);
+        assert_eq!(auth.consume_recovery_code("bob@example.com", &codes[0]), Err(AuthError::UserNotFound));
+    }
+
+    #[test]
+    fn test_regenerate_invalidates_old_batch() {
+        let mut auth = auth();
+        let old = auth.regenerate_recovery_codes(EMAIL, &policy()).unwrap();
+        auth.consume_recovery_code(EMAIL, &old[0]).unwrap();
+
+        let new = auth.regenerate_recovery_codes(EMAIL, &policy()).unwrap();
+        assert_eq!(remaining_recovery_codes(&auth.users[EMAIL].recovery_codes), 4);
+        assert_eq!(auth.consume_recovery_code(EMAIL, &old[1]), Err(AuthError::InvalidRecoveryCode));
+        auth.consume_recovery_code(EMAIL, &new[0]).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_z::Authorization;
+    use auth_kit::auth::scope_graph::ScopeGraph;
+    use auth_kit::error::AuthError;
+    use auth_kit::model::{AuthContext, Claims};
+
+    fn sample_graph() -> ScopeGraph {
+        let mut graph = ScopeGraph::new();
+        graph.add_implication("admin", "write").unwrap();
+        graph.add_implication("write", "read").unwrap();
+        graph.add_implication("repo", "repo:status").unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_expand_is_transitive() {
+        let graph = sample_graph();
+        assert_eq!(graph.expand(["admin"]), vec!["admin", "write", "read"]);
+        assert_eq!(graph.expand(["repo", "read"]), vec!["repo", "read", "repo:status"]);
+        assert_eq!(graph.expand(["unknown"]), vec!["unknown"]);
+    }
+
+    #[test]
+    fn test_implies() {
+        let graph = sample_graph();
+        assert!(graph.implies("admin", "read"));
+        assert!(graph.implies("read", "read"));
+        assert!(!graph.implies("read", "admin"));
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let mut graph = sample_graph();
+        assert_eq!(
+            graph.add_implication("read", "admin"),
+            Err(AuthError::ScopeImplicationCycle("admin -> write -> read -> admin".to_string()))
+        );
+        assert_eq!(
+            graph.add_implication("self", "self"),
+            Err(AuthError::ScopeImplicationCycle("self -> self".to_string()))
+        );
+        assert!(!graph.implies("read", "admin"));
+    }
+
+    #[test]
+    fn test_minimal_cover() {
+        let graph = sample_graph();
+        assert_eq!(graph.minimal_cover(["read", "write", "repo:status"]), vec!["write", "repo:status"]);
+        assert_eq!(graph.minimal_cover(["read", "admin", "repo", "repo:status"]), vec!["admin", "repo"]);
+        assert_eq!(graph.minimal_cover(["read", "read"]), vec!["read"]);
+    }
+
+    #[test]
+    fn test_sba_uses_scope_graph() {
+        let mut graph = ScopeGraph::new();
+        graph.add_implication("admin_service.admin", "admin_service.create").unwrap();
+        graph.add_implication("admin_service.create", "admin_service.read").unwrap();
+
+        let context = AuthContext {
+            user: None,
+            claims: Some(Claims {
+                email: "jwt@example.com".to_string(),
+                service: "admin_service".to_string(),
+                scopes: vec!["admin_service.admin".to_string()],
+                ..Default::default()
+            }),
+            resource: None,
+        };
+
+        let mut plain = Authorization::new("SBA").unwrap();
+        assert!(plain.authorize(&context, "admin_service", "read", None).is_err());
+
+        let mut expanded = Authorization::new("SBA").unwrap().with_scope_graph(graph);
+        assert!(expanded.authorize(&context, "admin_service", "read", None).is_ok());
+        assert!(expanded.authorize(&context, "admin_service", "delete", None).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::scope::{authorize_with_matcher, FlexibleMatcher, GlobMatcher};
+    use auth_kit::auth::scope_set::ScopeSet;
+    use auth_kit::error::AuthError;
+    use proptest::prelude::*;
+
+    fn set(scopes: &str) -> ScopeSet {
+        ScopeSet::from_scope_string(scopes)
+    }
+
+    #[test]
+    fn test_contains() {
+        let granted = set("read:* write:posts");
+        assert!(granted.contains("read:users"));
+        assert!(granted.contains("write:posts"));
+        assert!(!granted.contains("write:users"));
+    }
+
+    #[test]
+    fn test_is_subset_of() {
+        assert!(set("read:users write:posts").is_subset_of(&set("read:* write:posts")));
+        assert!(set("read:*").is_subset_of(&set("*:*")));
+        assert!(!set("read:*").is_subset_of(&set("read:users")));
+        assert!(set("").is_subset_of(&set("")));
+    }
+
+    #[test]
+    fn test_intersect() {
+        assert_eq!(set("read:* write:posts").intersect(&set("read:users admin:all")), set("read:users"));
+        assert_eq!(set("read:users").intersect(&set("write:users")), set(""));
+    }
+
+    #[test]
+    fn test_union() {
+        assert_eq!(set("read:users write:posts").union(&set("read:*")), set("write:posts read:*"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(set("read:users read:* write:posts").normalize(), set("read:* write:posts"));
+        assert_eq!(set("read:users * write:posts").normalize(), set("*"));
+    }
+
+    #[test]
+    fn test_down_scope() {
+        let granted = set("read:* write:posts");
+        assert_eq!(granted.down_scope(&set("read:users read:posts")).unwrap(), set("read:users read:posts"));
+        assert_eq!(
+            granted.down_scope(&set("read:users write:users admin:all")),
+            Err(AuthError::ScopeNotGranted("write:users admin:all".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_glob_matcher_set() {
+        let granted: ScopeSet<GlobMatcher> = ScopeSet::from_scope_string("billing:**");
+        assert!(granted.contains("billing:invoice:line:read"));
+        assert!(ScopeSet::<GlobMatcher>::from_scope_string("billing:invoice:*").is_subset_of(&granted));
+    }
+
+    fn token() -> impl Strategy<Value = String> {
+        prop_oneof![
+            1 => Just("*".to_string()),
+            10 => (
+                prop::sample::select(vec![":", "."]),
+                prop::collection::vec(prop::sample::select(vec!["a", "b", "c", "*"]), 1..4),
+            )
+                .prop_map(|(delim, parts)| parts.join(delim)),
+        ]
+    }
+
+    fn scope_string() -> impl Strategy<Value = String> {
+        prop::collection::vec(token(), 0..6).prop_map(|tokens| tokens.join(" "))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_contains_agrees_with_authorize(scopes in scope_string(), required in token()) {
+            prop_assert_eq!(
+                set(&scopes).contains(&required),
+                authorize_with_matcher::<FlexibleMatcher>(&scopes, &required)
+            );
+        }
+
+        #[test]
+        fn prop_normalize_preserves_coverage(scopes in scope_string(), required in token()) {
+            let normalized = set(&scopes).normalize();
+            prop_assert!(normalized.len() <= set(&scopes).len());
+            prop_assert_eq!(
+                normalized.contains(&required),
+                authorize_with_matcher::<FlexibleMatcher>(&scopes, &required)
+            );
+        }
+
+        #[test]
+        fn prop_union_covers_either(a in scope_string(), b in scope_string(), required in token()) {
+            prop_assert_eq!(
+                set(&a).union(&set(&b)).contains(&required),
+                authorize_with_matcher::<FlexibleMatcher>(&a, &required)
+                    || authorize_with_matcher::<FlexibleMatcher>(&b, &required)
+            );
+        }
+
+        #[test]
+        fn prop_intersect_covers_only_both(a in scope_string(), b in scope_string(), required in token()) {
+            if set(&a).intersect(&set(&b)).contains(&required) {
+                prop_assert!(authorize_with_matcher::<FlexibleMatcher>(&a, &required));
+                prop_assert!(authorize_with_matcher::<FlexibleMatcher>(&b, &required));
+            }
+        }
+
+        #[test]
+        fn prop_subset_implies_coverage(a in scope_string(), b in scope_string(), required in token()) {
+            if set(&a).is_subset_of(&set(&b)) && authorize_with_matcher::<FlexibleMatcher>(&a, &required) {
+                prop_assert!(authorize_with_matcher::<FlexibleMatcher>(&b, &required));
+            }
+        }
+
+        #[test]
+        fn prop_subset_of_union(a in scope_string(), b in scope_string()) {
+            let union = set(&a).union(&set(&b));
+            prop_assert!(set(&a).is_subset_of(&union));
+            prop_assert!(set(&b).is_subset_of(&union));
+        }
+    }
+}
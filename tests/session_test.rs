@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::session::{
+        AuthLevel, ClientInfo, InMemorySessionStore, SessionManager, SessionPolicy,
+    };
+    use auth_kit::clock::ManualClock;
+    use auth_kit::error::AuthError;
+    use auth_kit::model::{Role, User};
+    use std::sync::Arc;
+
+    fn user(email: &str) -> User {
+        User {
+            email: email.to_string(),
+            password_hash: "".to_string(),
+            role: Role { name: "".to_string(), permissions: vec![] },
+            department: "".to_string(),
+            clearance_level: 0,
+            ..Default::default()
+        }
+    }
+
+    fn manager(clock: &ManualClock) -> SessionManager {
+        let policy = SessionPolicy { idle_timeout: 600, absolute_timeout: 3_600 };
+        SessionManager::new(Box::new(InMemorySessionStore::new()), policy).with_clock(Arc::new(clock.clone()))
+    }
+
+    fn browser() -> ClientInfo {
+        ClientInfo {
+            ip_address: Some("203.0.113.7".to_string()),
+            user_agent: Some("Mozilla/5.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_create_and_validate() {
+        let clock = ManualClock::new(1_000);
+        let mut sessions = manager(&clock);
+        let (session_id, created) = sessions.create(&user("alice@example.com"), browser(), AuthLevel::SingleFactor).unwrap();
+        assert_eq!(session_id.len(), 43);
+        assert_ne!(created.id_hash, session_id);
+
+        clock.advance(120);
+        let session = sessions.validate(&session_id).unwrap();
+        assert_eq!(session.subject, "alice@example.com");
+        assert_eq!(session.created_at, 1_000);
+        assert_eq!(session.last_seen_at, 1_120);
+        assert_eq!(session.client, browser());
+        assert_eq!(session.auth_level, AuthLevel::SingleFactor);
+
+        assert_eq!(sessions.validate("unknown"), Err(AuthError::InvalidToken));
+    }
+
+    #[test]
+    fn test_idle_and_absolute_timeouts() {
+        let clock = ManualClock::new(1_000);
+        let mut sessions = manager(&clock);
+
+        let (idle, _) = sessions.create(&user("alice@example.com"), browser(), AuthLevel::SingleFactor).unwrap();
+        clock.advance(600);
+        assert_eq!(sessions.validate(&idle), Err(AuthError::SessionExpired));
+        assert_eq!(sessions.validate(&idle), Err(AuthError::InvalidToken));
+
+        let (busy, _) = sessions.create(&user("alice@example.com"), browser(), AuthLevel::SingleFactor).unwrap();
+        for _ in 0..6 {
+            clock.advance(590);
+            assert!(sessions.validate(&busy).is_ok());
+        }
+        clock.advance(590);
+        assert_eq!(sessions.validate(&busy), Err(AuthError::SessionExpired));
+    }
+
+    #[test]
+    fn test_rotation_on_privilege_change() {
+        let clock = ManualClock::new(1_000);
+        let mut sessions = manager(&clock);
+        let (session_id, _) = sessions.create(&user("alice@example.com"), browser(), AuthLevel::SingleFactor).unwrap();
+
+        clock.advance(60);
+        let (elevated_id, elevated) = sessions.elevate(&session_id, AuthLevel::MultiFactor).unwrap();
+        assert_ne!(elevated_id, session_id);
+        assert_eq!(elevated.auth_level, AuthLevel::MultiFactor);
+        assert_eq!(elevated.created_at, 1_000);
+        assert_eq!(sessions.validate(&session_id), Err(AuthError::InvalidToken));
+
+        let (rotated_id, rotated) = sessions.rotate(&elevated_id).unwrap();
+        assert_eq!(rotated.auth_level, AuthLevel::MultiFactor);
+        assert_eq!(sessions.validate(&elevated_id), Err(AuthError::InvalidToken));
+        assert!(sessions.validate(&rotated_id).is_ok());
+
+        let (_, lowered) = sessions.elevate(&rotated_id, AuthLevel::SingleFactor).unwrap();
+        assert_eq!(lowered.auth_level, AuthLevel::MultiFactor);
+    }
+
+    #[test]
+    fn test_list_and_revoke_user_sessions() {
+        let clock = ManualClock::new(1_000);
+        let mut sessions = manager(&clock);
+        let alice = user("alice@example.com");
+        let (laptop, _) = sessions.create(&alice, browser(), AuthLevel::SingleFactor).unwrap();
+        clock.advance(10);
+        let (phone, _) = sessions.create(&alice, ClientInfo::default(), AuthLevel::SingleFactor).unwrap();
+        let (bob, _) = sessions.create(&user("bob@example.com"), browser(), AuthLevel::SingleFactor).unwrap();
+
+        let listed = sessions.list("alice@example.com").unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].client, ClientInfo::default());
+
+        sessions.revoke_by_hash(&listed[1].id_hash).unwrap();
+        assert_eq!(sessions.validate(&laptop), Err(AuthError::InvalidToken));
+
+        sessions.revoke(&phone).unwrap();
+        assert!(sessions.list("alice@example.com").unwrap().is_empty());
+
+        sessions.create(&alice, browser(), AuthLevel::SingleFactor).unwrap();
+        sessions.create(&alice, browser(), AuthLevel::SingleFactor).unwrap();
+        assert_eq!(sessions.revoke_all("alice@example.com").unwrap(), 2);
+        assert!(sessions.validate(&bob).is_ok());
+    }
+
+    #[test]
+    fn test_expired_sessions_hidden_and_purged() {
+        let clock = ManualClock::new(1_000);
+        let mut sessions = manager(&clock);
+        let alice = user("alice@example.com");
+        sessions.create(&alice, browser(), AuthLevel::SingleFactor).unwrap();
+        clock.advance(500);
+        let (fresh, _) = sessions.create(&alice, browser(), AuthLevel::SingleFactor).unwrap();
+
+        clock.advance(200);
+        assert_eq!(sessions.list("alice@example.com").unwrap().len(), 1);
+        sessions.purge_expired().unwrap();
+        assert_eq!(sessions.revoke_all("alice@example.com").unwrap(), 1);
+        assert_eq!(sessions.validate(&fresh), Err(AuthError::InvalidToken));
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::strict_scope::{Scope, ScopeGrammar, StrictMatcher};
+    use auth_kit::error::{AuthError, ScopeError};
+
+    #[test]
+    fn test_parse_valid_scope() {
+        let scope = Scope::parse("user_service:user:read").unwrap();
+        assert_eq!(scope.segments(), ["user_service", "user", "read"]);
+        assert_eq!(scope.to_string(), "user_service:user:read");
+    }
+
+    #[test]
+    fn test_mixed_delimiters_are_not_split() {
+        assert_eq!(
+            Scope::parse("a.b:c"),
+            Err(ScopeError::InvalidCharacter { character: '.', position: 0 })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Scope::parse(""), Err(ScopeError::Empty));
+        assert_eq!(Scope::parse("user::read"), Err(ScopeError::EmptySegment { position: 1 }));
+        assert_eq!(Scope::parse("user:read:"), Err(ScopeError::EmptySegment { position: 2 }));
+        assert_eq!(
+            Scope::parse("user:re ad"),
+            Err(ScopeError::InvalidCharacter { character: ' ', position: 1 })
+        );
+        assert_eq!(
+            Scope::parse("a:b:c:d:e:f:g:h:i"),
+            Err(ScopeError::TooManySegments { max: 8, found: 9 })
+        );
+        assert_eq!(
+            Scope::parse(&format!("user:{}", "x".repeat(65))),
+            Err(ScopeError::SegmentTooLong { position: 1, max: 64 })
+        );
+    }
+
+    #[test]
+    fn test_custom_grammar() {
+        let grammar = ScopeGrammar {
+            min_segments: 2,
+            allow_wildcard: false,
+            ..ScopeGrammar::with_delimiter('.')
+        };
+        assert!(Scope::parse_with("admin_service.read", &grammar).is_ok());
+        assert_eq!(
+            Scope::parse_with("read", &grammar),
+            Err(ScopeError::TooFewSegments { min: 2, found: 1 })
+        );
+        assert_eq!(
+            Scope::parse_with("admin_service.*", &grammar),
+            Err(ScopeError::WildcardNotAllowed { position: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_all_reports_first_error() {
+        let grammar = ScopeGrammar::default();
+        assert_eq!(Scope::parse_all("user:read user:write", &grammar).unwrap().len(), 2);
+        assert_eq!(
+            Scope::parse_all("user:read user:wr!te", &grammar),
+            Err(ScopeError::InvalidCharacter { character: '!', position: 1 })
+        );
+    }
+
+    #[test]
+    fn test_scope_error_converts_to_auth_error() {
+        let err: AuthError = Scope::parse("").unwrap_err().into();
+        assert_eq!(err, AuthError::InvalidScope(ScopeError::Empty));
+        assert_eq!(err.to_string(), "Invalid scope: scope is empty");
+    }
+
+    #[test]
+    fn test_strict_matcher() {
+        let required: Scope = "user_service:user:read".parse().unwrap();
+        assert!(StrictMatcher::matches(&Scope::parse("user_service:user:read").unwrap(), &required));
+        assert!(StrictMatcher::matches(&Scope::parse("user_service:*:read").unwrap(), &required));
+        assert!(StrictMatcher::matches(&Scope::parse("*:*:read").unwrap(), &required));
+        assert!(!StrictMatcher::matches(&Scope::parse("user_service:*").unwrap(), &required));
+        assert!(!StrictMatcher::matches(&Scope::parse("user_service:user:write").unwrap(), &required));
+    }
+
+    #[test]
+    fn test_strict_matcher_requires_same_delimiter() {
+        let dotted = Scope::parse_with("user.read", &ScopeGrammar::with_delimiter('.')).unwrap();
+        let coloned = Scope::parse("user:read").unwrap();
+        assert!(!StrictMatcher::matches(&dotted, &coloned));
+    }
+
+    #[test]
+    fn test_strict_matcher_authorize() {
+        let granted = Scope::parse_all("user:read post:*", &ScopeGrammar::default()).unwrap();
+        assert!(StrictMatcher::authorize(&granted, &Scope::parse("post:delete").unwrap()));
+        assert!(!StrictMatcher::authorize(&granted, &Scope::parse("user:write").unwrap()));
+    }
+}
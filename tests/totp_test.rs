@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::auth::auth_n::Authentication;
+    use auth_kit::clock::{Clock, ManualClock};
+    use auth_kit::error::AuthError;
+    use auth_kit::mfa::login::{LoginOutcome, MfaLogins, SecondFactor};
+    use auth_kit::mfa::hotp::{hotp, HotpAuthenticator, HotpConfig, OtpAlgorithm};
+    use auth_kit::mfa::recovery::RecoveryCodePolicy;
+    use auth_kit::mfa::totp::{TotpAuthenticator, TotpConfig, TotpSetup};
+    use auth_kit::model::{Role, User};
+    use data_encoding::BASE32_NOPAD;
+    use std::sync::Arc;
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn authenticator(clock: &ManualClock) -> TotpAuthenticator {
+        TotpAuthenticator::new(TotpConfig { issuer: "Acme Corp".to_string(), ..TotpConfig::default() })
+            .with_clock(Arc::new(clock.clone()))
+    }
+
+    fn code(totp: &TotpAuthenticator, setup: &TotpSetup, time: u64) -> String {
+        totp.config().code_at(&BASE32_NOPAD.decode(setup.secret.as_bytes()).unwrap(), time)
+    }
+
+    fn enrolled(clock: &ManualClock) -> (TotpAuthenticator, TotpSetup) {
+        let mut totp = authenticator(clock);
+        let setup = totp.begin_enrollment(EMAIL);
+        let confirmation = code(&totp, &setup, clock.now());
+        totp.confirm_enrollment(EMAIL, &confirmation).unwrap();
+        (totp, setup)
+    }
+
+    #[test]
+    fn test_rfc_vectors() {
+        assert_eq!(hotp(b"12345678901234567890", 0, 6, OtpAlgorithm::Sha1), "755224");
+        assert_eq!(hotp(b"12345678901234567890", 9, 6, OtpAlgorithm::Sha1), "520489");
+
+        let sha1 = TotpConfig { digits: 8, ..TotpConfig::default() };
+        assert_eq!(sha1.code_at(b"12345678901234567890", 59), "94287082");
+        assert_eq!(sha1.code_at(b"12345678901234567890", 1_111_111_109), "07081804");
+        let sha256 = TotpConfig { digits: 8, algorithm: OtpAlgorithm::Sha256, ..TotpConfig::default() };
+        assert_eq!(sha256.code_at(b"12345678901234567890123456789012", 59), "46119246");
+        let sha512 = TotpConfig { digits: 8, algorithm: OtpAlgorithm::Sha512, ..TotpConfig::default() };
+        let secret = b"1234567890123456789012345678901234567890123456789012345678901234";
+        assert_eq!(sha512.code_at(secret, 59), "90693936");
+    }
+
+    #[test]
+    fn test_enrollment_and_provisioning_uri() {
+        let clock = ManualClock::new(1_700_000_000);
+        let mut totp = authenticator(&clock);
+        let setup = totp.begin_enrollment(EMAIL);
+
+        assert_eq!(setup.secret.len(), 32);
+        assert_eq!(
+            setup.uri,
+            format!(
+                "otpauth://totp/Acme%20Corp:alice%40example.com?secret={}&issuer=Acme%20Corp&algorithm=SHA1&digits=6&period=30",
+                setup.secret
+            )
+        );
+        assert!(!totp.is_enrolled(EMAIL));
+        assert_eq!(totp.verify(EMAIL, "000000"), Err(AuthError::MfaNotEnrolled));
+
+        assert_eq!(totp.confirm_enrollment(EMAIL, "12345"), Err(AuthError::InvalidOtp));
+        let wrong = code(&totp, &setup, 1_700_000_000 + 300);
+        assert_eq!(totp.confirm_enrollment(EMAIL, &wrong), Err(AuthError::InvalidOtp));
+        totp.confirm_enrollment(EMAIL, &code(&totp, &setup, 1_700_000_000)).unwrap();
+        assert!(totp.is_enrolled(EMAIL));
+        assert!(totp.enrollment(EMAIL).unwrap().confirmed);
+        assert_eq!(totp.confirm_enrollment(EMAIL, "000000"), Err(AuthError::MfaNotEnrolled));
+
+        totp.disable(EMAIL);
+        assert!(!totp.is_enrolled(EMAIL));
+    }
+
+    #[test]
+    fn test_window_and_replay() {
+        let clock = ManualClock::new(1_700_000_010);
+        let (mut totp, setup) = enrolled(&clock);
+
+        let now = 1_700_000_010;
+        assert_eq!(totp.verify(EMAIL, &code(&totp, &setup, now)), Err(AuthError::OtpReplayed));
+
+        clock.advance(60);
+        let previous = code(&totp, &setup, now + 30);
+        let current = code(&totp, &setup, now + 60);
+        let too_old = code(&totp, &setup, now);
+        let too_new = code(&totp, &setup, now + 120);
+        assert_eq!(totp.verify(EMAIL, &too_new), Err(AuthError::InvalidOtp));
+        totp.verify(EMAIL, &previous).unwrap();
+        assert_eq!(totp.verify(EMAIL, &previous), Err(AuthError::OtpReplayed));
+        totp.verify(EMAIL, &current).unwrap();
+        assert!(totp.verify(EMAIL, &too_old).is_err());
+    }
+
+    #[test]
+    fn test_reenrollment_keeps_old_secret_until_confirmed() {
+        let clock = ManualClock::new(1_700_000_000);
+        let (mut totp, old) = enrolled(&clock);
+        let new = totp.begin_enrollment(EMAIL);
+        assert_ne!(old.secret, new.secret);
+
+        clock.advance(30);
+        totp.verify(EMAIL, &code(&totp, &old, 1_700_000_030)).unwrap();
+        clock.advance(30);
+        totp.confirm_enrollment(EMAIL, &code(&totp, &new, 1_700_000_060)).unwrap();
+        clock.advance(30);
+        assert_eq!(totp.verify(EMAIL, &code(&totp, &old, 1_700_000_090)), Err(AuthError::InvalidOtp));
+    }
+
+    #[test]
+    fn test_login_requires_second_factor() {
+        let clock = ManualClock::new(1_700_000_000);
+        let (mut totp, setup) = enrolled(&clock);
+        let mut logins = MfaLogins::new(300).with_clock(Arc::new(clock.clone()));
+        let user = User {
+            email: EMAIL.to_string(),
+            password_hash: "".to_string(),
+            role: Role { name: "".to_string(), permissions: vec![] },
+            department: "".to_string(),
+            clearance_level: 0,
+            ..Default::default()
+        };
+
+        let challenge = match logins.begin(user.clone(), &[&totp]) {
+            LoginOutcome::MfaRequired(challenge) => challenge,
+            LoginOutcome::Authenticated(_) => panic!("expected MFA to be required"),
+        };
+        assert_eq!(challenge.subject, EMAIL);
+        assert_eq!(challenge.expires_at, 1_700_000_300);
+
+        clock.advance(30);
+        let result = logins.complete(&challenge.token, &mut totp, "000000");
+        assert_eq!(result.unwrap_err(), AuthError::InvalidOtp);
+        let valid = code(&totp, &setup, 1_700_000_030);
+        let user = logins.complete(&challenge.token, &mut totp, &valid).unwrap();
+        assert_eq!(user.email, EMAIL);
+        assert_eq!(logins.complete(&challenge.token, &mut totp, &valid).unwrap_err(), AuthError::InvalidToken);
+
+        let LoginOutcome::MfaRequired(expired) = logins.begin(user.clone(), &[&totp]) else {
+            panic!("expected MFA to be required");
+        };
+        clock.advance(300);
+        let valid = code(&totp, &setup, clock.now());
+        assert_eq!(logins.complete(&expired.token, &mut totp, &valid).unwrap_err(), AuthError::InvalidToken);
+
+        totp.disable(EMAIL);
+        assert!(matches!(logins.begin(user, &[&totp]), LoginOutcome::Authenticated(_)));
+    }
+
+    #[test]
+    fn test_login_accepts_any_enrolled_factor() {
+        let clock = ManualClock::new(1_700_000_000);
+        let totp = authenticator(&clock);
+        let mut hotp = HotpAuthenticator::new(HotpConfig::default());
+        let mut auth = Authentication::new();
+        auth.register(EMAIL, "hash").unwrap();
+        let policy = RecoveryCodePolicy { count: 2, cost: 4, ..RecoveryCodePolicy::default() };
+        let codes = auth.regenerate_recovery_codes(EMAIL, &policy).unwrap();
+        let user = auth.login(EMAIL).unwrap().unwrap();
+        let mut logins = MfaLogins::new(300).with_clock(Arc::new(clock.clone()));
+
+        assert!(matches!(logins.begin(User { email: EMAIL.to_string(), ..Default::default() }, &[&totp, &hotp]), LoginOutcome::Authenticated(_)));
+
+        let LoginOutcome::MfaRequired(challenge) = logins.begin(user.clone(), &[&totp, &hotp, &auth]) else {
+            panic!("expected MFA to be required");
+        };
+        assert_eq!(logins.complete(&challenge.token, &mut hotp, "755224").unwrap_err(), AuthError::MfaNotEnrolled);
+        assert_eq!(logins.complete(&challenge.token, &mut auth, &codes[0]).unwrap().email, EMAIL);
+
+        let LoginOutcome::MfaRequired(challenge) = logins.begin(user.clone(), &[&totp, &hotp, &auth]) else {
+            panic!("expected MFA to be required");
+        };
+        assert_eq!(logins.complete(&challenge.token, &mut auth, &codes[0]).unwrap_err(), AuthError::RecoveryCodeUsed);
+
+        hotp.register(EMAIL, b"12345678901234567890".to_vec(), 0);
+        let LoginOutcome::MfaRequired(challenge) = logins.begin(user, &[&hotp]) else {
+            panic!("expected MFA to be required");
+        };
+        assert_eq!(logins.complete(&challenge.token, &mut hotp, "755224").unwrap().email, EMAIL);
+    }
+
+    struct FixedFactor(bool);
+
+    impl SecondFactor for FixedFactor {
+        fn is_enrolled(&self, _: &User) -> bool {
+            true
+        }
+
+        fn verify(&mut self, _: &User, _: &str) -> Result<(), AuthError> {
+            if self.0 { Ok(()) } else { Err(AuthError::InvalidOtp) }
+        }
+    }
+
+    #[test]
+    fn test_login_attempts_are_limited_across_challenges() {
+        let clock = ManualClock::new(1_700_000_000);
+        let mut logins = MfaLogins::new(300).with_max_attempts(3).with_clock(Arc::new(clock.clone()));
+        let user = User { email: EMAIL.to_string(), ..Default::default() };
+        let begin = |logins: &mut MfaLogins| match logins.begin(user.clone(), &[&FixedFactor(true)]) {
+            LoginOutcome::MfaRequired(challenge) => challenge.token,
+            LoginOutcome::Authenticated(_) => panic!("expected MFA to be required"),
+        };
+        let (mut right, mut wrong) = (FixedFactor(true), FixedFactor(false));
+
+        let first = begin(&mut logins);
+        for _ in 0..2 {
+            assert_eq!(logins.complete(&first, &mut wrong, "000000").unwrap_err(), AuthError::InvalidOtp);
+        }
+        let second = begin(&mut logins);
+        assert_eq!(logins.complete(&second, &mut wrong, "000000").unwrap_err(), AuthError::InvalidOtp);
+        assert_eq!(logins.complete(&second, &mut right, "000000").unwrap_err(), AuthError::InvalidToken);
+        assert_eq!(logins.complete(&first, &mut right, "000000").unwrap_err(), AuthError::TooManyAttempts { retry_at: 1_700_000_300 });
+        let third = begin(&mut logins);
+        assert_eq!(logins.complete(&third, &mut right, "000000").unwrap_err(), AuthError::TooManyAttempts { retry_at: 1_700_000_300 });
+
+        clock.advance(300);
+        let fourth = begin(&mut logins);
+        assert_eq!(logins.complete(&fourth, &mut right, "000000").unwrap().email, EMAIL);
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_qr_code_svg() {
+        let clock = ManualClock::new(1_700_000_000);
+        let setup = authenticator(&clock).begin_enrollment(EMAIL);
+        let svg = setup.qr_code_svg().unwrap();
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("<svg"));
+    }
+}
@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod tests {
+    use auth_kit::error::AuthError;
+    use auth_kit::jose::{JwsAlgorithm, SigningKey};
+    use auth_kit::model::{Role, User};
+    use auth_kit::webauthn::cbor::{self, Value};
+    use auth_kit::webauthn::cose::{cose_algorithm, jwk_to_cose};
+    use auth_kit::webauthn::data::{FLAG_ATTESTED_CREDENTIAL_DATA, FLAG_USER_PRESENT, FLAG_USER_VERIFIED};
+    use auth_kit::webauthn::relying_party::{AssertionResponse, RegistrationResponse, WebAuthn, WebAuthnConfig};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    const RP_ID: &str = "example.com";
+    const ORIGIN: &str = "https://example.com";
+
+    fn user(email: &str) -> User {
+        User {
+            email: email.to_string(),
+            password_hash: "".to_string(),
+            role: Role { name: "".to_string(), permissions: vec![] },
+            department: "".to_string(),
+            clearance_level: 0,
+            ..Default::default()
+        }
+    }
+
+    fn relying_party() -> WebAuthn {
+        WebAuthn::new(WebAuthnConfig {
+            rp_id: RP_ID.to_string(),
+            rp_name: "Example".to_string(),
+            origin: ORIGIN.to_string(),
+            ..WebAuthnConfig::default()
+        })
+    }
+
+    /// A software authenticator producing the structures a security key would.
+    struct Authenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        sign_count: u32,
+    }
+
+    impl Authenticator {
+        fn new(algorithm: JwsAlgorithm, id: u8) -> Self {
+            Self { key: SigningKey::generate(algorithm, "authenticator"), credential_id: vec![id; 16], sign_count: 0 }
+        }
+
+        fn id(&self) -> String {
+            URL_SAFE_NO_PAD.encode(&self.credential_id)
+        }
+
+        fn client_data(ceremony: &str, challenge: &str, origin: &str) -> Vec<u8> {
+            format!(r#"{{"type":"{}","challenge":"{}","origin":"{}"}}"#, ceremony, challenge, origin).into_bytes()
+        }
+
+        fn auth_data(&self, flags: u8, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+            data.push(flags | if attested { FLAG_ATTESTED_CREDENTIAL_DATA } else { 0 });
+            data.extend(self.sign_count.to_be_bytes());
+            if attested {
+                data.extend([0u8; 16]);
+                data.extend((self.credential_id.len() as u16).to_be_bytes());
+                data.extend(&self.credential_id);
+                data.extend(cbor::encode(&jwk_to_cose(&self.key.public_jwk()).unwrap()));
+            }
+            data
+        }
+
+        /// Signs as WebAuthn does: ES256 signatures are DER-encoded.
+        fn sign(&self, auth_data: &[u8], client_data: &[u8]) -> Vec<u8> {
+            let mut message = auth_data.to_vec();
+            message.extend(Sha256::digest(client_data));
+            let raw = self.key.sign(&message);
+            match self.key.algorithm() {
+                JwsAlgorithm::ES256 => p256::ecdsa::Signature::from_slice(&raw).unwrap().to_der().as_bytes().to_vec(),
+                JwsAlgorithm::EdDSA => raw,
+            }
+        }
+
+        fn register(&self, challenge: &str, format: &str) -> RegistrationResponse {
+            let client_data = Self::client_data("webauthn.create", challenge, ORIGIN);
+            let auth_data = self.auth_data(FLAG_USER_PRESENT | FLAG_USER_VERIFIED, true);
+            let statement = match format {
+                "packed" => Value::Map(vec![
+                    ("alg".into(), cose_algorithm(self.key.algorithm()).into()),
+                    ("sig".into(), Value::Bytes(self.sign(&auth_data, &client_data))),
+                ]),
+                _ => Value::Map(vec![]),
+            };
+            let attestation = Value::Map(vec![
+                ("fmt".into(), format.into()),
+                ("attStmt".into(), statement),
+                ("authData".into(), Value::Bytes(auth_data)),
+            ]);
+            RegistrationResponse {
+                credential_id: self.id(),
+                client_data_json: URL_SAFE_NO_PAD.encode(&client_data),
+                attestation_object: URL_SAFE_NO_PAD.encode(cbor::encode(&attestation)),
+            }
+        }
+
+        fn assert(&mut self, challenge: &str, origin: &str) -> AssertionResponse {
+            self.sign_count += 1;
+            let client_data = Self::client_data("webauthn.get", challenge, origin);
+            let auth_data = self.auth_data(FLAG_USER_PRESENT, false);
+            AssertionResponse {
+                credential_id: self.id(),
+                signature: URL_SAFE_NO_PAD.encode(self.sign(&auth_data, &client_data)),
+                client_data_json: URL_SAFE_NO_PAD.encode(&client_data),
+                authenticator_data: URL_SAFE_NO_PAD.encode(&auth_data),
+                user_handle: None,
+            }
+        }
+    }
+
+    fn invalid(result: Result<impl std::fmt::Debug, AuthError>) -> String {
+        match result.unwrap_err() {
+            AuthError::InvalidWebAuthnResponse(reason) => reason,
+            other => panic!("expected InvalidWebAuthnResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_register_and_authenticate_with_both_algorithms() {
+        let mut webauthn = relying_party();
+        let alice = user("alice@example.com");
+
+        for (algorithm, format, id) in [(JwsAlgorithm::ES256, "none", 1), (JwsAlgorithm::EdDSA, "packed", 2)] {
+            let mut authenticator = Authenticator::new(algorithm, id);
+            let options = webauthn.start_registration(&alice);
+            assert_eq!(options.rp_id, RP_ID);
+            assert_eq!(options.algorithms, vec![-7, -8]);
+            assert_ne!(options.user_id, alice.email);
+            let options_user_id = options.user_id.clone();
+
+            let credential = webauthn.finish_registration(&authenticator.register(&options.challenge, format)).unwrap();
+            assert_eq!(credential.subject, alice.email);
+            assert_eq!(credential.attestation_format, format);
+
+            let options = webauthn.start_authentication(None);
+            let mut assertion = authenticator.assert(&options.challenge, ORIGIN);
+            assertion.user_handle = Some(options_user_id.clone());
+            let credential = webauthn.finish_authentication(&assertion).unwrap();
+            assert_eq!(credential.subject, alice.email);
+            assert_eq!(credential.sign_count, 1);
+            assert!(credential.last_used_at.is_some());
+        }
+
+        let options = webauthn.start_authentication(Some(&alice.email));
+        assert_eq!(options.allow_credentials.len(), 2);
+        assert_eq!(webauthn.start_registration(&alice).exclude_credentials.len(), 2);
+    }
+
+    #[test]
+    fn test_packed_attestation_signature_is_checked() {
+        let mut webauthn = relying_party();
+        let authenticator = Authenticator::new(JwsAlgorithm::ES256, 1);
+        let options = webauthn.start_registration(&user("alice@example.com"));
+
+        let mut response = authenticator.register(&options.challenge, "packed");
+        let forged = Authenticator::new(JwsAlgorithm::ES256, 1).register(&options.challenge, "packed");
+        let mut attestation = cbor::decode(&URL_SAFE_NO_PAD.decode(&response.attestation_object).unwrap()).unwrap();
+        let forged = cbor::decode(&URL_SAFE_NO_PAD.decode(&forged.attestation_object).unwrap()).unwrap();
+        if let Value::Map(entries) = &mut attestation {
+            entries[1].1 = forged.get(&"attStmt".into()).unwrap().clone();
+        }
+        response.attestation_object = URL_SAFE_NO_PAD.encode(cbor::encode(&attestation));
+
+        assert!(invalid(webauthn.finish_registration(&response)).contains("signature"));
+    }
+
+    #[test]
+    fn test_rejects_wrong_origin_and_replayed_challenge() {
+        let mut webauthn = relying_party();
+        let alice = user("alice@example.com");
+        let mut authenticator = Authenticator::new(JwsAlgorithm::ES256, 1);
+        let options = webauthn.start_registration(&alice);
+        webauthn.finish_registration(&authenticator.register(&options.challenge, "none")).unwrap();
+
+        let options = webauthn.start_authentication(Some(&alice.email));
+        let phished = authenticator.assert(&options.challenge, "https://examp1e.com");
+        assert_eq!(invalid(webauthn.finish_authentication(&phished)), "unexpected origin");
+
+        let assertion = authenticator.assert(&options.challenge, ORIGIN);
+        webauthn.finish_authentication(&assertion).unwrap();
+        assert_eq!(invalid(webauthn.finish_authentication(&assertion)), "unknown or expired challenge");
+
+        let unissued = authenticator.assert("not-a-challenge", ORIGIN);
+        assert_eq!(invalid(webauthn.finish_authentication(&unissued)), "unknown or expired challenge");
+
+        let options = webauthn.start_registration(&alice);
+        let duplicate = authenticator.register(&options.challenge, "none");
+        assert_eq!(invalid(webauthn.finish_registration(&duplicate)), "credential is already registered");
+    }
+
+    #[test]
+    fn test_sign_count_regression_is_detected() {
+        let mut webauthn = relying_party();
+        let mut authenticator = Authenticator::new(JwsAlgorithm::EdDSA, 1);
+        let options = webauthn.start_registration(&user("alice@example.com"));
+        webauthn.finish_registration(&authenticator.register(&options.challenge, "none")).unwrap();
+
+        authenticator.sign_count = 10;
+        let options = webauthn.start_authentication(None);
+        webauthn.finish_authentication(&authenticator.assert(&options.challenge, ORIGIN)).unwrap();
+
+        authenticator.sign_count = 5;
+        let options = webauthn.start_authentication(None);
+        let cloned = authenticator.assert(&options.challenge, ORIGIN);
+        assert_eq!(webauthn.finish_authentication(&cloned), Err(AuthError::SignCountRegression(authenticator.id())));
+        assert_eq!(webauthn.credential(&authenticator.id()).unwrap().sign_count, 11);
+    }
+
+    #[test]
+    fn test_rejects_unknown_credential_and_foreign_user() {
+        let mut webauthn = relying_party();
+        let mut alice_key = Authenticator::new(JwsAlgorithm::ES256, 1);
+        let options = webauthn.start_registration(&user("alice@example.com"));
+        webauthn.finish_registration(&alice_key.register(&options.challenge, "none")).unwrap();
+
+        let mut stranger = Authenticator::new(JwsAlgorithm::ES256, 9);
+        let options = webauthn.start_authentication(None);
+        assert_eq!(invalid(webauthn.finish_authentication(&stranger.assert(&options.challenge, ORIGIN))), "unknown credential");
+
+        let options = webauthn.start_authentication(Some("bob@example.com"));
+        let assertion = alice_key.assert(&options.challenge, ORIGIN);
+        assert_eq!(invalid(webauthn.finish_authentication(&assertion)), "credential belongs to another user");
+
+        webauthn.remove_credential(&alice_key.id()).unwrap();
+        let options = webauthn.start_authentication(None);
+        assert_eq!(invalid(webauthn.finish_authentication(&alice_key.assert(&options.challenge, ORIGIN))), "unknown credential");
+    }
+
+    #[test]
+    fn test_user_handles_are_random_and_stored_with_credentials() {
+        let mut webauthn = relying_party();
+        let alice = user("alice@example.com");
+        let mut authenticator = Authenticator::new(JwsAlgorithm::ES256, 1);
+        assert_eq!(webauthn.user_handle(&alice.email), None);
+
+        let options = webauthn.start_registration(&alice);
+        assert_eq!(webauthn.start_registration(&alice).user_id, options.user_id);
+        assert_ne!(relying_party().start_registration(&alice).user_id, options.user_id);
+        let credential = webauthn.finish_registration(&authenticator.register(&options.challenge, "none")).unwrap();
+        assert_eq!(credential.user_handle, options.user_id);
+        assert_eq!(webauthn.user_handle(&alice.email), Some(options.user_id.as_str()));
+
+        let challenge = webauthn.start_authentication(None).challenge;
+        let mut assertion = authenticator.assert(&challenge, ORIGIN);
+        assertion.user_handle = Some(URL_SAFE_NO_PAD.encode(Sha256::digest(alice.email.as_bytes())));
+        assert_eq!(invalid(webauthn.finish_authentication(&assertion)), "user handle does not match credential");
+
+        let mut restored = relying_party();
+        restored.insert_credential(credential);
+        assert_eq!(restored.user_handle(&alice.email), Some(options.user_id.as_str()));
+        assert_eq!(restored.start_registration(&alice).user_id, options.user_id);
+        let challenge = restored.start_authentication(None).challenge;
+        let mut assertion = authenticator.assert(&challenge, ORIGIN);
+        assertion.user_handle = Some(options.user_id.clone());
+        assert!(restored.finish_authentication(&assertion).is_ok());
+    }
+}